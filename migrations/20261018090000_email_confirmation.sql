@@ -0,0 +1,18 @@
+create table if not exists email_confirmation_token
+(
+    id           serial
+        primary key,
+    user_id      integer   not null
+        references "user"
+            on delete cascade,
+    token_hash   varchar   not null
+        unique,
+    expires_at   timestamp not null,
+    used_at      timestamp,
+    created_date timestamp not null
+);
+
+alter table email_confirmation_token
+    owner to postgres;
+
+CREATE INDEX email_confirmation_token_user_id_idx ON email_confirmation_token(user_id);
@@ -0,0 +1,18 @@
+create table if not exists password_reset_token
+(
+    id           serial
+        primary key,
+    user_id      integer   not null
+        references "user"
+            on delete cascade,
+    token_hash   varchar   not null
+        unique,
+    expires_at   timestamp not null,
+    used_at      timestamp,
+    created_date timestamp not null
+);
+
+alter table password_reset_token
+    owner to postgres;
+
+CREATE INDEX password_reset_token_user_id_idx ON password_reset_token(user_id, created_date);
@@ -0,0 +1,15 @@
+-- sessions created before the store existed carry no refresh token and can't be resumed
+DELETE FROM active_session;
+
+ALTER TABLE active_session DROP CONSTRAINT active_session_user_id_key;
+
+ALTER TABLE active_session
+    ADD COLUMN refresh_token_hash varchar   not null unique,
+    ADD COLUMN user_agent         varchar,
+    ADD COLUMN ip_address         varchar,
+    ADD COLUMN created_date       timestamp not null,
+    ADD COLUMN last_seen_date     timestamp not null,
+    ADD COLUMN expires_at         timestamp not null,
+    ADD COLUMN revoked_at         timestamp;
+
+CREATE INDEX active_session_user_id_idx ON active_session(user_id);
@@ -0,0 +1,26 @@
+create table if not exists role_permission
+(
+    role_id    integer not null
+        references role
+            on delete cascade,
+    permission varchar not null,
+    primary key (role_id, permission)
+);
+
+alter table role_permission
+    owner to postgres;
+
+-- default grants for databases that already hold the seeded roles (fresh databases get them from script.sql)
+INSERT INTO role_permission (role_id, permission)
+SELECT role.id, grants.permission
+FROM role
+         JOIN (VALUES ('Admin', 'invoice:create'),
+                      ('Admin', 'invoice:delete'),
+                      ('Admin', 'user:manage'),
+                      ('Admin', 'group:admin'),
+                      ('Manager', 'invoice:create'),
+                      ('Manager', 'invoice:delete'),
+                      ('Manager', 'group:admin'),
+                      ('User', 'invoice:create')) AS grants(role_name, permission)
+              ON grants.role_name = role.name
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,43 @@
+alter table meal
+    add column if not exists name        varchar,
+    add column if not exists description varchar not null default '',
+    add column if not exists group_id    integer
+        references "group";
+
+alter table meal_product
+    add column if not exists position integer;
+
+-- existing meals are named after their products and owned by the group that invoiced them
+update meal
+set name = coalesce((select string_agg(p.name, ', ' order by p.name)
+                     from meal_product mp
+                              join product p on p.id = mp.product_id
+                     where mp.meal_id = meal.id), 'Meal ' || meal.id)
+where name is null;
+
+update meal
+set group_id = coalesce((select min(invoice.group_id) from invoice where invoice.meal_id = meal.id),
+                        (select min(p.group_id)
+                         from meal_product mp
+                                  join product p on p.id = mp.product_id
+                         where mp.meal_id = meal.id))
+where group_id is null;
+
+-- a meal with neither products nor invoices can't be attributed to a group and is unusable anyway
+delete from meal where group_id is null;
+
+update meal_product
+set position = ordered.position
+from (select meal_id, product_id, row_number() over (partition by meal_id order by product_id) - 1 as position
+      from meal_product) as ordered
+where ordered.meal_id = meal_product.meal_id
+  and ordered.product_id = meal_product.product_id;
+
+alter table meal
+    alter column name set not null,
+    alter column group_id set not null;
+
+alter table meal_product
+    alter column position set not null;
+
+create index if not exists meal_group_id_idx on meal (group_id);
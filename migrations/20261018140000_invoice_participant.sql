@@ -0,0 +1,14 @@
+create table if not exists invoice_participant
+(
+    invoice_id integer not null
+        references invoice,
+    user_id    integer not null
+        references "user",
+    amount     bigint  not null,
+    primary key (invoice_id, user_id)
+);
+
+alter table invoice_participant
+    owner to postgres;
+
+create index invoice_participant_user_id_idx on invoice_participant (user_id);
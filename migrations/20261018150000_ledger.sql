@@ -0,0 +1,83 @@
+-- balances are sums of bigint postings, so they get the same range
+alter table "user"
+    alter column balance type bigint;
+alter table supplier
+    alter column balance type bigint;
+alter table customer
+    alter column balance type bigint;
+
+create table if not exists ledger_transaction
+(
+    id           serial
+        primary key,
+    kind         varchar   not null
+        constraint ledger_transaction_kind_check
+            check (kind in ('invoice', 'payment', 'adjustment', 'reversal')),
+    description  varchar   not null,
+    group_id     integer   not null
+        references "group",
+    invoice_id   integer
+        references invoice,
+    created_date timestamp not null
+);
+
+alter table ledger_transaction
+    owner to postgres;
+
+-- amount > 0 credits the user (the group owes them), amount < 0 debits them;
+-- a null user_id is the group's own account, the counterpart of opening balances
+create table if not exists ledger_entry
+(
+    id             serial
+        primary key,
+    transaction_id integer not null
+        references ledger_transaction,
+    user_id        integer
+        references "user",
+    amount         bigint  not null
+);
+
+alter table ledger_entry
+    owner to postgres;
+
+create index ledger_transaction_group_id_idx on ledger_transaction (group_id);
+create index ledger_transaction_invoice_id_idx on ledger_transaction (invoice_id);
+create index ledger_entry_transaction_id_idx on ledger_entry (transaction_id);
+create index ledger_entry_user_id_idx on ledger_entry (user_id);
+
+-- opening balances for existing users, one adjustment per group
+-- (fresh databases get theirs from script.sql)
+insert into ledger_transaction (kind, description, group_id, created_date)
+select 'adjustment', 'Opening balance', g.id, now()
+from "group" g
+where exists (select 1 from "user" u where u.group_id = g.id and u.balance <> 0);
+
+insert into ledger_entry (transaction_id, user_id, amount)
+select t.id, u.id, u.balance
+from ledger_transaction t
+         join "user" u on u.group_id = t.group_id and u.balance <> 0;
+
+insert into ledger_entry (transaction_id, user_id, amount)
+select t.id, null, -sum(u.balance)
+from ledger_transaction t
+         join "user" u on u.group_id = t.group_id
+group by t.id
+having sum(u.balance) <> 0;
+
+-- checked at commit, once every entry of the transaction is in
+create or replace function ledger_transaction_balanced() returns trigger
+    language plpgsql as
+$$
+begin
+    if (select coalesce(sum(amount), 0) from ledger_entry where transaction_id = new.transaction_id) <> 0 then
+        raise exception 'ledger transaction % does not balance', new.transaction_id;
+    end if;
+    return null;
+end;
+$$;
+
+create constraint trigger ledger_entry_balanced
+    after insert or update on ledger_entry
+    deferrable initially deferred
+    for each row
+execute function ledger_transaction_balanced();
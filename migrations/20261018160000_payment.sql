@@ -0,0 +1,37 @@
+create table if not exists payment
+(
+    id            serial
+        primary key,
+    payer_id      integer   not null
+        references "user",
+    payee_id      integer   not null
+        references "user",
+    group_id      integer   not null
+        references "group",
+    amount        bigint    not null
+        constraint payment_amount_check
+            check (amount > 0),
+    method        varchar   not null
+        constraint payment_method_check
+            check (method in ('cash', 'bank_transfer', 'other')),
+    note          varchar   not null default '',
+    status        varchar   not null default 'pending'
+        constraint payment_status_check
+            check (status in ('pending', 'confirmed', 'rejected')),
+    created_date  timestamp not null,
+    resolved_date timestamp,
+    constraint payment_payer_payee_check
+        check (payer_id <> payee_id)
+);
+
+alter table payment
+    owner to postgres;
+
+create index payment_group_id_idx on payment (group_id);
+
+-- the payment a payment-kind transaction books, if it came from one
+alter table ledger_transaction
+    add column payment_id integer
+        references payment;
+
+create index ledger_transaction_payment_id_idx on ledger_transaction (payment_id);
@@ -0,0 +1,7 @@
+-- optimistic concurrency for rows without a last_modification_date: every edit bumps the counter
+-- and only applies if the row still has the version the client read
+alter table "user"
+    add column version integer default 1 not null;
+
+alter table meal
+    add column version integer default 1 not null;
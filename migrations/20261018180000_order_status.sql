@@ -0,0 +1,6 @@
+alter table "order"
+    add column status varchar not null default 'draft'
+        constraint order_status_check
+            check (status in ('draft', 'open', 'locked', 'fulfilled', 'invoiced', 'cancelled'));
+
+create index order_group_id_idx on "order" (group_id);
@@ -0,0 +1,17 @@
+-- who ordered each line, so the order can be billed line by line
+alter table order_details
+    add column customer_id integer
+        references customer;
+
+-- the supplier a stock row was bought from, which an order's lines are billed to
+alter table stock
+    add column supplier_id integer
+        references supplier;
+
+-- the order an invoice bills, if it was made from one
+alter table invoice
+    add column order_id integer
+        references "order";
+
+create index order_details_order_id_idx on order_details (order_id);
+create index invoice_order_id_idx on invoice (order_id);
@@ -0,0 +1,8 @@
+-- ordering rounds: orders a manager opens for a date, filled by members until the cut-off
+alter table "order"
+    add column created_by integer
+        references "user",
+    add column round_date date,
+    add column cutoff     timestamp;
+
+create index order_cutoff_idx on "order" (cutoff) where status = 'open';
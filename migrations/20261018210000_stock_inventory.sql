@@ -0,0 +1,48 @@
+-- a product can be bought many times, so stock rows are lots rather than one row per product
+alter table stock
+    drop constraint if exists stock_product_id_key;
+
+create index if not exists stock_product_id_idx on stock (product_id);
+
+-- how much each lot holds and how much of it is left; existing rows were single items
+alter table stock
+    add column unit           varchar        not null default 'piece'
+        constraint stock_unit_check
+            check (unit in ('piece', 'g', 'kg', 'ml', 'l')),
+    add column quantity       numeric(14, 3) not null default 1
+        constraint stock_quantity_check
+            check (quantity > 0),
+    add column remaining      numeric(14, 3) not null default 1
+        constraint stock_remaining_check
+            check (remaining >= 0),
+    add column purchased_date timestamp      not null default (now() at time zone 'utc');
+
+update stock
+set remaining = 0
+where consumed;
+
+-- how much of a lot an invoice used, and how much an order line asks for (all that is left when unset)
+alter table invoice_details
+    add column quantity numeric(14, 3) not null default 1;
+
+alter table order_details
+    add column quantity numeric(14, 3);
+
+-- recounts of a lot, with why the count changed
+create table if not exists stock_adjustment
+(
+    id           serial
+        primary key,
+    stock_id     integer        not null
+        references stock,
+    change       numeric(14, 3) not null,
+    reason       varchar(255)   not null,
+    user_id      integer        not null
+        references "user",
+    created_date timestamp      not null
+);
+
+alter table stock_adjustment
+    owner to postgres;
+
+create index stock_adjustment_stock_id_idx on stock_adjustment (stock_id);
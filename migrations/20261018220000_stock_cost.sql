@@ -0,0 +1,13 @@
+-- what each lot an invoice used cost it, so cost of goods doesn't depend on the lot being edited later
+alter table invoice_details
+    add column cost bigint not null default 0;
+
+update invoice_details ind
+set cost = round(s.price * ind.quantity / s.quantity)
+from stock s
+where s.id = ind.stock_id;
+
+create index if not exists invoice_details_invoice_id_idx on invoice_details (invoice_id);
+
+-- lots of a product still on hand, oldest first, for first-in first-out consumption
+create index if not exists stock_product_fifo_idx on stock (product_id, purchased_date, id) where not consumed;
@@ -0,0 +1,35 @@
+-- a posting a member takes as a supplier or customer also names that account,
+-- so supplier.balance and customer.balance are backed by the ledger like user.balance
+alter table ledger_entry
+    add column supplier_id integer
+        references supplier,
+    add column customer_id integer
+        references customer;
+
+create index ledger_entry_supplier_id_idx on ledger_entry (supplier_id);
+create index ledger_entry_customer_id_idx on ledger_entry (customer_id);
+
+-- opening balances for existing suppliers and customers, one adjustment per group: each moves the
+-- amount from the member's own account onto their supplier or customer account, which leaves the
+-- member's balance as it was (fresh databases get theirs from script.sql)
+insert into ledger_transaction (kind, description, group_id, created_date)
+select 'adjustment', 'Opening supplier and customer balances', g.id, now()
+from "group" g
+where exists (select 1 from supplier s join "user" u on u.id = s.user_id where u.group_id = g.id and s.balance <> 0)
+   or exists (select 1 from customer c join "user" u on u.id = c.user_id where u.group_id = g.id and c.balance <> 0);
+
+insert into ledger_entry (transaction_id, user_id, amount, supplier_id, customer_id)
+select t.id, s.user_id, a.amount, a.supplier_id, null
+from ledger_transaction t
+         join "user" u on u.group_id = t.group_id
+         join supplier s on s.user_id = u.id and s.balance <> 0
+         cross join lateral (values (s.balance, s.id), (-s.balance, null)) as a (amount, supplier_id)
+where t.description = 'Opening supplier and customer balances';
+
+insert into ledger_entry (transaction_id, user_id, amount, supplier_id, customer_id)
+select t.id, c.user_id, a.amount, null, a.customer_id
+from ledger_transaction t
+         join "user" u on u.group_id = t.group_id
+         join customer c on c.user_id = u.id and c.balance <> 0
+         cross join lateral (values (c.balance, c.id), (-c.balance, null)) as a (amount, customer_id)
+where t.description = 'Opening supplier and customer balances';
@@ -0,0 +1,13 @@
+-- every reset request by the address asked for, so the rate limit applies whether or not an account has it
+create table if not exists password_reset_request
+(
+    id           serial
+        primary key,
+    email        varchar   not null,
+    created_date timestamp not null
+);
+
+alter table password_reset_request
+    owner to postgres;
+
+CREATE INDEX password_reset_request_email_idx ON password_reset_request(email, created_date);
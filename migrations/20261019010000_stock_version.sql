@@ -0,0 +1,3 @@
+-- recounts are checked against the version the client read; drawing a lot down bumps it too
+alter table stock
+    add column version integer default 1 not null;
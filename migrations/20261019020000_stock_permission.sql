@@ -0,0 +1,6 @@
+-- buying stock becomes its own grant instead of riding on invoice:create
+INSERT INTO role_permission (role_id, permission)
+SELECT role.id, 'stock:purchase'
+FROM role
+WHERE role.name IN ('Admin', 'Manager')
+ON CONFLICT DO NOTHING;
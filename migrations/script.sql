@@ -13,6 +13,20 @@ VALUES ('Admin'),
     ('User'),
     ('Manager');
 -- =====================================
+-- Role permissions
+-- =====================================
+INSERT INTO role_permission (role_id, permission)
+VALUES (1, 'invoice:create'),
+    (1, 'invoice:delete'),
+    (1, 'user:manage'),
+    (1, 'group:admin'),
+    (1, 'stock:purchase'),
+    (2, 'invoice:create'),
+    (3, 'invoice:create'),
+    (3, 'invoice:delete'),
+    (3, 'group:admin'),
+    (3, 'stock:purchase');
+-- =====================================
 -- Users
 -- =====================================
 INSERT INTO "user" (
@@ -121,33 +135,31 @@ VALUES ('Product A', 1),
 -- =====================================
 -- Stock
 -- =====================================
-INSERT INTO stock (price, consumed, product_id)
-VALUES (100, false, 1),
-    (200, false, 2),
-    (800, false, 11),
-    (270, false, 9),
-    (650, false, 3),
-    (430, true, 1),
-    (111, false, 7),
-    (302, false, 6),
-    (901, false, 8);
+-- single pieces; the ones the invoices below drew are used up, 2 and 3 are left for the order
+INSERT INTO stock (price, consumed, remaining, product_id)
+VALUES (100, true, 0, 1),
+    (200, false, 1, 2),
+    (800, false, 1, 11),
+    (270, true, 0, 9),
+    (650, true, 0, 3),
+    (430, true, 0, 1),
+    (111, true, 0, 7),
+    (302, true, 0, 6),
+    (901, true, 0, 8);
 -- =====================================
 -- Meals
 -- =====================================
-INSERT INTO meal DEFAULT
-VALUES;
--- Meal 1
-INSERT INTO meal DEFAULT
-VALUES;
--- Meal 2
+INSERT INTO meal (name, description, group_id)
+VALUES ('Meal 1', '', 1),
+    ('Meal 2', '', 1);
 -- =====================================
 -- Meal-Product links
 -- =====================================
-INSERT INTO meal_product (meal_id, product_id)
-VALUES (1, 1),
-    (1, 2),
-    (2, 2),
-    (2, 3);
+INSERT INTO meal_product (meal_id, product_id, position)
+VALUES (1, 1, 0),
+    (1, 2, 1),
+    (2, 2, 0),
+    (2, 3, 1);
 -- =====================================
 -- Suppliers
 -- =====================================
@@ -174,17 +186,15 @@ INSERT INTO invoice ( price, is_deleted, deleted_by, created_date, last_modifica
 -- =====================================
 -- Invoice Details
 -- =====================================
-INSERT INTO invoice_details (invoice_id, stock_id)
-VALUES (1, 1),
-    (1, 2),
-    (1, 7),
-    (2, 13),
-    (2, 8),
-    (2, 9),
-    (3, 1),
-    (4, 2),
-    (4, 3),
-    (4, 10);
+-- every lot is a single piece, so each one is drawn by one invoice only
+INSERT INTO invoice_details (invoice_id, stock_id, cost)
+VALUES (1, 1, 100),
+    (1, 7, 111),
+    (2, 4, 270),
+    (2, 8, 302),
+    (2, 9, 901),
+    (3, 5, 650),
+    (4, 6, 430);
 -- =====================================
 -- Orders
 -- =====================================
@@ -218,31 +228,55 @@ INSERT INTO system_log (
         group_id
     )
 VALUES ('CREATE', 'Created initial data', NOW (), 1, 1);
--- =====================================
--- Active Session
--- =====================================
-INSERT INTO active_session (user_id, group_id)
-VALUES (1, 1);
--- Reset sequences for all tables
-ALTER SEQUENCE "group_id_seq" RESTART WITH 1;
-ALTER SEQUENCE role_id_seq RESTART WITH 1;
-ALTER SEQUENCE "user_id_seq" RESTART WITH 1;
-ALTER SEQUENCE product_id_seq RESTART WITH 1;
-ALTER SEQUENCE stock_id_seq RESTART WITH 1;
-ALTER SEQUENCE meal_id_seq RESTART WITH 1;
-ALTER SEQUENCE meal_product_meal_id_seq RESTART WITH 1;
--- if needed
-ALTER SEQUENCE supplier_id_seq RESTART WITH 1;
-ALTER SEQUENCE customer_id_seq RESTART WITH 1;
-ALTER SEQUENCE invoice_id_seq RESTART WITH 1;
-ALTER SEQUENCE invoice_details_id_seq RESTART WITH 1;
-ALTER SEQUENCE "order_id_seq" RESTART WITH 1;
-ALTER SEQUENCE order_details_id_seq RESTART WITH 1;
-ALTER SEQUENCE system_log_id_seq RESTART WITH 1;
-ALTER SEQUENCE active_session_id_seq RESTART WITH 1;
--- remove unique inside stock indexes
-ALTER TABLE stock DROP CONSTRAINT stock_product_id_key;
-create index stock_product_id_key on stock (product_id);
--- remove unique inside invoice details indexes
-ALTER TABLE invoice_details DROP CONSTRAINT invoice_details_invoice_id_fkey;
-create index invoice_details_invoice_id_fkey on stock (product_id);
\ No newline at end of file
+-- opening balances, so the ledger agrees with the seeded user balances;
+-- one transaction because a ledger transaction has to balance at commit
+BEGIN;
+INSERT INTO ledger_transaction (kind, description, group_id, created_date)
+SELECT 'adjustment', 'Opening balance', g.id, NOW ()
+FROM "group" g
+WHERE EXISTS (SELECT 1 FROM "user" u WHERE u.group_id = g.id AND u.balance <> 0);
+INSERT INTO ledger_entry (transaction_id, user_id, amount)
+SELECT t.id, u.id, u.balance
+FROM ledger_transaction t
+    JOIN "user" u ON u.group_id = t.group_id AND u.balance <> 0;
+INSERT INTO ledger_entry (transaction_id, user_id, amount)
+SELECT t.id, NULL, -SUM (u.balance)
+FROM ledger_transaction t
+    JOIN "user" u ON u.group_id = t.group_id
+GROUP BY t.id
+HAVING SUM (u.balance) <> 0;
+-- supplier and customer balances, moved from the member's own account onto that account
+INSERT INTO ledger_transaction (kind, description, group_id, created_date)
+SELECT 'adjustment', 'Opening supplier and customer balances', g.id, NOW ()
+FROM "group" g
+WHERE EXISTS (SELECT 1 FROM supplier s JOIN "user" u ON u.id = s.user_id WHERE u.group_id = g.id AND s.balance <> 0)
+    OR EXISTS (SELECT 1 FROM customer c JOIN "user" u ON u.id = c.user_id WHERE u.group_id = g.id AND c.balance <> 0);
+INSERT INTO ledger_entry (transaction_id, user_id, amount, supplier_id, customer_id)
+SELECT t.id, s.user_id, a.amount, a.supplier_id, NULL
+FROM ledger_transaction t
+    JOIN "user" u ON u.group_id = t.group_id
+    JOIN supplier s ON s.user_id = u.id AND s.balance <> 0
+    CROSS JOIN LATERAL (VALUES (s.balance, s.id), (-s.balance, NULL)) AS a (amount, supplier_id)
+WHERE t.description = 'Opening supplier and customer balances';
+INSERT INTO ledger_entry (transaction_id, user_id, amount, supplier_id, customer_id)
+SELECT t.id, c.user_id, a.amount, NULL, a.customer_id
+FROM ledger_transaction t
+    JOIN "user" u ON u.group_id = t.group_id
+    JOIN customer c ON c.user_id = u.id AND c.balance <> 0
+    CROSS JOIN LATERAL (VALUES (c.balance, c.id), (-c.balance, NULL)) AS a (amount, customer_id)
+WHERE t.description = 'Opening supplier and customer balances';
+COMMIT;
+-- move the sequences past the seeded rows so the next insert gets a fresh id
+SELECT setval('"group_id_seq"', coalesce(max(id), 0) + 1, false) FROM "group";
+SELECT setval('role_id_seq', coalesce(max(id), 0) + 1, false) FROM role;
+SELECT setval('"user_id_seq"', coalesce(max(id), 0) + 1, false) FROM "user";
+SELECT setval('product_id_seq', coalesce(max(id), 0) + 1, false) FROM product;
+SELECT setval('stock_id_seq', coalesce(max(id), 0) + 1, false) FROM stock;
+SELECT setval('meal_id_seq', coalesce(max(id), 0) + 1, false) FROM meal;
+SELECT setval('supplier_id_seq', coalesce(max(id), 0) + 1, false) FROM supplier;
+SELECT setval('customer_id_seq', coalesce(max(id), 0) + 1, false) FROM customer;
+SELECT setval('invoice_id_seq', coalesce(max(id), 0) + 1, false) FROM invoice;
+SELECT setval('invoice_details_id_seq', coalesce(max(id), 0) + 1, false) FROM invoice_details;
+SELECT setval('"order_id_seq"', coalesce(max(id), 0) + 1, false) FROM "order";
+SELECT setval('order_details_id_seq', coalesce(max(id), 0) + 1, false) FROM order_details;
+SELECT setval('system_log_id_seq', coalesce(max(id), 0) + 1, false) FROM system_log;
@@ -0,0 +1,196 @@
+use std::fmt;
+
+use actix_web::{
+    http::{header, StatusCode},
+    HttpResponse, ResponseError,
+};
+
+use crate::interfaces::dtos::response_dto::ApiResponse;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Application-wide error, rendered as an `ApiResponse` envelope with a matching HTTP status.
+#[derive(Debug)]
+pub enum Error {
+    NotFound(String),
+    Validation(String),
+    Unauthorized(String),
+    Forbidden(String),
+    Conflict(String),
+    /// The `If-Match` version is not the current one: someone else changed the row first.
+    PreconditionFailed(String),
+    /// An update came without the `If-Match` header it needs.
+    PreconditionRequired(String),
+    TooManyRequests(String),
+    Database(sqlx::Error),
+    Internal(String),
+}
+
+impl Error {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Error::NotFound(message.into())
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Error::Validation(message.into())
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Error::Unauthorized(message.into())
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Error::Forbidden(message.into())
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Error::Conflict(message.into())
+    }
+
+    pub fn precondition_failed(message: impl Into<String>) -> Self {
+        Error::PreconditionFailed(message.into())
+    }
+
+    pub fn precondition_required(message: impl Into<String>) -> Self {
+        Error::PreconditionRequired(message.into())
+    }
+
+    pub fn internal(message: impl fmt::Display) -> Self {
+        Error::Internal(message.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(message)
+            | Error::Validation(message)
+            | Error::Unauthorized(message)
+            | Error::Forbidden(message)
+            | Error::Conflict(message)
+            | Error::PreconditionFailed(message)
+            | Error::PreconditionRequired(message)
+            | Error::TooManyRequests(message)
+            | Error::Internal(message) => write!(f, "{}", message),
+            Error::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => Error::NotFound("Not found".to_string()),
+            e => Error::Database(e),
+        }
+    }
+}
+
+impl From<validator::ValidationErrors> for Error {
+    fn from(e: validator::ValidationErrors) -> Self {
+        Error::Validation(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::internal(e)
+    }
+}
+
+impl From<tokio::task::JoinError> for Error {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Error::internal(e)
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for Error {
+    fn from(e: jsonwebtoken::errors::Error) -> Self {
+        Error::internal(e)
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::Validation(_) => StatusCode::BAD_REQUEST,
+            Error::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+            Error::Conflict(_) => StatusCode::CONFLICT,
+            Error::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            Error::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            Error::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Error::Database(_) | Error::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+
+        // details of server-side failures are logged, not sent to the client
+        let message = match self {
+            Error::Database(_) | Error::Internal(_) => {
+                eprintln!("{}", self);
+                "Internal server error".to_string()
+            }
+            _ => self.to_string(),
+        };
+
+        let mut response = HttpResponse::build(status);
+        if status == StatusCode::UNAUTHORIZED {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        response.json(ApiResponse::new(status.as_u16(), Vec::<()>::new(), message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    async fn render(error: Error) -> (StatusCode, serde_json::Value) {
+        let response = error.error_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn maps_variants_to_http_statuses_inside_the_envelope() {
+        let cases = [
+            (Error::not_found("missing"), StatusCode::NOT_FOUND),
+            (Error::validation("bad"), StatusCode::BAD_REQUEST),
+            (Error::unauthorized("who"), StatusCode::UNAUTHORIZED),
+            (Error::forbidden("no"), StatusCode::FORBIDDEN),
+            (Error::conflict("taken"), StatusCode::CONFLICT),
+            (Error::precondition_failed("stale"), StatusCode::PRECONDITION_FAILED),
+            (Error::precondition_required("which"), StatusCode::PRECONDITION_REQUIRED),
+        ];
+
+        for (error, expected) in cases {
+            let message = error.to_string();
+            let (status, body) = render(error).await;
+            assert_eq!(status, expected);
+            assert_eq!(body["status"], expected.as_u16());
+            assert_eq!(body["error_message"], message);
+            assert_eq!(body["data"], serde_json::json!([]));
+        }
+    }
+
+    #[actix_web::test]
+    async fn hides_server_side_details() {
+        let (status, body) = render(Error::from(sqlx::Error::PoolTimedOut)).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error_message"], "Internal server error");
+    }
+
+    #[test]
+    fn missing_rows_become_not_found() {
+        assert!(matches!(Error::from(sqlx::Error::RowNotFound), Error::NotFound(_)));
+    }
+}
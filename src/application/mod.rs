@@ -1,4 +1,5 @@
 // src/application/mod.rs
 pub mod commands;
+pub mod error;
 pub mod queries;
-pub mod use_cases;
\ No newline at end of file
+pub mod use_cases;
@@ -7,4 +7,22 @@ pub struct ActiveSession {
     pub id: i32,
     pub user_id: i32,
     pub group_id: i32,
-}
\ No newline at end of file
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_date: chrono::NaiveDateTime,
+    pub last_seen_date: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewActiveSession {
+    pub user_id: i32,
+    pub group_id: i32,
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_date: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::{
+    application::error::Result,
+    domain::active_session::active_session_entity::{ActiveSession, NewActiveSession},
+};
+
+#[async_trait]
+pub trait ActiveSessionRepository: Send + Sync {
+    async fn create(&self, session: NewActiveSession) -> Result<ActiveSession>;
+
+    /// Swaps a live session's refresh token for a new one and pushes its expiry out.
+    /// Returns `None` if no unrevoked, unexpired session holds `refresh_token_hash`.
+    async fn rotate_refresh_token(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        now: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<Option<ActiveSession>>;
+
+    /// Records activity at `now` on a live session last seen before `stale_before`; a session seen
+    /// since is left as it is. Returns `false` if it is revoked, expired or not the user's.
+    async fn touch(
+        &self,
+        session_id: i32,
+        user_id: i32,
+        now: NaiveDateTime,
+        stale_before: NaiveDateTime,
+    ) -> Result<bool>;
+
+    /// Unrevoked, unexpired sessions of the user, most recently used first.
+    async fn find_live_by_user(&self, user_id: i32, now: NaiveDateTime) -> Result<Vec<ActiveSession>>;
+
+    /// Returns `false` if the session doesn't exist, isn't the user's or is already revoked.
+    async fn revoke(&self, user_id: i32, session_id: i32, now: NaiveDateTime) -> Result<bool>;
+
+    async fn revoke_all(&self, user_id: i32, now: NaiveDateTime) -> Result<u64>;
+
+    /// Revokes every live session of the user except `keep_session_id`.
+    async fn revoke_others(&self, user_id: i32, keep_session_id: i32, now: NaiveDateTime) -> Result<u64>;
+}
@@ -0,0 +1,111 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        active_session::{
+            active_session_entity::{ActiveSession, NewActiveSession},
+            active_session_repository::ActiveSessionRepository,
+        },
+        user::{user_entity::User, user_repository::UserRepository},
+    },
+    infrastructure::token::{generate_token, hash_token},
+};
+
+/// How stale a session's `last_seen_date` may get before a request writes it again; it saves a write
+/// on every authenticated request.
+const LAST_SEEN_PRECISION: Duration = Duration::minutes(1);
+
+/// Client details recorded alongside a session.
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+pub struct ActiveSessionService;
+
+impl ActiveSessionService {
+    /// Opens a session for `user` and returns it with its plain refresh token.
+    pub async fn create_session(
+        user: &User,
+        client: SessionClient,
+        ttl: Duration,
+        sessions: &dyn ActiveSessionRepository,
+    ) -> Result<(ActiveSession, String)> {
+        let (refresh_token, refresh_token_hash) = generate_token();
+        let now = Utc::now().naive_utc();
+
+        let session = sessions
+            .create(NewActiveSession {
+                user_id: user.id,
+                group_id: user.group_id,
+                refresh_token_hash,
+                user_agent: client.user_agent,
+                ip_address: client.ip_address,
+                created_date: now,
+                expires_at: now + ttl,
+            })
+            .await?;
+
+        Ok((session, refresh_token))
+    }
+
+    /// Exchanges a refresh token for a new one, extending the session.
+    ///
+    /// The old token stops working immediately, so a leaked token can be used at most once.
+    pub async fn refresh_session(
+        refresh_token: &str,
+        client: SessionClient,
+        ttl: Duration,
+        sessions: &dyn ActiveSessionRepository,
+        users: &dyn UserRepository,
+    ) -> Result<(User, ActiveSession, String)> {
+        let invalid = || Error::unauthorized("Invalid or expired refresh token");
+        let (new_token, new_token_hash) = generate_token();
+        let now = Utc::now().naive_utc();
+
+        let session = sessions
+            .rotate_refresh_token(
+                &hash_token(refresh_token),
+                &new_token_hash,
+                client.user_agent,
+                client.ip_address,
+                now,
+                now + ttl,
+            )
+            .await?
+            .ok_or_else(invalid)?;
+
+        let user = users
+            .find_by_id(session.user_id)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or_else(invalid)?;
+
+        Ok((user, session, new_token))
+    }
+
+    /// Returns `true` and records activity, to the minute, if the session is still usable.
+    pub async fn touch_session(session_id: i32, user_id: i32, sessions: &dyn ActiveSessionRepository) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+        sessions.touch(session_id, user_id, now, now - LAST_SEEN_PRECISION).await
+    }
+
+    pub async fn get_user_sessions(user_id: i32, sessions: &dyn ActiveSessionRepository) -> Result<Vec<ActiveSession>> {
+        sessions.find_live_by_user(user_id, Utc::now().naive_utc()).await
+    }
+
+    /// Revokes one of the user's own sessions.
+    pub async fn revoke_session(user_id: i32, session_id: i32, sessions: &dyn ActiveSessionRepository) -> Result<()> {
+        if !sessions.revoke(user_id, session_id, Utc::now().naive_utc()).await? {
+            return Err(Error::not_found("Session not found"));
+        }
+
+        Ok(())
+    }
+
+    pub async fn revoke_all_sessions(user_id: i32, sessions: &dyn ActiveSessionRepository) -> Result<u64> {
+        sessions.revoke_all(user_id, Utc::now().naive_utc()).await
+    }
+}
@@ -1 +1,3 @@
-pub mod active_session_entity; 
\ No newline at end of file
+pub mod active_session_entity;
+pub mod active_session_repository;
+pub mod active_session_service;
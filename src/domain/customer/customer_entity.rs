@@ -5,5 +5,5 @@ use sqlx::prelude::FromRow;
 pub struct Customer {
     pub id: i32,
     pub user_id: i32,
-    pub balance: i32,
+    pub balance: i64,
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::{application::error::Result, domain::customer::customer_entity::Customer};
+
+#[async_trait]
+pub trait CustomerRepository: Send + Sync {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Customer>>;
+
+    async fn find_by_user(&self, user_id: i32) -> Result<Vec<Customer>>;
+}
@@ -1,3 +1,2 @@
 pub mod customer_entity;
 pub mod customer_repository;
-pub mod customer_service;
\ No newline at end of file
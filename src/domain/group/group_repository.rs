@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+use crate::{application::error::Result, domain::group::group_entity::Group};
+
+#[async_trait]
+pub trait GroupRepository: Send + Sync {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Group>>;
+
+    /// Whether `user_id` is an active member of `group_id`.
+    async fn is_member(&self, user_id: i32, group_id: i32) -> Result<bool>;
+}
@@ -1,5 +1,10 @@
+use crate::{application::error::Result, domain::group::group_repository::GroupRepository};
+
 pub struct GroupService;
 
 impl GroupService {
-
+    /// Whether `user_id` is an active member of `group_id`.
+    pub async fn is_member(user_id: i32, group_id: i32, groups: &dyn GroupRepository) -> Result<bool> {
+        groups.is_member(user_id, group_id).await
+    }
 }
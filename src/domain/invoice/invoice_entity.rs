@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
+use crate::domain::{
+    invoice_details::invoice_details_entity::InvoiceDetails,
+    invoice_participant::invoice_participant_entity::{InvoiceParticipant, NewInvoiceParticipant},
+    ledger::ledger_entity::NewLedgerEntry,
+    stock::stock_entity::StockUse,
+    version::Version,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Invoice {
     pub id: i32,
@@ -12,4 +20,62 @@ pub struct Invoice {
     pub meal_id: i32,
     pub group_id: i32,
     pub supplier_id: i32,
-}
\ No newline at end of file
+    /// The order it bills, for invoices made from one.
+    pub order_id: Option<i32>,
+}
+
+impl Invoice {
+    pub fn version(&self) -> Version {
+        Version::of_timestamp(self.last_modification_date)
+    }
+}
+
+/// Values for an invoice row that hasn't been inserted yet; it starts out live.
+#[derive(Debug, Clone)]
+pub struct NewInvoice {
+    pub price: i64,
+    pub created_date: chrono::NaiveDateTime,
+    pub meal_id: i32,
+    pub group_id: i32,
+    pub supplier_id: i32,
+    pub order_id: Option<i32>,
+}
+
+/// An invoice ready to be inserted: its row, the stock rows it consumes, who shares the price
+/// and what it posts to the ledger.
+#[derive(Debug, Clone)]
+pub struct InvoiceDraft {
+    pub invoice: NewInvoice,
+    pub stock: Vec<StockUse>,
+    pub participants: Vec<NewInvoiceParticipant>,
+    pub entries: Vec<NewLedgerEntry>,
+}
+
+/// New values for an edited invoice; meal and stock stay as they were.
+#[derive(Debug, Clone)]
+pub struct InvoiceChanges {
+    pub price: i64,
+    pub supplier_id: i32,
+    pub last_modification_date: chrono::NaiveDateTime,
+}
+
+/// An invoice together with who shares its price.
+#[derive(Debug, Clone)]
+pub struct InvoiceWithParticipants {
+    pub invoice: Invoice,
+    pub participants: Vec<InvoiceParticipant>,
+}
+
+/// An invoice together with the stock it used, for what it charged against what that stock cost.
+#[derive(Debug, Clone)]
+pub struct InvoiceCost {
+    pub invoice: Invoice,
+    pub details: Vec<InvoiceDetails>,
+}
+
+impl InvoiceCost {
+    /// What the stock the invoice used cost when it was bought.
+    pub fn cost_of_goods(&self) -> i64 {
+        self.details.iter().map(|details| details.cost).sum()
+    }
+}
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{
+    application::error::Result,
+    domain::{
+        invoice::invoice_entity::{Invoice, InvoiceChanges, NewInvoice},
+        invoice_details::invoice_details_entity::InvoiceDetails,
+        invoice_participant::invoice_participant_entity::{InvoiceParticipant, NewInvoiceParticipant},
+        ledger::ledger_entity::NewLedgerEntry,
+        stock::stock_entity::StockUse,
+    },
+    interfaces::dtos::invoice_dto::InvoiceRow,
+};
+
+#[async_trait]
+pub trait InvoiceRepository: Send + Sync {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Invoice>>;
+
+    /// Non-deleted invoices of the group that served the meal, newest first.
+    async fn find_by_meal(&self, group_id: i32, meal_id: i32) -> Result<Vec<Invoice>>;
+
+    /// Deleted invoices of the group, most recently changed first.
+    async fn find_deleted(&self, group_id: i32) -> Result<Vec<Invoice>>;
+
+    /// Non-deleted invoices of the group created between the two dates (inclusive),
+    /// with supplier name and meal products resolved.
+    async fn find_report_rows(&self, group_id: i32, start_date: NaiveDate, end_date: NaiveDate)
+        -> Result<Vec<InvoiceRow>>;
+
+    /// The invoice's participants, by user id.
+    async fn find_participants(&self, invoice_id: i32) -> Result<Vec<InvoiceParticipant>>;
+
+    /// `(user_id, customer_id)` of the participants the invoice charged as customers of an order.
+    async fn find_billed_customers(&self, invoice_id: i32) -> Result<Vec<(i32, i32)>>;
+
+    /// The stock rows the invoice used, with how much of each and what it cost, in insertion order.
+    async fn find_details(&self, invoice_id: i32) -> Result<Vec<InvoiceDetails>>;
+
+    /// Inserts the invoice with one `invoice_details` row per stock use and its participants, draws
+    /// the used quantities from the stock and posts `entries` to the ledger, all in one transaction.
+    /// Fails with a conflict, changing nothing, if any stock row has less left than is used.
+    async fn create(
+        &self,
+        invoice: NewInvoice,
+        stock: &[StockUse],
+        participants: &[NewInvoiceParticipant],
+        entries: &[NewLedgerEntry],
+    ) -> Result<Invoice>;
+
+    /// Marks a live invoice deleted by `deleted_by`, posts a reversal of everything the ledger holds
+    /// for it and puts the stock it drew back, in one transaction. `None` if the invoice isn't live or
+    /// has been modified since `last_modified`.
+    async fn soft_delete(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        deleted_by: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<Invoice>>;
+
+    /// Brings a deleted invoice back, draws its stock again and posts `entries`, its balance effect, in
+    /// one transaction. `None` if the invoice isn't deleted or has been modified since `last_modified`;
+    /// a conflict if its stock has been used in the meantime.
+    async fn restore(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        now: NaiveDateTime,
+        entries: &[NewLedgerEntry],
+    ) -> Result<Option<Invoice>>;
+
+    /// Applies `changes` to a live invoice and replaces its participants; the ledger gets a reversal of
+    /// the invoice's previous effect and `entries` as the new one, all in one transaction.
+    /// `None` if the invoice isn't live or has been modified since `last_modified`.
+    async fn update(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        changes: InvoiceChanges,
+        participants: &[NewInvoiceParticipant],
+        entries: &[NewLedgerEntry],
+    ) -> Result<Option<Invoice>>;
+}
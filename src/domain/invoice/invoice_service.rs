@@ -1,56 +1,360 @@
-use actix_web::Result;
-use sqlx::{Pool, Postgres};
-use sqlx_clean_querybuilder::query_builder::PostgreSqlQueryBuilder;
+use std::collections::HashSet;
 
-use crate::interfaces::dtos::invoice_dto::{group_invoices, InvoiceResponse, InvoiceRow};
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        invoice::{
+            invoice_entity::{Invoice, InvoiceChanges, InvoiceCost, InvoiceWithParticipants, NewInvoice},
+            invoice_repository::InvoiceRepository,
+            split_strategy::SplitStrategy,
+        },
+        invoice_participant::invoice_participant_entity::NewInvoiceParticipant,
+        meal::{meal_repository::MealRepository, meal_service::find_meal},
+        stock::{
+            stock_entity::{Stock, StockUse},
+            stock_repository::StockRepository,
+            stock_service::{check_oldest_first, draw, draw_oldest_first},
+        },
+        supplier::{supplier_entity::Supplier, supplier_repository::SupplierRepository},
+        ledger::ledger_entity::NewLedgerEntry,
+        user::user_repository::UserRepository,
+        version::{stale, Version},
+    },
+    interfaces::dtos::invoice_dto::{
+        group_invoices, CreateInvoiceDto, CreateInvoiceParticipantDto, InvoiceResponse, UpdateInvoiceDto,
+    },
+};
 
 pub struct InvoiceService;
 
 impl InvoiceService {
     pub async fn get_invoice_report(
         group_id: i32,
-        start_date: String,
-        end_date: String,
-        db: Pool<Postgres>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        invoices: &dyn InvoiceRepository,
     ) -> Result<Vec<InvoiceResponse>> {
-        let condition = format!("invoice.group_id = {} AND is_deleted = false AND created_date::date BETWEEN '{}' and '{}'", group_id, start_date, end_date);
-
-        let qq = PostgreSqlQueryBuilder::select()
-            .columns(&[
-                "invoice.id",
-                "price",
-                "created_date",
-                "u.id as supplier_id",
-                "u.name as supplier_name",
-                "string_agg(DISTINCT p.name, ', ' ORDER BY p.name) as meal",
-                "supplier_id",
-            ])
-            .table("invoice", None)
-            .join_inner(
-                "invoice_details",
-                Some("ind"),
-                "invoice.id",
-                "ind.invoice_id",
-            )
-            .join_inner("meal", None, "meal.id", "invoice.meal_id")
-            .join_inner("meal_product", Some("mp"), "meal.id", "mp.meal_id")
-            .join_inner("product", Some("p"), "p.id", "mp.product_id")
-            .join_inner("supplier", Some("s"), "s.id", "supplier_id")
-            .join_inner("\"user\"", Some("u"), "u.id", "s.user_id")
-            .filter(&condition)
-            .group_by("invoice.id, price, created_date, u.name, meal.id, u.id, supplier_id")
-            .build();
-
-        println!("{}", qq);
-
-        // fetch from database
-        let invoices: Vec<InvoiceRow> = sqlx::query_as::<_, InvoiceRow>(&qq)
-            .fetch_all(&db)
-            .await
-            .unwrap();
-
-        let response = group_invoices(invoices);
-
-        Ok(response)
+        let rows = invoices.find_report_rows(group_id, start_date, end_date).await?;
+
+        Ok(group_invoices(rows))
+    }
+
+    /// Invoices of the group that served the meal; 404 if the meal isn't the group's.
+    pub async fn get_invoices_by_meal_id(
+        group_id: i32,
+        meal_id: i32,
+        invoices: &dyn InvoiceRepository,
+        meals: &dyn MealRepository,
+    ) -> Result<Vec<Invoice>> {
+        find_meal(group_id, meal_id, meals).await?;
+        invoices.find_by_meal(group_id, meal_id).await
+    }
+
+    /// The invoice with the stock rows it used and what each cost.
+    pub async fn get_invoice_cost(group_id: i32, id: i32, invoices: &dyn InvoiceRepository) -> Result<InvoiceCost> {
+        let invoice = find_invoice(group_id, id, invoices).await?;
+        let details = invoices.find_details(invoice.id).await?;
+
+        Ok(InvoiceCost { invoice, details })
+    }
+
+    /// Cost of goods of every live invoice of the meal; 404 if the meal isn't the group's.
+    pub async fn get_meal_cost(
+        group_id: i32,
+        meal_id: i32,
+        invoices: &dyn InvoiceRepository,
+        meals: &dyn MealRepository,
+    ) -> Result<Vec<InvoiceCost>> {
+        let mut result = Vec::new();
+
+        for invoice in Self::get_invoices_by_meal_id(group_id, meal_id, invoices, meals).await? {
+            let details = invoices.find_details(invoice.id).await?;
+            result.push(InvoiceCost { invoice, details });
+        }
+
+        Ok(result)
+    }
+
+    pub async fn get_invoice(group_id: i32, id: i32, invoices: &dyn InvoiceRepository) -> Result<InvoiceWithParticipants> {
+        let invoice = find_invoice(group_id, id, invoices).await?;
+        let participants = invoices.find_participants(invoice.id).await?;
+
+        Ok(InvoiceWithParticipants { invoice, participants })
+    }
+
+    /// Records a meal bought from `supplier_id`: consumes the stock, credits the supplier's user
+    /// with the price and debits each participant their part of it under `dto.split`.
+    pub async fn create_invoice(
+        group_id: i32,
+        dto: CreateInvoiceDto,
+        invoices: &dyn InvoiceRepository,
+        meals: &dyn MealRepository,
+        suppliers: &dyn SupplierRepository,
+        stocks: &dyn StockRepository,
+        users: &dyn UserRepository,
+    ) -> Result<InvoiceWithParticipants> {
+        find_meal(group_id, dto.meal_id, meals).await?;
+        let supplier = find_supplier(group_id, dto.supplier_id, suppliers).await?;
+
+        // whole rows first, then the partly used ones, then products from their oldest lots
+        let requested: Vec<(i32, Option<Decimal>)> = dto
+            .stock_ids
+            .iter()
+            .map(|stock_id| (*stock_id, None))
+            .chain(dto.stock_uses.iter().map(|used| (used.stock_id, Some(used.quantity))))
+            .collect();
+        if requested.is_empty() && dto.product_uses.is_empty() {
+            return Err(Error::validation("An invoice needs at least one stock item"));
+        }
+        let stock_ids: Vec<i32> = requested.iter().map(|(stock_id, _)| *stock_id).collect();
+        if has_duplicates(&stock_ids) {
+            return Err(Error::validation("stock_ids must not repeat"));
+        }
+        let product_ids: Vec<i32> = dto.product_uses.iter().map(|used| used.product_id).collect();
+        if has_duplicates(&product_ids) {
+            return Err(Error::validation("product_uses must not repeat a product"));
+        }
+        let mut available = stocks.find_available_by_group(group_id).await?;
+        let mut uses = Vec::with_capacity(requested.len());
+        for (stock_id, quantity) in requested {
+            let stock = available
+                .iter()
+                .find(|stock| stock.id == stock_id)
+                .ok_or_else(|| Error::validation(format!("Stock {} is not available in this group", stock_id)))?;
+            let used = draw(stock, quantity)?;
+            take(&mut available, &mut uses, used)?;
+        }
+        for product in &dto.product_uses {
+            for used in draw_oldest_first(&available, product.product_id, product.quantity)? {
+                take(&mut available, &mut uses, used)?;
+            }
+        }
+        check_oldest_first(&available, &uses)?;
+        let price = dto.price.unwrap_or(uses.iter().map(|used| used.cost).sum());
+
+        let participants = split_among_members(group_id, price, dto.split, &dto.participants, users).await?;
+        let entries = invoice_entries(&supplier, price, &participants, &[]);
+
+        let invoice = NewInvoice {
+            price,
+            created_date: Utc::now().naive_utc(),
+            meal_id: dto.meal_id,
+            group_id,
+            supplier_id: supplier.id,
+            order_id: None,
+        };
+        let invoice = invoices.create(invoice, &uses, &participants, &entries).await?;
+        let participants = invoices.find_participants(invoice.id).await?;
+
+        Ok(InvoiceWithParticipants { invoice, participants })
+    }
+
+    pub async fn get_deleted_invoices(group_id: i32, invoices: &dyn InvoiceRepository) -> Result<Vec<Invoice>> {
+        invoices.find_deleted(group_id).await
+    }
+
+    /// Moves the invoice to the trash on behalf of `user_id`, reversing its balance effects and putting
+    /// its stock back.
+    pub async fn delete_invoice(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        expected: Version,
+        invoices: &dyn InvoiceRepository,
+    ) -> Result<Invoice> {
+        let invoice = find_invoice(group_id, id, invoices).await?;
+        expected.check(invoice.version(), "Invoice")?;
+        if invoice.is_deleted {
+            return Err(Error::conflict("Invoice is already deleted"));
+        }
+
+        invoices
+            .soft_delete(invoice.id, invoice.last_modification_date, user_id, Utc::now().naive_utc())
+            .await?
+            .ok_or_else(|| stale("Invoice"))
     }
+
+    /// Takes the invoice out of the trash, draws its stock again and charges its participants again.
+    pub async fn restore_invoice(
+        group_id: i32,
+        id: i32,
+        expected: Version,
+        invoices: &dyn InvoiceRepository,
+        suppliers: &dyn SupplierRepository,
+    ) -> Result<InvoiceWithParticipants> {
+        let invoice = find_invoice(group_id, id, invoices).await?;
+        expected.check(invoice.version(), "Invoice")?;
+        if !invoice.is_deleted {
+            return Err(Error::conflict("Invoice is not deleted"));
+        }
+        let supplier = find_supplier(group_id, invoice.supplier_id, suppliers).await?;
+        let participants: Vec<NewInvoiceParticipant> = invoices
+            .find_participants(invoice.id)
+            .await?
+            .into_iter()
+            .map(|participant| NewInvoiceParticipant {
+                user_id: participant.user_id,
+                amount: participant.amount,
+            })
+            .collect();
+
+        // invoices from before participants were recorded never posted anything, so there is nothing to redo
+        let entries = if participants.is_empty() {
+            Vec::new()
+        } else {
+            let customers = invoices.find_billed_customers(invoice.id).await?;
+            invoice_entries(&supplier, invoice.price, &participants, &customers)
+        };
+
+        let invoice = invoices
+            .restore(invoice.id, invoice.last_modification_date, Utc::now().naive_utc(), &entries)
+            .await?
+            .ok_or_else(|| stale("Invoice"))?;
+        let participants = invoices.find_participants(invoice.id).await?;
+
+        Ok(InvoiceWithParticipants { invoice, participants })
+    }
+
+    /// Changes price, supplier or participants of a live invoice; its previous balance effect is
+    /// reversed and the new one posted in its place.
+    pub async fn update_invoice(
+        group_id: i32,
+        id: i32,
+        expected: Version,
+        dto: UpdateInvoiceDto,
+        invoices: &dyn InvoiceRepository,
+        suppliers: &dyn SupplierRepository,
+        users: &dyn UserRepository,
+    ) -> Result<InvoiceWithParticipants> {
+        let invoice = find_invoice(group_id, id, invoices).await?;
+        expected.check(invoice.version(), "Invoice")?;
+        if invoice.is_deleted {
+            return Err(Error::conflict("A deleted invoice has to be restored before it can be edited"));
+        }
+
+        let supplier = find_supplier(group_id, dto.supplier_id.unwrap_or(invoice.supplier_id), suppliers).await?;
+        let price = dto.price.unwrap_or(invoice.price);
+
+        let participants = match &dto.participants {
+            Some(participants) => split_among_members(group_id, price, dto.split, participants, users).await?,
+            None if price != invoice.price => {
+                return Err(Error::validation("Changing the price needs the participants to split it among"));
+            }
+            None => {
+                let current: Vec<NewInvoiceParticipant> = invoices
+                    .find_participants(invoice.id)
+                    .await?
+                    .into_iter()
+                    .map(|participant| NewInvoiceParticipant {
+                        user_id: participant.user_id,
+                        amount: participant.amount,
+                    })
+                    .collect();
+                if current.is_empty() {
+                    return Err(Error::validation("This invoice has no participants yet, so they have to be given"));
+                }
+                current
+            }
+        };
+        // whoever was billed for their order stays billed as that customer
+        let customers = invoices.find_billed_customers(invoice.id).await?;
+        let entries = invoice_entries(&supplier, price, &participants, &customers);
+
+        let changes = InvoiceChanges {
+            price,
+            supplier_id: supplier.id,
+            last_modification_date: Utc::now().naive_utc(),
+        };
+        let invoice = invoices
+            .update(invoice.id, invoice.last_modification_date, changes, &participants, &entries)
+            .await?
+            .ok_or_else(|| stale("Invoice"))?;
+        let participants = invoices.find_participants(invoice.id).await?;
+
+        Ok(InvoiceWithParticipants { invoice, participants })
+    }
+}
+
+async fn find_invoice(group_id: i32, id: i32, invoices: &dyn InvoiceRepository) -> Result<Invoice> {
+    invoices
+        .find_by_id(group_id, id)
+        .await?
+        .ok_or_else(|| Error::not_found("Invoice not found"))
+}
+
+pub(crate) async fn find_supplier(group_id: i32, supplier_id: i32, suppliers: &dyn SupplierRepository) -> Result<Supplier> {
+    suppliers
+        .find_by_group(group_id)
+        .await?
+        .into_iter()
+        .find(|supplier| supplier.id == supplier_id)
+        .ok_or_else(|| Error::validation(format!("Supplier {} does not exist in this group", supplier_id)))
+}
+
+/// Checks the participants are distinct active members and divides `price` among them.
+async fn split_among_members(
+    group_id: i32,
+    price: i64,
+    split: SplitStrategy,
+    participants: &[CreateInvoiceParticipantDto],
+    users: &dyn UserRepository,
+) -> Result<Vec<NewInvoiceParticipant>> {
+    let participant_ids: Vec<i32> = participants.iter().map(|participant| participant.user_id).collect();
+    if has_duplicates(&participant_ids) {
+        return Err(Error::validation("Participants must not repeat"));
+    }
+    let members = users.find_active_by_group(group_id).await?;
+    if let Some(id) = participant_ids.iter().find(|id| !members.iter().any(|user| user.id == **id)) {
+        return Err(Error::validation(format!("User {} is not an active member of this group", id)));
+    }
+
+    let values: Vec<Option<f64>> = participants.iter().map(|participant| participant.value).collect();
+    Ok(participant_ids
+        .into_iter()
+        .zip(split.split(price, &values)?)
+        .map(|(user_id, amount)| NewInvoiceParticipant { user_id, amount })
+        .collect())
+}
+
+/// The supplier's user is credited the price on their supplier account and each participant debited
+/// their part, on their customer account if `customers` names one for them as `(user_id, customer_id)`.
+pub(crate) fn invoice_entries(
+    supplier: &Supplier,
+    price: i64,
+    participants: &[NewInvoiceParticipant],
+    customers: &[(i32, i32)],
+) -> Vec<NewLedgerEntry> {
+    let mut entries = vec![NewLedgerEntry::user(supplier.user_id, price).as_supplier(supplier.id)];
+    entries.extend(participants.iter().map(|participant| {
+        let entry = NewLedgerEntry::user(participant.user_id, -participant.amount);
+        match customers.iter().find(|(user_id, _)| *user_id == participant.user_id) {
+            Some((_, customer_id)) => entry.as_customer(*customer_id),
+            None => entry,
+        }
+    }));
+    entries
+}
+
+/// Adds `used` to `uses`, merged into an earlier use of the same lot, and takes it off what is left.
+fn take(available: &mut [Stock], uses: &mut Vec<StockUse>, used: StockUse) -> Result<()> {
+    let Some(stock) = available.iter_mut().find(|stock| stock.id == used.stock_id) else {
+        return Ok(());
+    };
+    stock.remaining -= used.quantity;
+    match uses.iter_mut().find(|earlier| earlier.stock_id == used.stock_id) {
+        Some(earlier) => {
+            earlier.quantity += used.quantity;
+            earlier.cost = stock.cost_of(earlier.quantity)?;
+        }
+        None => uses.push(used),
+    }
+    Ok(())
+}
+
+pub(crate) fn has_duplicates(ids: &[i32]) -> bool {
+    let mut seen = HashSet::new();
+    !ids.iter().all(|id| seen.insert(*id))
 }
@@ -1,2 +1,4 @@
 pub mod invoice_entity;
-pub mod invoice_service; 
\ No newline at end of file
+pub mod invoice_repository;
+pub mod invoice_service;
+pub mod split_strategy;
@@ -0,0 +1,177 @@
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::application::error::{Error, Result};
+
+/// How an invoice's price is divided among its participants.
+///
+/// Every strategy works in whole cents and hands out the cents lost to rounding by largest
+/// remainder (ties go to the earlier participant), so the parts always sum to the price exactly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitStrategy {
+    /// Everyone pays the same; participants carry no value.
+    #[default]
+    Equal,
+    /// Proportional to each participant's positive whole-number weight.
+    Shares,
+    /// Each participant's value is their amount; the amounts must add up to the price.
+    Exact,
+    /// Each participant's value is a percent of the price with at most two decimals;
+    /// the percents must add up to 100.
+    Percentage,
+}
+
+impl SplitStrategy {
+    /// Divides `price` according to `values`, one per participant and in the same order.
+    pub fn split(self, price: i64, values: &[Option<f64>]) -> Result<Vec<i64>> {
+        if values.is_empty() {
+            return Err(Error::validation("An invoice needs at least one participant"));
+        }
+
+        match self {
+            SplitStrategy::Equal => {
+                if values.iter().any(Option::is_some) {
+                    return Err(Error::validation("The equal split takes no participant values"));
+                }
+                Ok(split_by_weights(price, &vec![1; values.len()]))
+            }
+            SplitStrategy::Shares => {
+                let weights = whole_numbers(values, "share")?;
+                if weights.iter().any(|weight| *weight <= 0) {
+                    return Err(Error::validation("Shares must be positive"));
+                }
+                Ok(split_by_weights(price, &weights))
+            }
+            SplitStrategy::Exact => {
+                let amounts = whole_numbers(values, "amount")?;
+                if amounts.iter().any(|amount| *amount < 0) {
+                    return Err(Error::validation("Amounts must not be negative"));
+                }
+                if checked_sum(&amounts, "amount")? != price {
+                    return Err(Error::validation(format!("Amounts must add up to the price of {}", price)));
+                }
+                Ok(amounts)
+            }
+            SplitStrategy::Percentage => {
+                // hundredths of a percent, so the weights stay whole numbers
+                let basis_points = values
+                    .iter()
+                    .map(|value| {
+                        let percent = value.ok_or_else(|| Error::validation("Every participant needs a percentage"))?;
+                        if !(percent > 0.0 && percent <= 100.0) {
+                            return Err(Error::validation("Percentages must be above 0 and at most 100"));
+                        }
+                        let basis_points = (percent * 100.0).round();
+                        if (percent * 100.0 - basis_points).abs() > 1e-6 {
+                            return Err(Error::validation("Percentages must have at most two decimals"));
+                        }
+                        Ok(basis_points as i64)
+                    })
+                    .collect::<Result<Vec<i64>>>()?;
+                if checked_sum(&basis_points, "percentage")? != 10_000 {
+                    return Err(Error::validation("Percentages must add up to 100"));
+                }
+                Ok(split_by_weights(price, &basis_points))
+            }
+        }
+    }
+}
+
+fn whole_numbers(values: &[Option<f64>], name: &str) -> Result<Vec<i64>> {
+    values
+        .iter()
+        .map(|value| match value {
+            Some(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f64 => Ok(*value as i64),
+            Some(_) => Err(Error::validation(format!("Every {} must be a whole number", name))),
+            None => Err(Error::validation(format!("Every participant needs a {}", name))),
+        })
+        .collect()
+}
+
+/// Adds up participant values, which are caller-supplied and so may not fit together.
+fn checked_sum(values: &[i64], name: &str) -> Result<i64> {
+    values
+        .iter()
+        .try_fold(0i64, |sum, value| sum.checked_add(*value))
+        .ok_or_else(|| Error::validation(format!("The {}s are too large", name)))
+}
+
+/// Largest-remainder apportionment of `total` over positive `weights`.
+pub(crate) fn split_by_weights(total: i64, weights: &[i64]) -> Vec<i64> {
+    let weight_sum: i128 = weights.iter().map(|weight| i128::from(*weight)).sum();
+    let exact: Vec<(i64, i128)> = weights
+        .iter()
+        .map(|weight| {
+            let scaled = i128::from(total) * i128::from(*weight);
+            ((scaled / weight_sum) as i64, scaled % weight_sum)
+        })
+        .collect();
+
+    let mut parts: Vec<i64> = exact.iter().map(|(part, _)| *part).collect();
+    let leftover = total - parts.iter().sum::<i64>();
+
+    let mut by_remainder: Vec<usize> = (0..parts.len()).collect();
+    by_remainder.sort_by_key(|i| (Reverse(exact[*i].1), *i));
+    for i in by_remainder.into_iter().take(leftover as usize) {
+        parts[i] += 1;
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_split_gives_leftover_cents_to_the_first_participants() {
+        let parts = SplitStrategy::Equal.split(100, &[None, None, None]).unwrap();
+        assert_eq!(parts, [34, 33, 33]);
+    }
+
+    #[test]
+    fn shares_split_by_largest_remainder() {
+        // exact parts 142.86, 285.71, 571.43
+        let parts = SplitStrategy::Shares.split(1000, &[Some(1.0), Some(2.0), Some(4.0)]).unwrap();
+        assert_eq!(parts, [143, 286, 571]);
+        assert_eq!(parts.iter().sum::<i64>(), 1000);
+    }
+
+    #[test]
+    fn exact_amounts_must_match_the_price() {
+        assert_eq!(SplitStrategy::Exact.split(500, &[Some(120.0), Some(380.0)]).unwrap(), [120, 380]);
+        assert!(SplitStrategy::Exact.split(500, &[Some(120.0), Some(379.0)]).is_err());
+        assert!(SplitStrategy::Exact.split(500, &[Some(120.5), Some(379.5)]).is_err());
+    }
+
+    #[test]
+    fn percentages_must_total_one_hundred() {
+        let parts = SplitStrategy::Percentage.split(1001, &[Some(33.33), Some(33.33), Some(33.34)]).unwrap();
+        assert_eq!(parts, [334, 333, 334]);
+        assert_eq!(parts.iter().sum::<i64>(), 1001);
+
+        assert!(SplitStrategy::Percentage.split(1000, &[Some(50.0), Some(49.0)]).is_err());
+        assert!(SplitStrategy::Percentage.split(1000, &[Some(50.005), Some(49.995)]).is_err());
+        assert!(SplitStrategy::Percentage.split(1000, &[Some(f64::NAN), Some(100.0)]).is_err());
+        assert!(SplitStrategy::Percentage.split(1000, &[Some(1e300), Some(-1e300)]).is_err());
+    }
+
+    #[test]
+    fn values_have_to_fit_the_strategy() {
+        assert!(SplitStrategy::Equal.split(100, &[Some(1.0)]).is_err());
+        assert!(SplitStrategy::Shares.split(100, &[Some(1.0), None]).is_err());
+        assert!(SplitStrategy::Shares.split(100, &[Some(0.0)]).is_err());
+        assert!(SplitStrategy::Shares.split(100, &[]).is_err());
+    }
+
+    #[test]
+    fn values_whose_sum_overflows_are_rejected() {
+        let huge = Some(9.0e18);
+        assert!(SplitStrategy::Exact.split(100, &[huge, huge, Some(0.0)]).is_err());
+        // shares are summed in i128, so any that fit one by one also fit together
+        assert_eq!(SplitStrategy::Shares.split(100, &[huge, huge]).unwrap(), [50, 50]);
+    }
+}
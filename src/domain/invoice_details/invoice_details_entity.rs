@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
@@ -6,4 +7,8 @@ pub struct InvoiceDetails {
     pub id: i32,
     pub invoice_id: i32,
     pub stock_id: i32,
-}
\ No newline at end of file
+    /// How much of the stock row the invoice used, in its unit.
+    pub quantity: Decimal,
+    /// What that quantity of the stock row cost, its share of the row's price.
+    pub cost: i64,
+}
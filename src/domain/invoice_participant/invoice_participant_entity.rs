@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+/// A user's part of an invoice; `amount` is what was debited from their balance.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InvoiceParticipant {
+    pub invoice_id: i32,
+    pub user_id: i32,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewInvoiceParticipant {
+    pub user_id: i32,
+    pub amount: i64,
+}
@@ -0,0 +1 @@
+pub mod invoice_participant_entity;
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+/// What a ledger transaction records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum LedgerKind {
+    Invoice,
+    Payment,
+    Adjustment,
+    Reversal,
+}
+
+/// One balanced set of postings; its entries always sum to zero.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LedgerTransaction {
+    pub id: i32,
+    pub kind: LedgerKind,
+    pub description: String,
+    pub group_id: i32,
+    pub invoice_id: Option<i32>,
+    pub payment_id: Option<i32>,
+    pub created_date: chrono::NaiveDateTime,
+}
+
+/// A posting to a user's account: positive credits it (the group owes them), negative debits it.
+/// `user_id` is `None` for the group's own account, which balances money entering or leaving
+/// the group, such as opening balances.
+///
+/// A posting the user takes as a supplier or customer also names that account, so `supplier.balance`
+/// and `customer.balance` are the sums of the postings tagged with them.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LedgerEntry {
+    pub id: i32,
+    pub transaction_id: i32,
+    pub user_id: Option<i32>,
+    pub amount: i64,
+    pub supplier_id: Option<i32>,
+    pub customer_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromRow)]
+pub struct NewLedgerEntry {
+    pub user_id: Option<i32>,
+    pub amount: i64,
+    pub supplier_id: Option<i32>,
+    pub customer_id: Option<i32>,
+}
+
+impl NewLedgerEntry {
+    pub fn user(user_id: i32, amount: i64) -> Self {
+        NewLedgerEntry {
+            user_id: Some(user_id),
+            amount,
+            supplier_id: None,
+            customer_id: None,
+        }
+    }
+
+    /// The group's own account.
+    pub fn group(amount: i64) -> Self {
+        NewLedgerEntry {
+            user_id: None,
+            amount,
+            supplier_id: None,
+            customer_id: None,
+        }
+    }
+
+    /// Books the posting to the user's supplier account too.
+    pub fn as_supplier(self, supplier_id: i32) -> Self {
+        NewLedgerEntry {
+            supplier_id: Some(supplier_id),
+            ..self
+        }
+    }
+
+    /// Books the posting to the user's customer account too.
+    pub fn as_customer(self, customer_id: i32) -> Self {
+        NewLedgerEntry {
+            customer_id: Some(customer_id),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewLedgerTransaction {
+    pub kind: LedgerKind,
+    pub description: String,
+    pub group_id: i32,
+    pub invoice_id: Option<i32>,
+    pub payment_id: Option<i32>,
+    pub created_date: chrono::NaiveDateTime,
+    pub entries: Vec<NewLedgerEntry>,
+}
+
+impl NewLedgerTransaction {
+    pub fn is_balanced(&self) -> bool {
+        self.entries.iter().map(|entry| i128::from(entry.amount)).sum::<i128>() == 0
+    }
+}
+
+/// A user's posting together with the transaction it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserLedgerEntry {
+    pub transaction_id: i32,
+    pub kind: LedgerKind,
+    pub description: String,
+    pub invoice_id: Option<i32>,
+    pub payment_id: Option<i32>,
+    pub created_date: chrono::NaiveDateTime,
+    pub amount: i64,
+}
+
+/// A history line: the posting and the user's balance right after it.
+#[derive(Debug, Clone)]
+pub struct LedgerHistoryEntry {
+    pub entry: UserLedgerEntry,
+    pub balance: i64,
+}
+
+/// The kinds of account that cache a balance the ledger backs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum LedgerAccount {
+    User,
+    Supplier,
+    Customer,
+}
+
+/// A user, supplier or customer whose cached `balance` doesn't match the sum of their postings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct BalanceDrift {
+    pub account: LedgerAccount,
+    pub id: i32,
+    pub balance: i64,
+    pub ledger_balance: i64,
+}
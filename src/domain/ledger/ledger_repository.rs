@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::ledger::ledger_entity::{BalanceDrift, LedgerTransaction, NewLedgerTransaction, UserLedgerEntry},
+};
+
+#[async_trait]
+pub trait LedgerRepository: Send + Sync {
+    /// Records the transaction and applies its entries to the users' cached `balance`, atomically.
+    /// The transaction must be balanced.
+    async fn create(&self, transaction: NewLedgerTransaction) -> Result<LedgerTransaction>;
+
+    /// The user's postings in the group, oldest first.
+    async fn find_user_entries(&self, group_id: i32, user_id: i32) -> Result<Vec<UserLedgerEntry>>;
+
+    /// Users, suppliers and customers of the group whose cached `balance` differs from the sum of
+    /// their postings in the group.
+    async fn find_drift(&self, group_id: i32) -> Result<Vec<BalanceDrift>>;
+
+    /// Resets every balance [`find_drift`](Self::find_drift) reports to the sum of its postings and
+    /// returns what had drifted, with the balance it had before.
+    async fn rebuild_balances(&self, group_id: i32) -> Result<Vec<BalanceDrift>>;
+}
@@ -0,0 +1,169 @@
+use chrono::Utc;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        ledger::{
+            ledger_entity::{
+                BalanceDrift, LedgerHistoryEntry, LedgerKind, LedgerTransaction, NewLedgerEntry, NewLedgerTransaction,
+            },
+            ledger_repository::LedgerRepository,
+            settlement::{settle, Settlement, Transfer},
+        },
+        payment::{
+            payment_entity::{NewPayment, PaymentMethod},
+            payment_repository::PaymentRepository,
+        },
+        user::{user_repository::UserRepository, user_service::UserService},
+    },
+    interfaces::dtos::ledger_dto::CreateAdjustmentDto,
+};
+
+pub struct LedgerService;
+
+impl LedgerService {
+    /// The user's postings in the group, oldest first, each with the running balance after it.
+    pub async fn get_user_history(
+        group_id: i32,
+        user_id: i32,
+        ledger: &dyn LedgerRepository,
+        users: &dyn UserRepository,
+    ) -> Result<Vec<LedgerHistoryEntry>> {
+        UserService::get_user_by_id(group_id, user_id, users).await?;
+
+        let mut balance = 0;
+        Ok(ledger
+            .find_user_entries(group_id, user_id)
+            .await?
+            .into_iter()
+            .map(|entry| {
+                balance += entry.amount;
+                LedgerHistoryEntry { entry, balance }
+            })
+            .collect())
+    }
+
+    /// Users, suppliers and customers of the group whose cached `balance` disagrees with the ledger.
+    pub async fn find_drift(group_id: i32, ledger: &dyn LedgerRepository) -> Result<Vec<BalanceDrift>> {
+        ledger.find_drift(group_id).await
+    }
+
+    /// Makes the ledger win: cached balances that drifted are overwritten with the ledger's.
+    pub async fn rebuild_balances(group_id: i32, ledger: &dyn LedgerRepository) -> Result<Vec<BalanceDrift>> {
+        ledger.rebuild_balances(group_id).await
+    }
+
+    /// Books a manual correction. Entries without a user go to the group's own account,
+    /// and all of them together must balance.
+    pub async fn create_adjustment(
+        group_id: i32,
+        dto: CreateAdjustmentDto,
+        ledger: &dyn LedgerRepository,
+        users: &dyn UserRepository,
+    ) -> Result<LedgerTransaction> {
+        for user_id in dto.entries.iter().filter_map(|entry| entry.user_id) {
+            UserService::get_user_by_id(group_id, user_id, users)
+                .await
+                .map_err(|_| Error::validation(format!("User {} is not a member of this group", user_id)))?;
+        }
+
+        let transaction = NewLedgerTransaction {
+            kind: LedgerKind::Adjustment,
+            description: dto.description,
+            group_id,
+            invoice_id: None,
+            payment_id: None,
+            created_date: Utc::now().naive_utc(),
+            entries: dto
+                .entries
+                .iter()
+                .map(|entry| match entry.user_id {
+                    Some(user_id) => NewLedgerEntry::user(user_id, entry.amount),
+                    None => NewLedgerEntry::group(entry.amount),
+                })
+                .collect(),
+        };
+        if !transaction.is_balanced() {
+            return Err(Error::validation("Adjustment entries must add up to zero"));
+        }
+
+        ledger.create(transaction).await
+    }
+
+    /// Who should pay whom so every active member of the group ends up at zero.
+    pub async fn plan_settlement(group_id: i32, users: &dyn UserRepository) -> Result<Settlement> {
+        let balances: Vec<(i32, i64)> = users
+            .find_active_by_group(group_id)
+            .await?
+            .iter()
+            .map(|user| (user.id, user.balance))
+            .collect();
+
+        Ok(settle(&balances))
+    }
+
+    /// Books the group's settlement plan as one confirmed payment per transfer, each with its ledger
+    /// transaction.
+    /// `expected` is the plan the caller was shown; if balances moved since, the plan differs and
+    /// nothing is booked.
+    pub async fn record_settlement(
+        group_id: i32,
+        expected: Vec<Transfer>,
+        payments: &dyn PaymentRepository,
+        users: &dyn UserRepository,
+    ) -> Result<Vec<LedgerTransaction>> {
+        let members = users.find_active_by_group(group_id).await?;
+        let balances: Vec<(i32, i64)> = members.iter().map(|user| (user.id, user.balance)).collect();
+        let settlement = settle(&balances);
+
+        if settlement.transfers != expected {
+            return Err(Error::conflict("Balances changed since this settlement was computed"));
+        }
+        if settlement.transfers.is_empty() {
+            return Err(Error::validation("Nothing to settle"));
+        }
+
+        let name = |user_id: i32| {
+            members
+                .iter()
+                .find(|user| user.id == user_id)
+                .map_or_else(String::new, |user| user.name.clone())
+        };
+        let now = Utc::now().naive_utc();
+        let booked = settlement
+            .transfers
+            .iter()
+            .map(|transfer| {
+                let payment = NewPayment {
+                    payer_id: transfer.from_user_id,
+                    payee_id: transfer.to_user_id,
+                    group_id,
+                    amount: transfer.amount,
+                    method: PaymentMethod::Other,
+                    note: "Settlement".to_string(),
+                    created_date: now,
+                };
+                let transaction = NewLedgerTransaction {
+                    kind: LedgerKind::Payment,
+                    description: format!(
+                        "Settlement: {} paid {}",
+                        name(transfer.from_user_id),
+                        name(transfer.to_user_id)
+                    ),
+                    group_id,
+                    invoice_id: None,
+                    // set once the payment is inserted
+                    payment_id: None,
+                    created_date: now,
+                    entries: vec![
+                        NewLedgerEntry::user(transfer.from_user_id, transfer.amount),
+                        NewLedgerEntry::user(transfer.to_user_id, -transfer.amount),
+                    ],
+                };
+                (payment, transaction)
+            })
+            .collect();
+
+        payments.create_confirmed(booked, now).await
+    }
+}
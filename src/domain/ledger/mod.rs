@@ -0,0 +1,4 @@
+pub mod ledger_entity;
+pub mod ledger_repository;
+pub mod ledger_service;
+pub mod settlement;
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+/// A payment that moves `amount` from a member who owes to a member who is owed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub from_user_id: i32,
+    pub to_user_id: i32,
+    pub amount: i64,
+}
+
+/// The transfers that clear the given balances, and whatever they can't clear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    pub transfers: Vec<Transfer>,
+    /// `(user_id, balance)` left over when the balances don't sum to zero, e.g. because an
+    /// inactive member still owes money.
+    pub unsettled: Vec<(i32, i64)>,
+}
+
+/// Plans transfers that bring every balance to zero.
+///
+/// Debts that exactly match a credit are paid in one transfer first; the rest is settled greedily,
+/// the largest debtor paying the largest creditor. That needs at most one transfer fewer than there
+/// are non-zero balances, which is near-minimal; the exact minimum is NP-hard. Ties are broken by
+/// user id, so the same balances always give the same plan.
+pub fn settle(balances: &[(i32, i64)]) -> Settlement {
+    // (user_id, amount still owed or due), both positive
+    let mut debtors: Vec<(i32, i64)> = balances
+        .iter()
+        .filter(|(_, balance)| *balance < 0)
+        .map(|(user_id, balance)| (*user_id, -balance))
+        .collect();
+    let mut creditors: Vec<(i32, i64)> = balances.iter().filter(|(_, balance)| *balance > 0).copied().collect();
+    debtors.sort();
+    creditors.sort();
+
+    let mut transfers = Vec::new();
+
+    debtors.retain(|(debtor, owed)| {
+        let Some(i) = creditors.iter().position(|(_, due)| due == owed) else {
+            return true;
+        };
+        let (creditor, _) = creditors.remove(i);
+        transfers.push(Transfer {
+            from_user_id: *debtor,
+            to_user_id: creditor,
+            amount: *owed,
+        });
+        false
+    });
+
+    loop {
+        debtors.sort_by_key(|(user_id, owed)| (-owed, *user_id));
+        creditors.sort_by_key(|(user_id, due)| (-due, *user_id));
+        let (Some(debtor), Some(creditor)) = (debtors.first_mut(), creditors.first_mut()) else {
+            break;
+        };
+
+        let amount = debtor.1.min(creditor.1);
+        transfers.push(Transfer {
+            from_user_id: debtor.0,
+            to_user_id: creditor.0,
+            amount,
+        });
+        debtor.1 -= amount;
+        creditor.1 -= amount;
+
+        debtors.retain(|(_, owed)| *owed > 0);
+        creditors.retain(|(_, due)| *due > 0);
+    }
+
+    let mut unsettled: Vec<(i32, i64)> = debtors
+        .into_iter()
+        .map(|(user_id, owed)| (user_id, -owed))
+        .chain(creditors)
+        .collect();
+    unsettled.sort();
+
+    Settlement { transfers, unsettled }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(from_user_id: i32, to_user_id: i32, amount: i64) -> Transfer {
+        Transfer {
+            from_user_id,
+            to_user_id,
+            amount,
+        }
+    }
+
+    #[test]
+    fn matching_amounts_settle_in_one_transfer() {
+        let settlement = settle(&[(1, -300), (2, 500), (3, -500), (4, 300)]);
+        assert_eq!(settlement.transfers, [transfer(1, 4, 300), transfer(3, 2, 500)]);
+        assert!(settlement.unsettled.is_empty());
+    }
+
+    #[test]
+    fn largest_debtor_pays_largest_creditor() {
+        let settlement = settle(&[(1, -100), (2, -250), (3, 200), (4, 150)]);
+        assert_eq!(
+            settlement.transfers,
+            [transfer(2, 3, 200), transfer(1, 4, 100), transfer(2, 4, 50)]
+        );
+        assert!(settlement.unsettled.is_empty());
+    }
+
+    #[test]
+    fn plan_does_not_depend_on_input_order() {
+        let balances = [(5, -40), (1, -40), (3, 20), (2, 60)];
+        let mut reversed = balances;
+        reversed.reverse();
+
+        assert_eq!(settle(&balances), settle(&reversed));
+        assert_eq!(settle(&balances).transfers, [transfer(1, 2, 40), transfer(5, 2, 20), transfer(5, 3, 20)]);
+    }
+
+    #[test]
+    fn leftover_is_reported_when_balances_do_not_sum_to_zero() {
+        let settlement = settle(&[(1, -300), (2, 500), (3, 0)]);
+        assert_eq!(settlement.transfers, [transfer(1, 2, 300)]);
+        assert_eq!(settlement.unsettled, [(2, 200)]);
+    }
+}
@@ -1,7 +1,42 @@
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
+use crate::domain::version::Version;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Meal {
-    pub id: i32
-}
\ No newline at end of file
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub group_id: i32,
+    /// Bumped whenever the meal's products change.
+    pub version: i32,
+}
+
+impl Meal {
+    pub fn version(&self) -> Version {
+        Version::of_counter(self.version)
+    }
+}
+
+/// A row of `meal_product`; `position` orders a meal's products starting at 0.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MealProduct {
+    pub meal_id: i32,
+    pub product_id: i32,
+    pub position: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewMeal {
+    pub name: String,
+    pub description: String,
+    pub group_id: i32,
+}
+
+/// A meal together with its products in order.
+#[derive(Debug, Clone)]
+pub struct MealWithProducts {
+    pub meal: Meal,
+    pub products: Vec<crate::domain::product::product_entity::Product>,
+}
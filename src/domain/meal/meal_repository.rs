@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        meal::meal_entity::{Meal, NewMeal},
+        product::product_entity::Product,
+    },
+};
+
+#[async_trait]
+pub trait MealRepository: Send + Sync {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Meal>>;
+
+    /// Meals of the group in name order.
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Meal>>;
+
+    /// The meal's products in their `meal_product.position` order.
+    async fn find_products(&self, meal_id: i32) -> Result<Vec<Product>>;
+
+    /// Inserts the meal and links `product_ids` in the given order, atomically.
+    async fn create(&self, meal: NewMeal, product_ids: &[i32]) -> Result<Meal>;
+
+    /// Links the product at `position`, shifting the products at or after it down by one, and bumps
+    /// the meal's version.
+    ///
+    /// Fails with `Error::PreconditionFailed` if the meal is no longer at `version` and with
+    /// `Error::Conflict` if the product is already part of the meal.
+    async fn add_product(&self, meal_id: i32, version: i32, product_id: i32, position: i32) -> Result<()>;
+
+    /// Unlinks the product, closes the gap in the positions and bumps the meal's version.
+    /// Returns `false` if it wasn't linked; fails like [`MealRepository::add_product`] on a stale `version`.
+    async fn remove_product(&self, meal_id: i32, version: i32, product_id: i32) -> Result<bool>;
+}
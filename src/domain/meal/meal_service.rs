@@ -1,6 +1,125 @@
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        meal::{
+            meal_entity::{Meal, MealWithProducts, NewMeal},
+            meal_repository::MealRepository,
+        },
+        product::product_repository::ProductRepository,
+        version::Version,
+    },
+    interfaces::dtos::meal_dto::{AddMealProductDto, CreateMealDto},
+};
 
 pub struct MealService;
 
 impl MealService {
+    pub async fn get_group_meals(group_id: i32, meals: &dyn MealRepository) -> Result<Vec<MealWithProducts>> {
+        let mut result = Vec::new();
 
+        for meal in meals.find_by_group(group_id).await? {
+            let products = meals.find_products(meal.id).await?;
+            result.push(MealWithProducts { meal, products });
+        }
+
+        Ok(result)
+    }
+
+    pub async fn get_meal(group_id: i32, id: i32, meals: &dyn MealRepository) -> Result<MealWithProducts> {
+        let meal = find_meal(group_id, id, meals).await?;
+        let products = meals.find_products(meal.id).await?;
+
+        Ok(MealWithProducts { meal, products })
+    }
+
+    /// Creates a meal of the group from `product_ids`, kept in the given order.
+    pub async fn create_meal(
+        group_id: i32,
+        dto: CreateMealDto,
+        meals: &dyn MealRepository,
+        products: &dyn ProductRepository,
+    ) -> Result<MealWithProducts> {
+        for (index, product_id) in dto.product_ids.iter().enumerate() {
+            if dto.product_ids[..index].contains(product_id) {
+                return Err(Error::validation(format!("Product {} is listed twice", product_id)));
+            }
+            check_product(group_id, *product_id, products).await?;
+        }
+
+        let meal = meals
+            .create(
+                NewMeal {
+                    name: dto.name,
+                    description: dto.description,
+                    group_id,
+                },
+                &dto.product_ids,
+            )
+            .await?;
+
+        Self::get_meal(group_id, meal.id, meals).await
+    }
+
+    /// Adds a product at `dto.position`, or at the end when no position is given.
+    pub async fn add_product(
+        group_id: i32,
+        meal_id: i32,
+        expected: Version,
+        dto: AddMealProductDto,
+        meals: &dyn MealRepository,
+        products: &dyn ProductRepository,
+    ) -> Result<MealWithProducts> {
+        let meal = Self::get_meal(group_id, meal_id, meals).await?;
+        expected.check(meal.meal.version(), "Meal")?;
+        check_product(group_id, dto.product_id, products).await?;
+
+        let len = meal.products.len() as i32;
+        let position = dto.position.unwrap_or(len);
+        if position > len {
+            return Err(Error::validation(format!("position must be between 0 and {}", len)));
+        }
+
+        meals.add_product(meal_id, meal.meal.version, dto.product_id, position).await?;
+
+        Self::get_meal(group_id, meal_id, meals).await
+    }
+
+    /// Removes a product from the meal. The last product can't be removed since invoices of
+    /// a meal without products would disappear from reports.
+    pub async fn remove_product(
+        group_id: i32,
+        meal_id: i32,
+        expected: Version,
+        product_id: i32,
+        meals: &dyn MealRepository,
+    ) -> Result<MealWithProducts> {
+        let meal = Self::get_meal(group_id, meal_id, meals).await?;
+        expected.check(meal.meal.version(), "Meal")?;
+
+        if !meal.products.iter().any(|product| product.id == product_id) {
+            return Err(Error::not_found("Product is not part of the meal"));
+        }
+        if meal.products.len() == 1 {
+            return Err(Error::validation("A meal needs at least one product"));
+        }
+
+        meals.remove_product(meal_id, meal.meal.version, product_id).await?;
+
+        Self::get_meal(group_id, meal_id, meals).await
+    }
+}
+
+/// Looks the meal up within `group_id`; meals of other groups are reported as not found.
+pub(crate) async fn find_meal(group_id: i32, id: i32, meals: &dyn MealRepository) -> Result<Meal> {
+    meals
+        .find_by_id(group_id, id)
+        .await?
+        .ok_or_else(|| Error::not_found("Meal not found"))
+}
+
+async fn check_product(group_id: i32, product_id: i32, products: &dyn ProductRepository) -> Result<()> {
+    match products.find_by_id(product_id).await? {
+        Some(product) if product.group_id == group_id => Ok(()),
+        _ => Err(Error::validation(format!("Product {} does not exist in this group", product_id))),
+    }
 }
@@ -1,2 +1,3 @@
 pub mod meal_entity;
-pub mod meal_service; 
\ No newline at end of file
+pub mod meal_repository;
+pub mod meal_service; 
@@ -6,9 +6,13 @@ pub mod supplier;
 pub mod meal;
 pub mod invoice;
 pub mod invoice_details;
+pub mod invoice_participant;
 pub mod order;
 pub mod order_details;
 pub mod system_log;
 pub mod group;
 pub mod active_session;
-pub mod customer;
\ No newline at end of file
+pub mod customer;
+pub mod ledger;
+pub mod payment;
+pub mod version;
@@ -1 +1,4 @@
-pub mod order_entity;
\ No newline at end of file
+pub mod order_entity;
+pub mod order_repository;
+pub mod order_service;
+pub mod order_status;
@@ -1,12 +1,42 @@
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
+use crate::domain::{order::order_status::OrderStatus, version::Version};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Order {
     pub id: i32,
     pub is_deleted: bool,
     pub deleted_by: i32,
-    pub created_date: chrono::NaiveDateTime,
-    pub last_modification_date: chrono::NaiveDateTime,
+    pub created_date: NaiveDateTime,
+    pub last_modification_date: NaiveDateTime,
+    pub group_id: i32,
+    pub status: OrderStatus,
+    /// Unset on orders from before it was recorded.
+    pub created_by: Option<i32>,
+    /// The day an ordering round is for; unset on plain orders.
+    pub round_date: Option<NaiveDate>,
+    /// When an ordering round stops taking items and locks, in UTC.
+    pub cutoff: Option<NaiveDateTime>,
+}
+
+impl Order {
+    pub fn version(&self) -> Version {
+        Version::of_timestamp(self.last_modification_date)
+    }
+
+    /// Whether members can still add or remove items at `now`.
+    pub fn takes_items(&self, now: NaiveDateTime) -> bool {
+        self.status == OrderStatus::Open && self.cutoff.is_none_or(|cutoff| now < cutoff)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewOrder {
     pub group_id: i32,
-}
\ No newline at end of file
+    pub created_by: i32,
+    pub created_date: NaiveDateTime,
+    pub round_date: Option<NaiveDate>,
+    pub cutoff: Option<NaiveDateTime>,
+}
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        invoice::invoice_entity::{Invoice, InvoiceDraft},
+        order::{
+            order_entity::{NewOrder, Order},
+            order_status::OrderStatus,
+        },
+        order_details::order_details_entity::OrderDetails,
+        system_log::system_log_entity::NewSystemLog,
+    },
+};
+
+#[async_trait]
+pub trait OrderRepository: Send + Sync {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Order>>;
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Order>>;
+
+    /// The order's lines, by id.
+    async fn find_lines(&self, order_id: i32) -> Result<Vec<OrderDetails>>;
+
+    /// Open ordering rounds whose cut-off is at or before `now`.
+    async fn find_expired_rounds(&self, now: NaiveDateTime) -> Result<Vec<Order>>;
+
+    /// Inserts the order as a draft.
+    async fn create(&self, order: NewOrder) -> Result<Order>;
+
+    /// Inserts the order already open and writes `log`, in one transaction.
+    async fn open_round(&self, order: NewOrder, log: NewSystemLog) -> Result<Order>;
+
+    /// Adds a line for `quantity` of `stock_id` ordered by `user_id` and bumps the order's version, in
+    /// one transaction that also gives the user a customer row and joins them to the order if needed.
+    /// `None` if the order no longer takes items at `now`; a conflict if the user already has the stock
+    /// on it. Other members may order from the same stock on lines of their own.
+    async fn add_line(
+        &self,
+        id: i32,
+        user_id: i32,
+        stock_id: i32,
+        quantity: Option<Decimal>,
+        now: NaiveDateTime,
+    ) -> Result<Option<OrderDetails>>;
+
+    /// Removes the line and bumps the order's version, dropping its customer from the order when
+    /// that was their last line. `false` if the order no longer takes items at `now`.
+    async fn remove_line(&self, id: i32, line_id: i32, now: NaiveDateTime) -> Result<bool>;
+
+    /// Moves the order from `from` to `to` and writes `log`, in one transaction. `None` if the order
+    /// was no longer in `from` or was modified after `last_modified`.
+    async fn transition(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        from: OrderStatus,
+        to: OrderStatus,
+        now: NaiveDateTime,
+        log: NewSystemLog,
+    ) -> Result<Option<Order>>;
+
+    /// Inserts `invoices`, consuming their stock, and marks the fulfilled order invoiced with `log`,
+    /// all in one transaction. `None` if the order was no longer fulfilled or was modified after
+    /// `last_modified`; a conflict, changing nothing, if any stock row was already consumed.
+    async fn invoice(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        invoices: &[InvoiceDraft],
+        now: NaiveDateTime,
+        log: NewSystemLog,
+    ) -> Result<Option<(Order, Vec<Invoice>)>>;
+}
@@ -0,0 +1,416 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        customer::customer_repository::CustomerRepository,
+        invoice::{
+            invoice_entity::{InvoiceDraft, InvoiceWithParticipants, NewInvoice},
+            invoice_service::{find_supplier, invoice_entries},
+        },
+        invoice_participant::invoice_participant_entity::{InvoiceParticipant, NewInvoiceParticipant},
+        meal::{meal_repository::MealRepository, meal_service::find_meal},
+        order::{
+            order_entity::{NewOrder, Order},
+            order_repository::OrderRepository,
+            order_status::OrderStatus,
+        },
+        order_details::order_details_entity::OrderDetails,
+        stock::{
+            stock_entity::{Stock, StockUse},
+            stock_repository::StockRepository,
+            stock_service::{check_oldest_first, draw, share_cost},
+        },
+        supplier::supplier_repository::SupplierRepository,
+        system_log::system_log_entity::NewSystemLog,
+        user::user_repository::UserRepository,
+        version::{stale, Version},
+    },
+    interfaces::dtos::order_dto::{AddOrderItemDto, InvoiceOrderDto, OpenRoundDto},
+};
+
+/// Who is acting on an order.
+pub struct OrderActor {
+    pub user_id: i32,
+    pub group_id: i32,
+    /// Whether the caller's role grants `group:admin`.
+    pub can_manage: bool,
+}
+
+pub struct OrderService;
+
+impl OrderService {
+    pub async fn get_group_orders(group_id: i32, orders: &dyn OrderRepository) -> Result<Vec<Order>> {
+        orders.find_by_group(group_id).await
+    }
+
+    pub async fn get_order(group_id: i32, id: i32, orders: &dyn OrderRepository) -> Result<Order> {
+        orders
+            .find_by_id(group_id, id)
+            .await?
+            .filter(|order| !order.is_deleted)
+            .ok_or_else(|| Error::not_found("Order not found"))
+    }
+
+    pub async fn create_order(user_id: i32, group_id: i32, orders: &dyn OrderRepository) -> Result<Order> {
+        orders
+            .create(NewOrder {
+                group_id,
+                created_by: user_id,
+                created_date: Utc::now().naive_utc(),
+                round_date: None,
+                cutoff: None,
+            })
+            .await
+    }
+
+    /// Opens an ordering round for `dto.date`: members add their items until `dto.cutoff`, when
+    /// [`OrderService::lock_expired_rounds`] locks it.
+    pub async fn open_round(
+        user_id: i32,
+        group_id: i32,
+        dto: OpenRoundDto,
+        orders: &dyn OrderRepository,
+    ) -> Result<Order> {
+        let now = Utc::now().naive_utc();
+        if dto.cutoff <= now {
+            return Err(Error::validation("The cut-off must be in the future"));
+        }
+        if dto.cutoff.date() > dto.date {
+            return Err(Error::validation("The cut-off can't be after the round's day"));
+        }
+
+        let log = NewSystemLog {
+            transaction_type: "ORDER_OPEN".to_string(),
+            description: format!("Ordering round for {} opened until {}", dto.date, dto.cutoff),
+            user_id,
+            group_id,
+        };
+
+        orders
+            .open_round(
+                NewOrder {
+                    group_id,
+                    created_by: user_id,
+                    created_date: now,
+                    round_date: Some(dto.date),
+                    cutoff: Some(dto.cutoff),
+                },
+                log,
+            )
+            .await
+    }
+
+    pub async fn get_items(group_id: i32, id: i32, orders: &dyn OrderRepository) -> Result<Vec<OrderDetails>> {
+        let order = Self::get_order(group_id, id, orders).await?;
+        orders.find_lines(order.id).await
+    }
+
+    /// Puts `dto.stock_id` on the order for the member, while the order takes items.
+    pub async fn add_item(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        dto: AddOrderItemDto,
+        orders: &dyn OrderRepository,
+        stocks: &dyn StockRepository,
+    ) -> Result<OrderDetails> {
+        let order = Self::get_order(group_id, id, orders).await?;
+        let now = Utc::now().naive_utc();
+        if !order.takes_items(now) {
+            return Err(closed());
+        }
+
+        let available = stocks.find_available_by_group(group_id).await?;
+        let stock = available
+            .iter()
+            .find(|stock| stock.id == dto.stock_id)
+            .ok_or_else(|| Error::validation(format!("Stock {} is not available in this group", dto.stock_id)))?;
+        // checked against what is left now; billing checks again
+        draw(stock, dto.quantity)?;
+
+        orders
+            .add_line(order.id, user_id, dto.stock_id, dto.quantity, now)
+            .await?
+            .ok_or_else(closed)
+    }
+
+    /// Takes one of the member's own items off the order, while the order takes items, and returns
+    /// the lines left.
+    pub async fn remove_item(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        line_id: i32,
+        orders: &dyn OrderRepository,
+        customers: &dyn CustomerRepository,
+    ) -> Result<Vec<OrderDetails>> {
+        let order = Self::get_order(group_id, id, orders).await?;
+        let now = Utc::now().naive_utc();
+        if !order.takes_items(now) {
+            return Err(closed());
+        }
+
+        let line = orders
+            .find_lines(order.id)
+            .await?
+            .into_iter()
+            .find(|line| line.id == line_id)
+            .ok_or_else(|| Error::not_found("Order line not found"))?;
+        let owner = match line.customer_id {
+            Some(customer_id) => customers.find_by_id(customer_id).await?.map(|customer| customer.user_id),
+            None => None,
+        };
+        if owner != Some(user_id) {
+            return Err(Error::forbidden("You can only remove your own items"));
+        }
+
+        if !orders.remove_line(order.id, line.id, now).await? {
+            return Err(closed());
+        }
+        orders.find_lines(order.id).await
+    }
+
+    /// Locks every open round whose cut-off has passed at `now`, on behalf of whoever opened it, and
+    /// returns the locked orders. A round changed meanwhile is left for the next run.
+    pub async fn lock_expired_rounds(now: NaiveDateTime, orders: &dyn OrderRepository) -> Result<Vec<Order>> {
+        let mut locked = Vec::new();
+        for order in orders.find_expired_rounds(now).await? {
+            let Some(user_id) = order.created_by else {
+                continue;
+            };
+            let log = NewSystemLog {
+                transaction_type: "ORDER_LOCKED".to_string(),
+                description: format!("Order {} moved from open to locked at its cut-off", order.id),
+                user_id,
+                group_id: order.group_id,
+            };
+
+            let order = orders
+                .transition(order.id, order.last_modification_date, OrderStatus::Open, OrderStatus::Locked, now, log)
+                .await?;
+            if let Some(order) = order {
+                locked.push(order);
+            }
+        }
+
+        Ok(locked)
+    }
+
+    /// Moves the order to `next` if its current state allows it, logging who did it. Only whoever
+    /// created the order or a group admin may move it. Invoicing goes through
+    /// [`OrderService::invoice_order`] instead, which also bills the order.
+    pub async fn transition(
+        actor: OrderActor,
+        id: i32,
+        expected: Version,
+        next: OrderStatus,
+        orders: &dyn OrderRepository,
+    ) -> Result<Order> {
+        if next == OrderStatus::Invoiced {
+            return Err(Error::validation("An order is invoiced by billing it"));
+        }
+        let order = Self::get_order(actor.group_id, id, orders).await?;
+        if !actor.can_manage && order.created_by != Some(actor.user_id) {
+            return Err(Error::forbidden("Only the order's creator or a group admin can change its state"));
+        }
+        expected.check(order.version(), "Order")?;
+        order.status.transition(next)?;
+
+        let log = NewSystemLog {
+            transaction_type: format!("ORDER_{}", next.as_str().to_uppercase()),
+            description: format!("Order {} moved from {} to {}", order.id, order.status.as_str(), next.as_str()),
+            user_id: actor.user_id,
+            group_id: actor.group_id,
+        };
+
+        orders
+            .transition(
+                order.id,
+                order.last_modification_date,
+                order.status,
+                next,
+                Utc::now().naive_utc(),
+                log,
+            )
+            .await?
+            .ok_or_else(|| stale("Order"))
+    }
+
+    /// Bills a fulfilled order: one invoice per supplier of its stock, each charging the ordering
+    /// customers for exactly their lines, and the order marked invoiced so it can't be billed again.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn invoice_order(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        expected: Version,
+        dto: InvoiceOrderDto,
+        orders: &dyn OrderRepository,
+        meals: &dyn MealRepository,
+        suppliers: &dyn SupplierRepository,
+        stocks: &dyn StockRepository,
+        customers: &dyn CustomerRepository,
+        users: &dyn UserRepository,
+    ) -> Result<(Order, Vec<InvoiceWithParticipants>)> {
+        let order = Self::get_order(group_id, id, orders).await?;
+        expected.check(order.version(), "Order")?;
+        order.status.transition(OrderStatus::Invoiced)?;
+        find_meal(group_id, dto.meal_id, meals).await?;
+
+        let lines = orders.find_lines(order.id).await?;
+        if lines.is_empty() {
+            return Err(Error::validation("The order has no lines to invoice"));
+        }
+        let available = stocks.find_available_by_group(group_id).await?;
+        let members = users.find_active_by_group(group_id).await?;
+        // customer id -> user id
+        let mut ordering: BTreeMap<i32, i32> = BTreeMap::new();
+        // stock id -> (user id, quantity) of every line on it
+        let mut by_stock: BTreeMap<i32, Vec<(i32, Option<Decimal>)>> = BTreeMap::new();
+
+        for line in &lines {
+            let customer_id = line
+                .customer_id
+                .ok_or_else(|| Error::validation(format!("Order line {} has no customer", line.id)))?;
+
+            let user_id = match ordering.get(&customer_id) {
+                Some(user_id) => *user_id,
+                None => {
+                    let customer = customers
+                        .find_by_id(customer_id)
+                        .await?
+                        .filter(|customer| members.iter().any(|user| user.id == customer.user_id))
+                        .ok_or_else(|| {
+                            Error::validation(format!("Customer {} is not an active member of this group", customer_id))
+                        })?;
+                    ordering.insert(customer_id, customer.user_id);
+                    customer.user_id
+                }
+            };
+
+            by_stock.entry(line.stock_id).or_default().push((user_id, line.quantity));
+        }
+
+        // supplier id -> (stock used, user id -> amount)
+        let mut by_supplier: BTreeMap<i32, (Vec<StockUse>, BTreeMap<i32, i64>)> = BTreeMap::new();
+        let mut drawn = Vec::with_capacity(by_stock.len());
+        for (stock_id, shares) in by_stock {
+            let stock = available
+                .iter()
+                .find(|stock| stock.id == stock_id)
+                .ok_or_else(|| Error::conflict(format!("Stock {} is no longer available", stock_id)))?;
+            let supplier_id = stock
+                .supplier_id
+                .ok_or_else(|| Error::validation(format!("Stock {} has no supplier to bill", stock.id)))?;
+
+            // members sharing a lot each say how much of it they want; drawn once, for all of them
+            let used = match shares.as_slice() {
+                [(_, quantity)] => draw(stock, *quantity)?,
+                _ => {
+                    let quantities = shares
+                        .iter()
+                        .map(|(_, quantity)| {
+                            quantity.ok_or_else(|| {
+                                Error::validation(format!(
+                                    "Stock {} is on several lines, so each of them needs a quantity",
+                                    stock.id
+                                ))
+                            })
+                        })
+                        .collect::<Result<Vec<Decimal>>>()?;
+                    draw(stock, Some(quantities.iter().sum()))?
+                }
+            };
+            let quantities: Vec<Decimal> =
+                shares.iter().map(|(_, quantity)| quantity.unwrap_or(used.quantity)).collect();
+
+            let (uses, amounts) = by_supplier.entry(supplier_id).or_default();
+            for ((user_id, _), cost) in shares.iter().zip(share_cost(&used, &quantities)?) {
+                *amounts.entry(*user_id).or_default() += cost;
+            }
+            uses.push(used);
+            drawn.push(used);
+        }
+        let left: Vec<Stock> = available
+            .into_iter()
+            .map(|mut stock| {
+                if let Some(used) = drawn.iter().find(|used| used.stock_id == stock.id) {
+                    stock.remaining -= used.quantity;
+                }
+                stock
+            })
+            .collect();
+        check_oldest_first(&left, &drawn)?;
+
+        let customers: Vec<(i32, i32)> = ordering.iter().map(|(customer_id, user_id)| (*user_id, *customer_id)).collect();
+        let now = Utc::now().naive_utc();
+        let mut drafts = Vec::with_capacity(by_supplier.len());
+        for (supplier_id, (stock, amounts)) in by_supplier {
+            let supplier = find_supplier(group_id, supplier_id, suppliers).await?;
+            let participants: Vec<NewInvoiceParticipant> = amounts
+                .into_iter()
+                .map(|(user_id, amount)| NewInvoiceParticipant { user_id, amount })
+                .collect();
+            let price = participants.iter().map(|participant| participant.amount).sum();
+            let entries = invoice_entries(&supplier, price, &participants, &customers);
+
+            drafts.push(InvoiceDraft {
+                invoice: NewInvoice {
+                    price,
+                    created_date: now,
+                    meal_id: dto.meal_id,
+                    group_id,
+                    supplier_id,
+                    order_id: Some(order.id),
+                },
+                stock,
+                participants,
+                entries,
+            });
+        }
+
+        let log = NewSystemLog {
+            transaction_type: "ORDER_INVOICED".to_string(),
+            description: format!(
+                "Order {} moved from fulfilled to invoiced, billed to {} supplier(s) for {}",
+                order.id,
+                drafts.len(),
+                drafts.iter().map(|draft| draft.invoice.price).sum::<i64>()
+            ),
+            user_id,
+            group_id,
+        };
+
+        let (order, invoices) = orders
+            .invoice(order.id, order.last_modification_date, &drafts, now, log)
+            .await?
+            .ok_or_else(|| stale("Order"))?;
+
+        let invoices = invoices
+            .into_iter()
+            .zip(drafts)
+            .map(|(invoice, draft)| InvoiceWithParticipants {
+                participants: draft
+                    .participants
+                    .into_iter()
+                    .map(|participant| InvoiceParticipant {
+                        invoice_id: invoice.id,
+                        user_id: participant.user_id,
+                        amount: participant.amount,
+                    })
+                    .collect(),
+                invoice,
+            })
+            .collect();
+
+        Ok((order, invoices))
+    }
+}
+
+fn closed() -> Error {
+    Error::conflict("The order no longer takes items")
+}
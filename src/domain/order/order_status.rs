@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::application::error::{Error, Result};
+
+/// Where an order is in its life: drafted, opened for members to add to, locked against changes,
+/// fulfilled by the suppliers and finally invoiced. It can be cancelled any time before fulfilment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum OrderStatus {
+    Draft,
+    Open,
+    Locked,
+    Fulfilled,
+    Invoiced,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn can_become(self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+
+        matches!(
+            (self, next),
+            (Draft, Open)
+                | (Open, Locked)
+                | (Locked, Fulfilled)
+                | (Fulfilled, Invoiced)
+                | (Draft | Open | Locked, Cancelled)
+        )
+    }
+
+    /// `next` if an order in this state may move there, a conflict otherwise.
+    pub fn transition(self, next: OrderStatus) -> Result<OrderStatus> {
+        if !self.can_become(next) {
+            return Err(Error::conflict(format!(
+                "An order that is {} can't become {}",
+                self.as_str(),
+                next.as_str()
+            )));
+        }
+        Ok(next)
+    }
+
+    /// The value stored in `"order".status`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderStatus::Draft => "draft",
+            OrderStatus::Open => "open",
+            OrderStatus::Locked => "locked",
+            OrderStatus::Fulfilled => "fulfilled",
+            OrderStatus::Invoiced => "invoiced",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderStatus::*;
+    use super::*;
+
+    const ALL: [OrderStatus; 6] = [Draft, Open, Locked, Fulfilled, Invoiced, Cancelled];
+
+    #[test]
+    fn happy_path_moves_one_step_at_a_time() {
+        let path = [Draft, Open, Locked, Fulfilled, Invoiced];
+        for pair in path.windows(2) {
+            assert_eq!(pair[0].transition(pair[1]).unwrap(), pair[1]);
+        }
+        assert!(Draft.transition(Locked).is_err());
+        assert!(Locked.transition(Open).is_err());
+    }
+
+    #[test]
+    fn only_unfulfilled_orders_can_be_cancelled() {
+        let cancellable: Vec<_> = ALL.into_iter().filter(|status| status.can_become(Cancelled)).collect();
+        assert_eq!(cancellable, [Draft, Open, Locked]);
+    }
+
+    #[test]
+    fn invoiced_and_cancelled_are_final() {
+        for next in ALL {
+            assert!(!Invoiced.can_become(next));
+            assert!(!Cancelled.can_become(next));
+        }
+    }
+}
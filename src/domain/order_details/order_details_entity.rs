@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
@@ -6,4 +7,8 @@ pub struct OrderDetails {
     pub id: i32,
     pub order_id: i32,
     pub stock_id: i32,
-}
\ No newline at end of file
+    /// The customer who ordered the line; unset on lines from before it was recorded.
+    pub customer_id: Option<i32>,
+    /// How much of the stock row it asks for, in its unit; all that is left when billed if unset.
+    pub quantity: Option<Decimal>,
+}
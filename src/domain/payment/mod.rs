@@ -0,0 +1,3 @@
+pub mod payment_entity;
+pub mod payment_repository;
+pub mod payment_service;
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Cash,
+    BankTransfer,
+    Other,
+}
+
+/// A payment starts out pending; the payee either confirms it, which moves the balances,
+/// or rejects it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    Confirmed,
+    Rejected,
+}
+
+/// Money the payer says they handed to the payee, outside the app.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Payment {
+    pub id: i32,
+    pub payer_id: i32,
+    pub payee_id: i32,
+    pub group_id: i32,
+    pub amount: i64,
+    pub method: PaymentMethod,
+    pub note: String,
+    pub status: PaymentStatus,
+    pub created_date: chrono::NaiveDateTime,
+    /// When the payee confirmed or rejected it.
+    pub resolved_date: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPayment {
+    pub payer_id: i32,
+    pub payee_id: i32,
+    pub group_id: i32,
+    pub amount: i64,
+    pub method: PaymentMethod,
+    pub note: String,
+    pub created_date: chrono::NaiveDateTime,
+}
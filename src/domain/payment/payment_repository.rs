@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        ledger::ledger_entity::{LedgerTransaction, NewLedgerTransaction},
+        payment::payment_entity::{NewPayment, Payment},
+        system_log::system_log_entity::NewSystemLog,
+    },
+};
+
+#[async_trait]
+pub trait PaymentRepository: Send + Sync {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Payment>>;
+
+    /// Payments of the group, newest first.
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Payment>>;
+
+    /// Inserts the payment as pending.
+    async fn create(&self, payment: NewPayment) -> Result<Payment>;
+
+    /// Inserts each payment as confirmed at `now` and posts its transaction, linked to it, all in one
+    /// transaction: either every payment is booked or none.
+    async fn create_confirmed(
+        &self,
+        payments: Vec<(NewPayment, NewLedgerTransaction)>,
+        now: NaiveDateTime,
+    ) -> Result<Vec<LedgerTransaction>>;
+
+    /// Marks a pending payment confirmed, posts `transaction` (which gets linked to the payment)
+    /// and writes `log`, all in one transaction. `None` if the payment wasn't pending anymore.
+    async fn confirm(
+        &self,
+        id: i32,
+        now: NaiveDateTime,
+        transaction: NewLedgerTransaction,
+        log: NewSystemLog,
+    ) -> Result<Option<Payment>>;
+
+    /// Marks a pending payment rejected and writes `log`. `None` if it wasn't pending anymore.
+    async fn reject(&self, id: i32, now: NaiveDateTime, log: NewSystemLog) -> Result<Option<Payment>>;
+}
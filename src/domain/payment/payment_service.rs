@@ -0,0 +1,146 @@
+use chrono::Utc;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        ledger::ledger_entity::{LedgerKind, NewLedgerEntry, NewLedgerTransaction},
+        payment::{
+            payment_entity::{NewPayment, Payment, PaymentStatus},
+            payment_repository::PaymentRepository,
+        },
+        system_log::system_log_entity::NewSystemLog,
+        user::user_repository::UserRepository,
+    },
+    interfaces::dtos::payment_dto::CreatePaymentDto,
+};
+
+pub struct PaymentService;
+
+impl PaymentService {
+    pub async fn get_group_payments(group_id: i32, payments: &dyn PaymentRepository) -> Result<Vec<Payment>> {
+        payments.find_by_group(group_id).await
+    }
+
+    pub async fn get_payment(group_id: i32, id: i32, payments: &dyn PaymentRepository) -> Result<Payment> {
+        payments
+            .find_by_id(group_id, id)
+            .await?
+            .ok_or_else(|| Error::not_found("Payment not found"))
+    }
+
+    /// Records that `payer_id` paid someone in their group; balances only move once the payee confirms.
+    pub async fn create_payment(
+        payer_id: i32,
+        group_id: i32,
+        dto: CreatePaymentDto,
+        payments: &dyn PaymentRepository,
+        users: &dyn UserRepository,
+    ) -> Result<Payment> {
+        if dto.payee_id == payer_id {
+            return Err(Error::validation("You can't pay yourself"));
+        }
+        let members = users.find_active_by_group(group_id).await?;
+        if !members.iter().any(|user| user.id == dto.payee_id) {
+            return Err(Error::validation(format!(
+                "User {} is not an active member of this group",
+                dto.payee_id
+            )));
+        }
+
+        payments
+            .create(NewPayment {
+                payer_id,
+                payee_id: dto.payee_id,
+                group_id,
+                amount: dto.amount,
+                method: dto.method,
+                note: dto.note,
+                created_date: Utc::now().naive_utc(),
+            })
+            .await
+    }
+
+    /// The payee acknowledges the money: the payer's balance goes up by the amount, the payee's down.
+    pub async fn confirm_payment(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        payments: &dyn PaymentRepository,
+    ) -> Result<Payment> {
+        let payment = Self::pending_payment_of_payee(user_id, group_id, id, payments).await?;
+        let now = Utc::now().naive_utc();
+
+        let transaction = NewLedgerTransaction {
+            kind: LedgerKind::Payment,
+            description: format!("Payment {}", payment.id),
+            group_id,
+            invoice_id: None,
+            payment_id: Some(payment.id),
+            created_date: now,
+            entries: vec![
+                NewLedgerEntry::user(payment.payer_id, payment.amount),
+                NewLedgerEntry::user(payment.payee_id, -payment.amount),
+            ],
+        };
+        let log = NewSystemLog {
+            transaction_type: "PAYMENT_CONFIRMED".to_string(),
+            description: format!(
+                "Payment {} of {} from user {} to user {} confirmed",
+                payment.id, payment.amount, payment.payer_id, payment.payee_id
+            ),
+            user_id,
+            group_id,
+        };
+
+        payments
+            .confirm(payment.id, now, transaction, log)
+            .await?
+            .ok_or_else(already_resolved)
+    }
+
+    pub async fn reject_payment(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        payments: &dyn PaymentRepository,
+    ) -> Result<Payment> {
+        let payment = Self::pending_payment_of_payee(user_id, group_id, id, payments).await?;
+
+        let log = NewSystemLog {
+            transaction_type: "PAYMENT_REJECTED".to_string(),
+            description: format!(
+                "Payment {} of {} from user {} to user {} rejected",
+                payment.id, payment.amount, payment.payer_id, payment.payee_id
+            ),
+            user_id,
+            group_id,
+        };
+
+        payments
+            .reject(payment.id, Utc::now().naive_utc(), log)
+            .await?
+            .ok_or_else(already_resolved)
+    }
+
+    async fn pending_payment_of_payee(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        payments: &dyn PaymentRepository,
+    ) -> Result<Payment> {
+        let payment = Self::get_payment(group_id, id, payments).await?;
+
+        if payment.payee_id != user_id {
+            return Err(Error::forbidden("Only the payee can confirm or reject a payment"));
+        }
+        if payment.status != PaymentStatus::Pending {
+            return Err(already_resolved());
+        }
+
+        Ok(payment)
+    }
+}
+
+fn already_resolved() -> Error {
+    Error::conflict("Payment has already been confirmed or rejected")
+}
@@ -1,2 +1,3 @@
 pub mod product_entity; 
-pub mod product_service;
\ No newline at end of file
+pub mod product_repository;
+pub mod product_service;
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::{application::error::Result, domain::product::product_entity::Product};
+
+#[async_trait]
+pub trait ProductRepository: Send + Sync {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Product>>;
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Product>>;
+
+    async fn create(&self, name: &str, group_id: i32) -> Result<Product>;
+}
@@ -1,3 +1,4 @@
+pub mod permission;
 pub mod role_entity;
 pub mod role_repository;
-pub mod role_service;
\ No newline at end of file
+pub mod role_service;
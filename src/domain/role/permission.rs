@@ -0,0 +1,41 @@
+use serde::{Serialize, Serializer};
+
+/// An action a role can be granted through the `role_permission` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    InvoiceCreate,
+    InvoiceDelete,
+    UserManage,
+    GroupAdmin,
+    StockPurchase,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 5] = [
+        Permission::InvoiceCreate,
+        Permission::InvoiceDelete,
+        Permission::UserManage,
+        Permission::GroupAdmin,
+        Permission::StockPurchase,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::InvoiceCreate => "invoice:create",
+            Permission::InvoiceDelete => "invoice:delete",
+            Permission::UserManage => "user:manage",
+            Permission::GroupAdmin => "group:admin",
+            Permission::StockPurchase => "stock:purchase",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Permission> {
+        Self::ALL.into_iter().find(|permission| permission.as_str() == value)
+    }
+}
+
+impl Serialize for Permission {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
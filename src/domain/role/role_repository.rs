@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::role::{permission::Permission, role_entity::Role},
+};
+
+#[async_trait]
+pub trait RoleRepository: Send + Sync {
+    async fn find_all(&self) -> Result<Vec<Role>>;
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Role>>;
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Role>>;
+
+    /// Permissions granted by the user's current role, in name order.
+    async fn find_user_permissions(&self, user_id: i32) -> Result<Vec<Permission>>;
+
+    async fn user_has_permission(&self, user_id: i32, permission: Permission) -> Result<bool>;
+}
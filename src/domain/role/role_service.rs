@@ -1,5 +1,19 @@
+use crate::{
+    application::error::Result,
+    domain::role::{permission::Permission, role_repository::RoleRepository},
+};
+
 pub struct RoleService;
 
 impl RoleService {
+    /// Permissions granted by the user's current role. Unknown permission names in the table are ignored.
+    pub async fn get_user_permissions(user_id: i32, roles: &dyn RoleRepository) -> Result<Vec<Permission>> {
+        roles.find_user_permissions(user_id).await
+    }
 
+    /// Checks the user's current role rather than the one baked into their access token,
+    /// so role changes apply immediately.
+    pub async fn user_has_permission(user_id: i32, permission: Permission, roles: &dyn RoleRepository) -> Result<bool> {
+        roles.user_has_permission(user_id, permission).await
+    }
 }
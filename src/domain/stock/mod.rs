@@ -1 +1,3 @@
-pub mod stock_entity; 
\ No newline at end of file
+pub mod stock_entity; 
+pub mod stock_repository;
+pub mod stock_service;
@@ -1,10 +1,139 @@
+use chrono::NaiveDateTime;
+use rust_decimal::{prelude::ToPrimitive, Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
 
+use crate::{
+    application::error::{Error, Result},
+    domain::version::Version,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[sqlx(type_name = "varchar")]
+pub enum Unit {
+    #[serde(rename = "piece")]
+    #[sqlx(rename = "piece")]
+    Piece,
+    #[serde(rename = "g")]
+    #[sqlx(rename = "g")]
+    Gram,
+    #[serde(rename = "kg")]
+    #[sqlx(rename = "kg")]
+    Kilogram,
+    #[serde(rename = "ml")]
+    #[sqlx(rename = "ml")]
+    Millilitre,
+    #[serde(rename = "l")]
+    #[sqlx(rename = "l")]
+    Litre,
+}
+
+/// One purchase of a product: a lot of `quantity` that invoices and orders draw down.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Stock {
     pub id: i32,
+    /// What the whole lot cost.
     pub price: i64,
+    /// Set once nothing of the lot remains.
     pub consumed: bool,
     pub product_id: i32,
-}
\ No newline at end of file
+    /// Who it was bought from, where known.
+    pub supplier_id: Option<i32>,
+    pub unit: Unit,
+    /// How much was bought, in `unit`.
+    pub quantity: Decimal,
+    /// How much is left, in `unit`.
+    pub remaining: Decimal,
+    pub purchased_date: NaiveDateTime,
+    pub version: i32,
+}
+
+impl Stock {
+    pub fn version(&self) -> Version {
+        Version::of_counter(self.version)
+    }
+
+    /// The share of the lot's price that `quantity` of it is worth, to the nearest unit of money. A
+    /// validation error if that can't be worked out, e.g. for an empty lot.
+    pub fn cost_of(&self, quantity: Decimal) -> Result<i64> {
+        Decimal::from(self.price)
+            .checked_mul(quantity)
+            .and_then(|total| total.checked_div(self.quantity))
+            .and_then(|cost| cost.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero).to_i64())
+            .ok_or_else(|| Error::validation(format!("The cost of {} of stock {} is out of range", quantity, self.id)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewStock {
+    pub product_id: i32,
+    pub supplier_id: Option<i32>,
+    pub price: i64,
+    pub unit: Unit,
+    pub quantity: Decimal,
+    pub purchased_date: NaiveDateTime,
+}
+
+/// How much of a lot an invoice or order takes, in the lot's unit, and what that much of it cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StockUse {
+    pub stock_id: i32,
+    pub quantity: Decimal,
+    pub cost: i64,
+}
+
+/// A recount of a lot: `change` is the counted remainder minus what was recorded before.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StockAdjustment {
+    pub id: i32,
+    pub stock_id: i32,
+    pub change: Decimal,
+    pub reason: String,
+    pub user_id: i32,
+    pub created_date: NaiveDateTime,
+}
+
+/// What `user_id` counted of a lot, and why it differs.
+#[derive(Debug, Clone)]
+pub struct StockCount {
+    pub remaining: Decimal,
+    pub reason: String,
+    pub user_id: i32,
+    pub created_date: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot(price: i64, quantity: i64) -> Stock {
+        Stock {
+            id: 1,
+            price,
+            consumed: false,
+            product_id: 1,
+            supplier_id: None,
+            unit: Unit::Kilogram,
+            quantity: Decimal::from(quantity),
+            remaining: Decimal::from(quantity),
+            purchased_date: NaiveDateTime::default(),
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn cost_is_the_price_share_of_the_quantity() {
+        assert_eq!(lot(1000, 2).cost_of(Decimal::from(2)).unwrap(), 1000);
+        assert_eq!(lot(1000, 2).cost_of(Decimal::new(25, 2)).unwrap(), 125);
+        // 1000 / 3 = 333.33..
+        assert_eq!(lot(1000, 3).cost_of(Decimal::ONE).unwrap(), 333);
+        assert_eq!(lot(5, 2).cost_of(Decimal::ONE).unwrap(), 3);
+    }
+
+    #[test]
+    fn cost_out_of_range_is_an_error() {
+        assert!(lot(i64::MAX, 1).cost_of(Decimal::from(i64::MAX)).is_err());
+        assert!(lot(1000, 0).cost_of(Decimal::ONE).is_err());
+    }
+}
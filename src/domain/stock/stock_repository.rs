@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::stock::stock_entity::{NewStock, Stock, StockAdjustment, StockCount},
+};
+
+#[async_trait]
+pub trait StockRepository: Send + Sync {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Stock>>;
+
+    /// The stock row if it is of one of the group's products.
+    async fn find_in_group(&self, group_id: i32, id: i32) -> Result<Option<Stock>>;
+
+    async fn find_by_product(&self, product_id: i32) -> Result<Vec<Stock>>;
+
+    /// Stock of the group's products that hasn't been consumed yet.
+    async fn find_available_by_group(&self, group_id: i32) -> Result<Vec<Stock>>;
+
+    /// The lot's recounts, oldest first.
+    async fn find_adjustments(&self, stock_id: i32) -> Result<Vec<StockAdjustment>>;
+
+    /// Inserts a purchased lot with all of it remaining.
+    async fn create(&self, stock: NewStock) -> Result<Stock>;
+
+    /// Sets what remains of the lot to the counted amount, bumps its version and records the
+    /// difference, in one transaction. `None` if there is no such lot; fails with
+    /// `Error::PreconditionFailed` if it is no longer at `version`.
+    async fn adjust(&self, id: i32, version: i32, count: StockCount) -> Result<Option<(Stock, StockAdjustment)>>;
+}
@@ -0,0 +1,228 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        invoice::{invoice_service::find_supplier, split_strategy::split_by_weights},
+        product::product_repository::ProductRepository,
+        stock::{
+            stock_entity::{NewStock, Stock, StockAdjustment, StockCount, StockUse},
+            stock_repository::StockRepository,
+        },
+        supplier::supplier_repository::SupplierRepository,
+        version::Version,
+    },
+    interfaces::dtos::stock_dto::{AdjustStockDto, CreateStockDto},
+};
+
+/// Quantities are stored with this many decimals.
+const QUANTITY_SCALE: u32 = 3;
+
+pub struct StockService;
+
+impl StockService {
+    /// The group's lots that still have something left.
+    pub async fn get_inventory(group_id: i32, stocks: &dyn StockRepository) -> Result<Vec<Stock>> {
+        stocks.find_available_by_group(group_id).await
+    }
+
+    pub async fn get_stock(group_id: i32, id: i32, stocks: &dyn StockRepository) -> Result<Stock> {
+        stocks
+            .find_in_group(group_id, id)
+            .await?
+            .ok_or_else(|| Error::not_found("Stock not found"))
+    }
+
+    pub async fn get_adjustments(group_id: i32, id: i32, stocks: &dyn StockRepository) -> Result<Vec<StockAdjustment>> {
+        let stock = Self::get_stock(group_id, id, stocks).await?;
+        stocks.find_adjustments(stock.id).await
+    }
+
+    /// Records a purchase of one of the group's products as a new lot, all of it remaining.
+    pub async fn add_purchase(
+        group_id: i32,
+        dto: CreateStockDto,
+        stocks: &dyn StockRepository,
+        products: &dyn ProductRepository,
+        suppliers: &dyn SupplierRepository,
+    ) -> Result<Stock> {
+        check_quantity(dto.quantity, "quantity")?;
+        if dto.quantity.is_zero() {
+            return Err(Error::validation("quantity must be positive"));
+        }
+        products
+            .find_by_id(dto.product_id)
+            .await?
+            .filter(|product| product.group_id == group_id)
+            .ok_or_else(|| Error::validation(format!("Product {} does not exist in this group", dto.product_id)))?;
+        if let Some(supplier_id) = dto.supplier_id {
+            find_supplier(group_id, supplier_id, suppliers).await?;
+        }
+
+        stocks
+            .create(NewStock {
+                product_id: dto.product_id,
+                supplier_id: dto.supplier_id,
+                price: dto.price,
+                unit: dto.unit,
+                quantity: dto.quantity,
+                purchased_date: Utc::now().naive_utc(),
+            })
+            .await
+    }
+
+    /// Sets what is left of the lot to what `user_id` counted, keeping the difference and its reason.
+    /// Refused if the lot changed since the caller read it at `expected`, or if the count is more than
+    /// was bought.
+    pub async fn adjust(
+        user_id: i32,
+        group_id: i32,
+        id: i32,
+        expected: Version,
+        dto: AdjustStockDto,
+        stocks: &dyn StockRepository,
+    ) -> Result<(Stock, StockAdjustment)> {
+        check_quantity(dto.remaining, "remaining")?;
+        if dto.reason.trim().is_empty() {
+            return Err(Error::validation("An adjustment needs a reason"));
+        }
+        let stock = Self::get_stock(group_id, id, stocks).await?;
+        expected.check(stock.version(), "Stock")?;
+        if dto.remaining > stock.quantity {
+            return Err(Error::validation(format!(
+                "Only {} was bought, so no more than that can be left",
+                stock.quantity.normalize()
+            )));
+        }
+
+        stocks
+            .adjust(
+                stock.id,
+                stock.version,
+                StockCount {
+                    remaining: dto.remaining,
+                    reason: dto.reason,
+                    user_id,
+                    created_date: Utc::now().naive_utc(),
+                },
+            )
+            .await?
+            .ok_or_else(|| Error::not_found("Stock not found"))
+    }
+}
+
+/// `quantity` of the lot, or all that is left of it when unset. A validation error for a quantity
+/// that isn't positive, a conflict if less than that is left.
+pub(crate) fn draw(stock: &Stock, quantity: Option<Decimal>) -> Result<StockUse> {
+    let quantity = quantity.unwrap_or(stock.remaining);
+    check_quantity(quantity, "quantity")?;
+    if quantity.is_zero() {
+        return Err(Error::validation(format!("The quantity of stock {} must be positive", stock.id)));
+    }
+    if quantity > stock.remaining {
+        return Err(Error::conflict(format!(
+            "Only {} of stock {} is left",
+            stock.remaining.normalize(),
+            stock.id
+        )));
+    }
+
+    Ok(StockUse {
+        stock_id: stock.id,
+        quantity,
+        cost: stock.cost_of(quantity)?,
+    })
+}
+
+/// The cost of `used` shared out over the `quantities` it was drawn for, in proportion to them; the
+/// shares add up to the cost exactly.
+pub(crate) fn share_cost(used: &StockUse, quantities: &[Decimal]) -> Result<Vec<i64>> {
+    let weights = quantities
+        .iter()
+        .map(|quantity| {
+            let mut scaled = *quantity;
+            scaled.rescale(QUANTITY_SCALE);
+            i64::try_from(scaled.mantissa()).map_err(|_| Error::validation("Quantity is too large"))
+        })
+        .collect::<Result<Vec<i64>>>()?;
+
+    Ok(split_by_weights(used.cost, &weights))
+}
+
+/// `quantity` of the product from `lots`, first in first out: the oldest lot with something left is
+/// used up before the next one is touched. A validation error if the product's lots are in different
+/// units, a conflict if they don't hold that much between them.
+pub(crate) fn draw_oldest_first(lots: &[Stock], product_id: i32, quantity: Decimal) -> Result<Vec<StockUse>> {
+    check_quantity(quantity, "quantity")?;
+    if quantity.is_zero() {
+        return Err(Error::validation(format!("The quantity of product {} must be positive", product_id)));
+    }
+    let mut lots: Vec<&Stock> = lots
+        .iter()
+        .filter(|stock| stock.product_id == product_id && stock.remaining > Decimal::ZERO)
+        .collect();
+    if lots.iter().any(|stock| stock.unit != lots[0].unit) {
+        return Err(Error::validation(format!(
+            "Product {} is stocked in different units, so its lots have to be picked",
+            product_id
+        )));
+    }
+    lots.sort_by_key(|stock| (stock.purchased_date, stock.id));
+
+    let mut left = quantity;
+    let mut uses = Vec::new();
+    for stock in lots {
+        if left.is_zero() {
+            break;
+        }
+        let used = draw(stock, Some(left.min(stock.remaining)))?;
+        left -= used.quantity;
+        uses.push(used);
+    }
+    if !left.is_zero() {
+        return Err(Error::conflict(format!(
+            "Only {} of product {} is left",
+            (quantity - left).normalize(),
+            product_id
+        )));
+    }
+
+    Ok(uses)
+}
+
+/// Lots are used first in first out, however they were picked: a conflict if `uses` drew on a lot
+/// while an older one of the same product and unit still has something left. `lots` are as they
+/// stand after the draws.
+pub(crate) fn check_oldest_first(lots: &[Stock], uses: &[StockUse]) -> Result<()> {
+    for used in uses {
+        let Some(stock) = lots.iter().find(|stock| stock.id == used.stock_id) else {
+            continue;
+        };
+        let older = lots.iter().find(|lot| {
+            lot.product_id == stock.product_id
+                && lot.unit == stock.unit
+                && (lot.purchased_date, lot.id) < (stock.purchased_date, stock.id)
+                && lot.remaining > Decimal::ZERO
+        });
+        if let Some(older) = older {
+            return Err(Error::conflict(format!(
+                "Stock {} is older than stock {} and still has {} left, so it goes first",
+                older.id,
+                stock.id,
+                older.remaining.normalize()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_quantity(quantity: Decimal, field: &str) -> Result<()> {
+    if quantity < Decimal::ZERO {
+        return Err(Error::validation(format!("{} must not be negative", field)));
+    }
+    if quantity.normalize().scale() > QUANTITY_SCALE {
+        return Err(Error::validation(format!("{} has at most {} decimals", field, QUANTITY_SCALE)));
+    }
+    Ok(())
+}
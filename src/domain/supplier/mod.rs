@@ -1,2 +1,3 @@
 pub mod supplier_entity; 
-pub mod supplier_service;
\ No newline at end of file
+pub mod supplier_repository;
+pub mod supplier_service;
@@ -4,6 +4,6 @@ use sqlx::prelude::FromRow;
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Supplier {
     pub id: i32,
-    pub balance: i32,
+    pub balance: i64,
     pub user_id: i32,
 }
\ No newline at end of file
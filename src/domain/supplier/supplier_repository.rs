@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::{application::error::Result, domain::supplier::supplier_entity::Supplier};
+
+#[async_trait]
+pub trait SupplierRepository: Send + Sync {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Supplier>>;
+
+    async fn find_by_user(&self, user_id: i32) -> Result<Option<Supplier>>;
+
+    /// Suppliers whose user belongs to the group.
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Supplier>>;
+}
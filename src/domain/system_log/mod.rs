@@ -1 +1,2 @@
-pub mod system_log_entity; 
\ No newline at end of file
+pub mod system_log_entity; 
+pub mod system_log_repository;
@@ -2,11 +2,19 @@ use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Model {
+pub struct SystemLog {
     pub id: i32,
     pub transaction_type: String,
     pub description: String,
     pub date: chrono::NaiveDateTime,
     pub user_id: i32,
     pub group_id: i32,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone)]
+pub struct NewSystemLog {
+    pub transaction_type: String,
+    pub description: String,
+    pub user_id: i32,
+    pub group_id: i32,
+}
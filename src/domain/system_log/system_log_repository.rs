@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::system_log::system_log_entity::{NewSystemLog, SystemLog},
+};
+
+#[async_trait]
+pub trait SystemLogRepository: Send + Sync {
+    async fn create(&self, entry: NewSystemLog) -> Result<SystemLog>;
+
+    /// Log entries of the group, newest first.
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<SystemLog>>;
+}
@@ -1,3 +1,5 @@
+pub mod email_confirmation_entity;
+pub mod password_reset_entity;
 pub mod user_entity;
 pub mod user_repository;
-pub mod user_service;
\ No newline at end of file
+pub mod user_service;
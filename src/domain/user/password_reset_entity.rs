@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PasswordResetToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub token_hash: String,
+    pub expires_at: chrono::NaiveDateTime,
+    pub used_at: Option<chrono::NaiveDateTime>,
+    pub created_date: chrono::NaiveDateTime,
+}
+
+/// A reset requested for an address, kept whether or not an account has that address.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PasswordResetRequest {
+    pub id: i32,
+    pub email: String,
+    pub created_date: chrono::NaiveDateTime,
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
+use crate::domain::version::Version;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: i32,
@@ -9,8 +11,31 @@ pub struct User {
     pub email: String,
     pub email_confirmed: bool,
     pub user_display_id: String,
-    pub balance: i32,
+    pub balance: i64,
+    pub is_active: bool,
+    pub role_id: i32,
+    pub group_id: i32,
+    /// Bumped by every edit; balance changes go through the ledger and leave it alone.
+    pub version: i32,
+}
+
+impl User {
+    pub fn version(&self) -> Version {
+        Version::of_counter(self.version)
+    }
+}
+
+/// Values for a user row that hasn't been inserted yet; `password` is already hashed.
+#[derive(Debug, Clone)]
+pub struct NewUser {
+    pub name: String,
+    pub password: String,
+    pub email: String,
+    pub email_confirmed: bool,
+    pub user_display_id: String,
+    pub balance: i64,
     pub is_active: bool,
     pub role_id: i32,
     pub group_id: i32,
 }
+
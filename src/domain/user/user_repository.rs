@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::{
+    application::error::Result,
+    domain::user::user_entity::{NewUser, User},
+};
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn find_by_id(&self, id: i32) -> Result<Option<User>>;
+
+    async fn find_active_by_email(&self, email: &str) -> Result<Option<User>>;
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>>;
+
+    async fn find_by_display_id(&self, user_display_id: &str) -> Result<Option<User>>;
+
+    /// Every member of a group, active or not, in name order.
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<User>>;
+
+    /// Members of a group whose name contains `name`, ignoring case, in name order.
+    async fn search_by_name(&self, group_id: i32, name: &str) -> Result<Vec<User>>;
+
+    /// Active members of a group, most indebted first.
+    async fn find_active_by_group(&self, group_id: i32) -> Result<Vec<User>>;
+
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<()>;
+
+    /// Fails with `Error::Conflict` if the email or display ID is already taken.
+    async fn create(&self, user: NewUser) -> Result<User>;
+
+    /// Stores every column of `user` except `balance`, which only moves through money flows, and
+    /// bumps `version`.
+    ///
+    /// Fails with `Error::PreconditionFailed` if the row is gone or no longer at `user.version`, and
+    /// `Error::Conflict` if the new email or display ID is already taken.
+    async fn update(&self, user: User) -> Result<User>;
+
+    async fn create_email_confirmation(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<()>;
+
+    /// Inserts the user together with a pending email confirmation token.
+    ///
+    /// Fails with `Error::Conflict` if the email or display ID is already taken.
+    async fn create_with_confirmation(
+        &self,
+        user: NewUser,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<User>;
+
+    /// Removes an account that never confirmed its email, e.g. when the confirmation mail could not be sent.
+    async fn delete_unconfirmed(&self, user_id: i32) -> Result<()>;
+
+    /// Consumes a valid confirmation token and marks its user's email as confirmed.
+    /// Returns the user id, or `None` if the token is unknown, used or expired.
+    async fn confirm_email(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<i32>>;
+
+    /// How many resets were requested for `email` since `since`, whether or not an account has it.
+    async fn count_password_reset_requests_since(&self, email: &str, since: NaiveDateTime) -> Result<i64>;
+
+    async fn create_password_reset_request(&self, email: &str) -> Result<()>;
+
+    async fn create_password_reset(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<()>;
+
+    /// Consumes a valid reset token, stores the new password hash, invalidates the user's other
+    /// reset tokens and revokes all of their sessions, atomically.
+    /// Returns the user id, or `None` if the token is unknown, used or expired.
+    async fn reset_password(&self, token_hash: &str, password_hash: &str, now: NaiveDateTime) -> Result<Option<i32>>;
+}
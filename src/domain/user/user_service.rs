@@ -1,28 +1,399 @@
-use actix_web::Result;
-use sqlx::{Pool, Postgres};
-use sqlx_clean_querybuilder::{qb::select::Order, query_builder::PostgreSqlQueryBuilder};
+use chrono::{Duration, Utc};
 
-use crate::interfaces::dtos::user_dto::UserDisplayDto;
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        active_session::active_session_repository::ActiveSessionRepository,
+        group::group_repository::GroupRepository,
+        role::role_repository::RoleRepository,
+        user::{
+            user_entity::{NewUser, User},
+            user_repository::UserRepository,
+        },
+        version::Version,
+    },
+    infrastructure::{
+        mailer::{MailMessage, Mailer},
+        password::{hash_password, verify_password},
+        token::{generate_token, hash_token},
+    },
+    interfaces::dtos::user_dto::{CreateUserDto, RegisterUserDto, UpdateUserDto, UserDisplayDto},
+};
+
+const DEFAULT_ROLE_NAME: &str = "User";
+const EMAIL_CONFIRMATION_TTL_HOURS: i64 = 24;
+const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+/// How many reset emails one address may request within [`PASSWORD_RESET_WINDOW_MINUTES`].
+const PASSWORD_RESET_MAX_REQUESTS: i64 = 3;
+const PASSWORD_RESET_WINDOW_MINUTES: i64 = 60;
+
+/// The caller of a user management operation.
+#[derive(Debug, Clone, Copy)]
+pub struct UserActor {
+    pub user_id: i32,
+    /// The session the request came in on.
+    pub session_id: i32,
+    pub group_id: i32,
+    /// Whether the caller's role grants `user:manage`.
+    pub can_manage: bool,
+}
 
 pub struct UserService;
 
 impl UserService {
-    pub async fn get_user_indebt(group_id: i32, db: Pool<Postgres>) -> Result<Vec<UserDisplayDto>> {
-        let condition = format!("group_id = {} AND is_active = true", group_id);
+    /// Every member of the group, including deactivated ones.
+    pub async fn get_users(group_id: i32, users: &dyn UserRepository) -> Result<Vec<User>> {
+        users.find_by_group(group_id).await
+    }
+
+    /// Looks the user up within `group_id`; users of other groups are reported as not found.
+    pub async fn get_user_by_id(group_id: i32, id: i32, users: &dyn UserRepository) -> Result<User> {
+        in_group(users.find_by_id(id).await?, group_id)
+    }
+
+    pub async fn get_user_by_email(group_id: i32, email: &str, users: &dyn UserRepository) -> Result<User> {
+        in_group(users.find_by_email(email).await?, group_id)
+    }
+
+    pub async fn get_user_by_display_id(group_id: i32, display_id: &str, users: &dyn UserRepository) -> Result<User> {
+        in_group(users.find_by_display_id(display_id).await?, group_id)
+    }
+
+    pub async fn get_users_by_name(group_id: i32, name: &str, users: &dyn UserRepository) -> Result<Vec<User>> {
+        users.search_by_name(group_id, name).await
+    }
 
+    /// Creates a user in the manager's group with a zero balance.
+    pub async fn create_user(
+        group_id: i32,
+        dto: CreateUserDto,
+        users: &dyn UserRepository,
+        roles: &dyn RoleRepository,
+    ) -> Result<User> {
+        if roles.find_by_id(dto.role_id).await?.is_none() {
+            return Err(Error::validation("Role does not exist"));
+        }
 
-        let qq = PostgreSqlQueryBuilder::select()
-            .columns(&["id", "name", "balance"])
-            .filter(&condition)
-            .table("user", None)
-            .order_by("balance", Order::Asc)
-            .build();
+        let hashed = hash_in_background(&dto.password).await?;
 
-        let users: Vec<UserDisplayDto> = sqlx::query_as::<_, UserDisplayDto>(&qq)
-            .fetch_all(&db)
+        users
+            .create(NewUser {
+                name: dto.name,
+                password: hashed,
+                email: dto.email,
+                email_confirmed: dto.email_confirmed,
+                user_display_id: dto.user_display_id,
+                balance: 0,
+                is_active: dto.is_active,
+                role_id: dto.role_id,
+                group_id,
+            })
             .await
-            .unwrap();
+    }
+
+    /// Applies the fields present in `dto` to user `id`.
+    ///
+    /// Members may edit their own name, email, display ID and password; everything else, and
+    /// editing other members, needs `user:manage`. A new email address has to be confirmed
+    /// again unless a manager marks it confirmed in the same request. A new password revokes the
+    /// user's sessions, except the one it was changed from when the user changed their own.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_user(
+        actor: UserActor,
+        id: i32,
+        expected: Version,
+        dto: UpdateUserDto,
+        mailer: &dyn Mailer,
+        users: &dyn UserRepository,
+        roles: &dyn RoleRepository,
+        sessions: &dyn ActiveSessionRepository,
+    ) -> Result<User> {
+        let is_self = actor.user_id == id;
+        if !is_self && !actor.can_manage {
+            return Err(Error::forbidden("Missing permission user:manage"));
+        }
+        if !actor.can_manage && (dto.role_id.is_some() || dto.is_active.is_some() || dto.email_confirmed.is_some()) {
+            return Err(Error::forbidden("Missing permission user:manage"));
+        }
+
+        if is_self && dto.is_active == Some(false) {
+            return Err(Error::validation("You can't deactivate your own account"));
+        }
+
+        let mut user = Self::get_user_by_id(actor.group_id, id, users).await?;
+        expected.check(user.version(), "User")?;
+        let deactivated = user.is_active && dto.is_active == Some(false);
+        let password_changed = dto.password.is_some();
+
+        if let Some(password) = &dto.password {
+            if is_self {
+                let current = dto
+                    .current_password
+                    .clone()
+                    .ok_or_else(|| Error::validation("current_password is required to change your password"))?;
+                let stored = user.password.clone();
+                let check = tokio::task::spawn_blocking(move || verify_password(&current, &stored)).await?;
+
+                if !check.valid {
+                    return Err(Error::validation("current_password is incorrect"));
+                }
+            }
+
+            user.password = hash_in_background(password).await?;
+        }
+
+        if let Some(role_id) = dto.role_id {
+            if roles.find_by_id(role_id).await?.is_none() {
+                return Err(Error::validation("Role does not exist"));
+            }
+            user.role_id = role_id;
+        }
+
+        let email_changed = dto.email.as_ref().is_some_and(|email| *email != user.email);
+        if let Some(email) = dto.email {
+            user.email = email;
+        }
+        if email_changed {
+            user.email_confirmed = false;
+        }
+        if let Some(name) = dto.name {
+            user.name = name;
+        }
+        if let Some(display_id) = dto.user_display_id {
+            user.user_display_id = display_id;
+        }
+        if let Some(is_active) = dto.is_active {
+            user.is_active = is_active;
+        }
+        if let Some(confirmed) = dto.email_confirmed {
+            user.email_confirmed = confirmed;
+        }
+
+        let user = users.update(user).await?;
+
+        // a new password signs the account out everywhere but where the owner changed it themselves
+        if deactivated || (password_changed && !is_self) {
+            sessions.revoke_all(user.id, Utc::now().naive_utc()).await?;
+        } else if password_changed {
+            sessions.revoke_others(user.id, actor.session_id, Utc::now().naive_utc()).await?;
+        }
+
+        if !user.email_confirmed && email_changed {
+            let (token, token_hash) = generate_token();
+            let expires_at = Utc::now().naive_utc() + Duration::hours(EMAIL_CONFIRMATION_TTL_HOURS);
+
+            users.create_email_confirmation(user.id, &token_hash, expires_at).await?;
+            mailer.send(confirmation_mail(&user, &token)).await?;
+        }
+
+        Ok(user)
+    }
+
+    /// Deactivates a member of the manager's group and signs them out everywhere.
+    /// The row is kept so their invoices and balance history stay intact.
+    pub async fn deactivate_user(
+        actor: UserActor,
+        id: i32,
+        expected: Version,
+        users: &dyn UserRepository,
+        sessions: &dyn ActiveSessionRepository,
+    ) -> Result<User> {
+        if actor.user_id == id {
+            return Err(Error::validation("You can't deactivate your own account"));
+        }
+
+        let mut user = Self::get_user_by_id(actor.group_id, id, users).await?;
+        expected.check(user.version(), "User")?;
+        user.is_active = false;
+        let user = users.update(user).await?;
+        sessions.revoke_all(id, Utc::now().naive_utc()).await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_user_indebt(group_id: i32, users: &dyn UserRepository) -> Result<Vec<UserDisplayDto>> {
+        let users = users.find_active_by_group(group_id).await?;
+
+        Ok(users
+            .into_iter()
+            .map(|user| UserDisplayDto {
+                id: user.id,
+                name: user.name,
+                balance: user.balance,
+            })
+            .collect())
+    }
+
+    /// Returns the active user matching `email` and `password`.
+    ///
+    /// Plaintext or outdated password hashes are upgraded as part of a successful login.
+    pub async fn authenticate(email: &str, password: &str, users: &dyn UserRepository) -> Result<User> {
+        let invalid = || Error::unauthorized("Invalid email or password");
+
+        let user = users.find_active_by_email(email).await?.ok_or_else(invalid)?;
 
-        Ok(users)
+        let (candidate, stored) = (password.to_string(), user.password.clone());
+        let check = tokio::task::spawn_blocking(move || verify_password(&candidate, &stored))
+            .await?;
+
+        if !check.valid {
+            return Err(invalid());
+        }
+
+        if check.needs_rehash {
+            Self::update_password(user.id, password, users).await?;
+        }
+
+        Ok(user)
+    }
+
+    /// Hashes `password` and stores it for the user. Plaintext is never written to the table.
+    pub async fn update_password(user_id: i32, password: &str, users: &dyn UserRepository) -> Result<()> {
+        let hashed = hash_in_background(password).await?;
+        users.update_password(user_id, &hashed).await
+    }
+
+    /// Creates an unconfirmed account in a public group and mails a single-use confirmation token.
+    ///
+    /// If the mail can't be sent the account is removed again so the address can register later.
+    pub async fn register(
+        dto: RegisterUserDto,
+        mailer: &dyn Mailer,
+        users: &dyn UserRepository,
+        groups: &dyn GroupRepository,
+        roles: &dyn RoleRepository,
+    ) -> Result<User> {
+        match groups.find_by_id(dto.group_id).await? {
+            None => return Err(Error::validation("Group does not exist")),
+            Some(group) if !group.is_public => return Err(Error::forbidden("Group is not open for registration")),
+            Some(_) => {}
+        }
+
+        let role = roles
+            .find_by_name(DEFAULT_ROLE_NAME)
+            .await?
+            .ok_or_else(|| Error::internal(format!("Role {} is missing", DEFAULT_ROLE_NAME)))?;
+
+        let hashed = hash_in_background(&dto.password).await?;
+        let (token, token_hash) = generate_token();
+        let now = Utc::now().naive_utc();
+
+        let user = users
+            .create_with_confirmation(
+                NewUser {
+                    name: dto.name,
+                    password: hashed,
+                    email: dto.email,
+                    email_confirmed: false,
+                    user_display_id: dto.user_display_id,
+                    balance: 0,
+                    is_active: true,
+                    role_id: role.id,
+                    group_id: dto.group_id,
+                },
+                &token_hash,
+                now + Duration::hours(EMAIL_CONFIRMATION_TTL_HOURS),
+            )
+            .await?;
+
+        let sent = mailer.send(confirmation_mail(&user, &token)).await;
+
+        if let Err(e) = sent {
+            users.delete_unconfirmed(user.id).await?;
+            return Err(e.into());
+        }
+
+        Ok(user)
+    }
+
+    /// Consumes a confirmation token and marks the owning user's email as confirmed.
+    pub async fn confirm_email(token: &str, users: &dyn UserRepository) -> Result<()> {
+        users
+            .confirm_email(&hash_token(token), Utc::now().naive_utc())
+            .await?
+            .ok_or_else(|| Error::validation("Invalid or expired confirmation token"))?;
+
+        Ok(())
+    }
+
+    pub async fn is_email_confirmed(user_id: i32, users: &dyn UserRepository) -> Result<bool> {
+        Ok(users.find_by_id(user_id).await?.is_some_and(|user| user.email_confirmed))
     }
+
+    /// Mails a password reset token if `email` belongs to an active user.
+    ///
+    /// Unknown addresses succeed silently and count against the same per-address limit, so the
+    /// endpoint can't be used to probe for accounts.
+    pub async fn request_password_reset(email: &str, mailer: &dyn Mailer, users: &dyn UserRepository) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        let recent = users
+            .count_password_reset_requests_since(email, now - Duration::minutes(PASSWORD_RESET_WINDOW_MINUTES))
+            .await?;
+
+        if recent >= PASSWORD_RESET_MAX_REQUESTS {
+            return Err(Error::TooManyRequests(
+                "Too many password reset requests, try again later".to_string(),
+            ));
+        }
+        users.create_password_reset_request(email).await?;
+
+        let Some(user) = users.find_active_by_email(email).await? else {
+            return Ok(());
+        };
+
+        let (token, token_hash) = generate_token();
+        users
+            .create_password_reset(user.id, &token_hash, now + Duration::minutes(PASSWORD_RESET_TTL_MINUTES))
+            .await?;
+
+        mailer
+            .send(MailMessage {
+                to: user.email.clone(),
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "Hi {},\n\nUse this code to reset your password: {}\nIt expires in {} minutes. \
+                     If you didn't ask for a reset you can ignore this email.",
+                    user.name, token, PASSWORD_RESET_TTL_MINUTES
+                ),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sets a new password using a reset token, then revokes every outstanding reset token
+    /// and active session of the user.
+    pub async fn reset_password(token: &str, new_password: &str, users: &dyn UserRepository) -> Result<()> {
+        let hashed = hash_in_background(new_password).await?;
+
+        users
+            .reset_password(&hash_token(token), &hashed, Utc::now().naive_utc())
+            .await?
+            .ok_or_else(|| Error::validation("Invalid or expired reset token"))?;
+
+        Ok(())
+    }
+}
+
+fn in_group(user: Option<User>, group_id: i32) -> Result<User> {
+    user.filter(|user| user.group_id == group_id)
+        .ok_or_else(|| Error::not_found("User not found"))
+}
+
+fn confirmation_mail(user: &User, token: &str) -> MailMessage {
+    MailMessage {
+        to: user.email.clone(),
+        subject: "Confirm your email".to_string(),
+        body: format!(
+            "Hi {},\n\nUse this code to confirm your email address: {}\nIt expires in {} hours.",
+            user.name, token, EMAIL_CONFIRMATION_TTL_HOURS
+        ),
+    }
+}
+
+async fn hash_in_background(password: &str) -> Result<String> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hash_password(&password))
+        .await?
+        .map_err(Error::internal)
 }
@@ -0,0 +1,49 @@
+use std::{fmt, str::FromStr};
+
+use chrono::NaiveDateTime;
+
+use crate::application::error::{Error, Result};
+
+/// Which state of a row a client last saw, so an update based on an older one can be refused.
+///
+/// Rows with a `last_modification_date` are versioned by it, to the microsecond since that is
+/// what Postgres keeps; rows without one carry a `version` counter bumped on every edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version(i64);
+
+impl Version {
+    pub fn of_timestamp(modified: NaiveDateTime) -> Self {
+        Version(modified.and_utc().timestamp_micros())
+    }
+
+    pub fn of_counter(version: i32) -> Self {
+        Version(version.into())
+    }
+
+    /// Fails with a precondition error unless the client saw `current`.
+    pub fn check(self, current: Version, what: &str) -> Result<()> {
+        if self != current {
+            return Err(stale(what));
+        }
+        Ok(())
+    }
+}
+
+/// The error for an update that lost the race against another one.
+pub fn stale(what: &str) -> Error {
+    Error::precondition_failed(format!("{} was changed by someone else; reload it and try again", what))
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Version {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(Version)
+    }
+}
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::{
+    application::error::Result,
+    domain::active_session::{
+        active_session_entity::{ActiveSession, NewActiveSession},
+        active_session_repository::ActiveSessionRepository,
+    },
+    infrastructure::in_memory::{next_id, InMemoryDatabase},
+};
+
+fn is_live(session: &ActiveSession, now: NaiveDateTime) -> bool {
+    session.revoked_at.is_none() && session.expires_at > now
+}
+
+#[async_trait]
+impl ActiveSessionRepository for InMemoryDatabase {
+    async fn create(&self, session: NewActiveSession) -> Result<ActiveSession> {
+        let mut state = self.state();
+        let session = ActiveSession {
+            id: next_id(&state.active_sessions, |session| session.id),
+            user_id: session.user_id,
+            group_id: session.group_id,
+            refresh_token_hash: session.refresh_token_hash,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_date: session.created_date,
+            last_seen_date: session.created_date,
+            expires_at: session.expires_at,
+            revoked_at: None,
+        };
+        state.active_sessions.push(session.clone());
+
+        Ok(session)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        now: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<Option<ActiveSession>> {
+        let mut state = self.state();
+        let Some(session) = state
+            .active_sessions
+            .iter_mut()
+            .find(|session| session.refresh_token_hash == refresh_token_hash && is_live(session, now))
+        else {
+            return Ok(None);
+        };
+
+        session.refresh_token_hash = new_refresh_token_hash.to_string();
+        session.last_seen_date = now;
+        session.expires_at = expires_at;
+        session.user_agent = user_agent.or(session.user_agent.take());
+        session.ip_address = ip_address.or(session.ip_address.take());
+
+        Ok(Some(session.clone()))
+    }
+
+    async fn touch(
+        &self,
+        session_id: i32,
+        user_id: i32,
+        now: NaiveDateTime,
+        stale_before: NaiveDateTime,
+    ) -> Result<bool> {
+        let mut state = self.state();
+        let Some(session) = state
+            .active_sessions
+            .iter_mut()
+            .find(|session| session.id == session_id && session.user_id == user_id && is_live(session, now))
+        else {
+            return Ok(false);
+        };
+        if session.last_seen_date < stale_before {
+            session.last_seen_date = now;
+        }
+
+        Ok(true)
+    }
+
+    async fn find_live_by_user(&self, user_id: i32, now: NaiveDateTime) -> Result<Vec<ActiveSession>> {
+        let mut sessions: Vec<ActiveSession> = self
+            .state()
+            .active_sessions
+            .iter()
+            .filter(|session| session.user_id == user_id && is_live(session, now))
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_seen_date));
+
+        Ok(sessions)
+    }
+
+    async fn revoke(&self, user_id: i32, session_id: i32, now: NaiveDateTime) -> Result<bool> {
+        let mut state = self.state();
+        let session = state.active_sessions.iter_mut().find(|session| {
+            session.id == session_id && session.user_id == user_id && session.revoked_at.is_none()
+        });
+
+        Ok(session.map(|session| session.revoked_at = Some(now)).is_some())
+    }
+
+    async fn revoke_all(&self, user_id: i32, now: NaiveDateTime) -> Result<u64> {
+        let mut state = self.state();
+        let mut revoked = 0;
+
+        for session in state
+            .active_sessions
+            .iter_mut()
+            .filter(|session| session.user_id == user_id && session.revoked_at.is_none())
+        {
+            session.revoked_at = Some(now);
+            revoked += 1;
+        }
+
+        Ok(revoked)
+    }
+
+    async fn revoke_others(&self, user_id: i32, keep_session_id: i32, now: NaiveDateTime) -> Result<u64> {
+        let mut state = self.state();
+        let mut revoked = 0;
+
+        for session in state.active_sessions.iter_mut().filter(|session| {
+            session.user_id == user_id && session.id != keep_session_id && session.revoked_at.is_none()
+        }) {
+            session.revoked_at = Some(now);
+            revoked += 1;
+        }
+
+        Ok(revoked)
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::customer::{customer_entity::Customer, customer_repository::CustomerRepository},
+    infrastructure::in_memory::InMemoryDatabase,
+};
+
+#[async_trait]
+impl CustomerRepository for InMemoryDatabase {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Customer>> {
+        Ok(self.state().customers.iter().find(|customer| customer.id == id).cloned())
+    }
+
+    async fn find_by_user(&self, user_id: i32) -> Result<Vec<Customer>> {
+        let mut customers: Vec<Customer> = self
+            .state()
+            .customers
+            .iter()
+            .filter(|customer| customer.user_id == user_id)
+            .cloned()
+            .collect();
+        customers.sort_by_key(|customer| customer.id);
+
+        Ok(customers)
+    }
+}
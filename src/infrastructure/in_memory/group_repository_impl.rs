@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::group::{group_entity::Group, group_repository::GroupRepository},
+    infrastructure::in_memory::InMemoryDatabase,
+};
+
+#[async_trait]
+impl GroupRepository for InMemoryDatabase {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Group>> {
+        Ok(self.state().groups.iter().find(|group| group.id == id).cloned())
+    }
+
+    async fn is_member(&self, user_id: i32, group_id: i32) -> Result<bool> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .any(|user| user.id == user_id && user.group_id == group_id && user.is_active))
+    }
+}
@@ -0,0 +1,419 @@
+use async_trait::async_trait;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        invoice::{
+            invoice_entity::{Invoice, InvoiceChanges, NewInvoice},
+            invoice_repository::InvoiceRepository,
+        },
+        invoice_details::invoice_details_entity::InvoiceDetails,
+        invoice_participant::invoice_participant_entity::{InvoiceParticipant, NewInvoiceParticipant},
+        ledger::ledger_entity::{LedgerEntry, LedgerKind, NewLedgerEntry, NewLedgerTransaction},
+        stock::stock_entity::StockUse,
+    },
+    infrastructure::in_memory::{ledger_repository_impl::post_transaction, next_id, InMemoryDatabase, InMemoryState},
+    interfaces::dtos::invoice_dto::InvoiceRow,
+};
+
+#[async_trait]
+impl InvoiceRepository for InMemoryDatabase {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Invoice>> {
+        Ok(self
+            .state()
+            .invoices
+            .iter()
+            .find(|invoice| invoice.id == id && invoice.group_id == group_id)
+            .cloned())
+    }
+
+    async fn find_by_meal(&self, group_id: i32, meal_id: i32) -> Result<Vec<Invoice>> {
+        let mut invoices: Vec<Invoice> = self
+            .state()
+            .invoices
+            .iter()
+            .filter(|invoice| invoice.group_id == group_id && invoice.meal_id == meal_id && !invoice.is_deleted)
+            .cloned()
+            .collect();
+        invoices.sort_by(|a, b| b.created_date.cmp(&a.created_date).then(b.id.cmp(&a.id)));
+
+        Ok(invoices)
+    }
+
+    async fn find_deleted(&self, group_id: i32) -> Result<Vec<Invoice>> {
+        let mut invoices: Vec<Invoice> = self
+            .state()
+            .invoices
+            .iter()
+            .filter(|invoice| invoice.group_id == group_id && invoice.is_deleted)
+            .cloned()
+            .collect();
+        invoices.sort_by(|a, b| {
+            b.last_modification_date.cmp(&a.last_modification_date).then(b.id.cmp(&a.id))
+        });
+
+        Ok(invoices)
+    }
+
+    async fn find_report_rows(
+        &self,
+        group_id: i32,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<InvoiceRow>> {
+        let state = self.state();
+        let mut rows = Vec::new();
+
+        for invoice in state.invoices.iter().filter(|invoice| {
+            invoice.group_id == group_id
+                && !invoice.is_deleted
+                && (start_date..=end_date).contains(&invoice.created_date.date())
+        }) {
+            // the SQL version inner-joins details, meal products and the supplier's user;
+            // an invoice missing any of them drops out of the report the same way
+            if !state.invoice_details.iter().any(|details| details.invoice_id == invoice.id) {
+                continue;
+            }
+
+            let mut products: Vec<&str> = state
+                .meal_products
+                .iter()
+                .filter(|link| link.meal_id == invoice.meal_id)
+                .filter_map(|link| state.products.iter().find(|product| product.id == link.product_id))
+                .map(|product| product.name.as_str())
+                .collect();
+            products.sort();
+            products.dedup();
+
+            let supplier_user = state
+                .suppliers
+                .iter()
+                .find(|supplier| supplier.id == invoice.supplier_id)
+                .and_then(|supplier| state.users.iter().find(|user| user.id == supplier.user_id));
+
+            let (Some(user), false) = (supplier_user, products.is_empty()) else {
+                continue;
+            };
+
+            rows.push(InvoiceRow {
+                id: invoice.id,
+                price: invoice.price,
+                created_date: invoice.created_date,
+                supplier_id: user.id,
+                supplier_name: user.name.clone(),
+                meal: products.join(", "),
+            });
+        }
+
+        Ok(rows)
+    }
+
+    async fn find_participants(&self, invoice_id: i32) -> Result<Vec<InvoiceParticipant>> {
+        let mut participants: Vec<InvoiceParticipant> = self
+            .state()
+            .invoice_participants
+            .iter()
+            .filter(|participant| participant.invoice_id == invoice_id)
+            .cloned()
+            .collect();
+        participants.sort_by_key(|participant| participant.user_id);
+
+        Ok(participants)
+    }
+
+    async fn find_billed_customers(&self, invoice_id: i32) -> Result<Vec<(i32, i32)>> {
+        let state = self.state();
+        let mut billed: Vec<(i32, i32)> = postings_of(&state, invoice_id)
+            .filter_map(|entry| Some((entry.user_id?, entry.customer_id?)))
+            .collect();
+        billed.sort();
+        billed.dedup();
+
+        Ok(billed)
+    }
+
+    async fn find_details(&self, invoice_id: i32) -> Result<Vec<InvoiceDetails>> {
+        Ok(self
+            .state()
+            .invoice_details
+            .iter()
+            .filter(|details| details.invoice_id == invoice_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn create(
+        &self,
+        invoice: NewInvoice,
+        stock: &[StockUse],
+        participants: &[NewInvoiceParticipant],
+        entries: &[NewLedgerEntry],
+    ) -> Result<Invoice> {
+        insert_invoice(&mut self.state(), &invoice, stock, participants, entries)
+    }
+
+    async fn soft_delete(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        deleted_by: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<Invoice>> {
+        let mut state = self.state();
+
+        let Some(index) = find_unchanged(&state, id, last_modified, false) else {
+            return Ok(None);
+        };
+        let mut deleted = state.invoices[index].clone();
+        deleted.is_deleted = true;
+        deleted.deleted_by = deleted_by;
+        deleted.last_modification_date = now;
+
+        reverse_invoice(&mut state, &deleted, format!("Invoice {} deleted", deleted.id))?;
+        return_stock(&mut state, deleted.id);
+        state.invoices[index] = deleted.clone();
+
+        Ok(Some(deleted))
+    }
+
+    async fn restore(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        now: NaiveDateTime,
+        entries: &[NewLedgerEntry],
+    ) -> Result<Option<Invoice>> {
+        let mut state = self.state();
+
+        let Some(index) = find_unchanged(&state, id, last_modified, true) else {
+            return Ok(None);
+        };
+        let mut restored = state.invoices[index].clone();
+        restored.is_deleted = false;
+        restored.deleted_by = 0;
+        restored.last_modification_date = now;
+
+        // the stock and the posting both have to go through, so they go to a copy first
+        let mut draft = state.clone();
+        redraw_stock(&mut draft, restored.id)?;
+        let description = format!("Invoice {} restored", restored.id);
+        post_invoice(&mut draft, &restored, LedgerKind::Invoice, description, entries)?;
+        draft.invoices[index] = restored.clone();
+        *state = draft;
+
+        Ok(Some(restored))
+    }
+
+    async fn update(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        changes: InvoiceChanges,
+        participants: &[NewInvoiceParticipant],
+        entries: &[NewLedgerEntry],
+    ) -> Result<Option<Invoice>> {
+        let mut state = self.state();
+
+        let Some(index) = find_unchanged(&state, id, last_modified, false) else {
+            return Ok(None);
+        };
+        let mut updated = state.invoices[index].clone();
+        updated.price = changes.price;
+        updated.supplier_id = changes.supplier_id;
+        updated.last_modification_date = changes.last_modification_date;
+
+        // two postings, so they go to a copy that only replaces the state once both succeeded
+        let mut draft = state.clone();
+        reverse_invoice(&mut draft, &updated, format!("Invoice {} edited", updated.id))?;
+        post_invoice(&mut draft, &updated, LedgerKind::Invoice, format!("Invoice {}", updated.id), entries)?;
+
+        draft.invoices[index] = updated.clone();
+        draft.invoice_participants.retain(|participant| participant.invoice_id != updated.id);
+        draft.invoice_participants.extend(participants.iter().map(|participant| InvoiceParticipant {
+            invoice_id: updated.id,
+            user_id: participant.user_id,
+            amount: participant.amount,
+        }));
+        *state = draft;
+
+        Ok(Some(updated))
+    }
+}
+
+/// Inserts the invoice with its details and participants, draws the stock and posts `entries`.
+/// Fails with a conflict, changing nothing, if any stock row has less left than is used.
+pub(crate) fn insert_invoice(
+    state: &mut InMemoryState,
+    invoice: &NewInvoice,
+    stock: &[StockUse],
+    participants: &[NewInvoiceParticipant],
+    entries: &[NewLedgerEntry],
+) -> Result<Invoice> {
+    let available = stock
+        .iter()
+        .all(|used| state.stocks.iter().any(|row| row.id == used.stock_id && row.remaining >= used.quantity));
+    if !available {
+        return Err(Error::conflict("Stock has already been consumed"));
+    }
+    let id = next_id(&state.invoices, |invoice| invoice.id);
+    // posted first: it checks everything before touching a table, so a failure leaves them all as they were
+    post_transaction(
+        state,
+        &NewLedgerTransaction {
+            kind: LedgerKind::Invoice,
+            description: format!("Invoice {}", id),
+            group_id: invoice.group_id,
+            invoice_id: Some(id),
+            payment_id: None,
+            created_date: invoice.created_date,
+            entries: entries.to_vec(),
+        },
+    )?;
+
+    for used in stock {
+        if let Some(row) = state.stocks.iter_mut().find(|row| row.id == used.stock_id) {
+            row.remaining -= used.quantity;
+            row.consumed = row.remaining.is_zero();
+            row.version += 1;
+        }
+    }
+
+    let created = Invoice {
+        id,
+        price: invoice.price,
+        is_deleted: false,
+        deleted_by: 0,
+        created_date: invoice.created_date,
+        last_modification_date: invoice.created_date,
+        meal_id: invoice.meal_id,
+        group_id: invoice.group_id,
+        supplier_id: invoice.supplier_id,
+        order_id: invoice.order_id,
+    };
+    state.invoices.push(created.clone());
+
+    for used in stock {
+        let id = next_id(&state.invoice_details, |details| details.id);
+        state.invoice_details.push(InvoiceDetails {
+            id,
+            invoice_id: created.id,
+            stock_id: used.stock_id,
+            quantity: used.quantity,
+            cost: used.cost,
+        });
+    }
+
+    state.invoice_participants.extend(participants.iter().map(|participant| InvoiceParticipant {
+        invoice_id: created.id,
+        user_id: participant.user_id,
+        amount: participant.amount,
+    }));
+
+    Ok(created)
+}
+
+/// What the invoice drew from each lot, as its details record it.
+fn drawn_by(state: &InMemoryState, invoice_id: i32) -> Vec<(i32, Decimal)> {
+    state
+        .invoice_details
+        .iter()
+        .filter(|details| details.invoice_id == invoice_id)
+        .map(|details| (details.stock_id, details.quantity))
+        .collect()
+}
+
+/// Puts what the invoice drew back on its lots, never more than was bought of one.
+fn return_stock(state: &mut InMemoryState, invoice_id: i32) {
+    for (stock_id, quantity) in drawn_by(state, invoice_id) {
+        if let Some(row) = state.stocks.iter_mut().find(|row| row.id == stock_id) {
+            row.remaining = (row.remaining + quantity).min(row.quantity);
+            row.consumed = row.remaining.is_zero();
+            row.version += 1;
+        }
+    }
+}
+
+/// Draws what the invoice drew from its lots again. Fails with a conflict, changing nothing, if any of
+/// them has less left by now.
+fn redraw_stock(state: &mut InMemoryState, invoice_id: i32) -> Result<()> {
+    let drawn = drawn_by(state, invoice_id);
+    let available = drawn
+        .iter()
+        .all(|(stock_id, quantity)| state.stocks.iter().any(|row| row.id == *stock_id && row.remaining >= *quantity));
+    if !available {
+        return Err(Error::conflict(format!("Stock of invoice {} has been used since it was deleted", invoice_id)));
+    }
+    for (stock_id, quantity) in drawn {
+        if let Some(row) = state.stocks.iter_mut().find(|row| row.id == stock_id) {
+            row.remaining -= quantity;
+            row.consumed = row.remaining.is_zero();
+            row.version += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Index of the invoice if it is still as the caller read it.
+fn find_unchanged(state: &InMemoryState, id: i32, last_modified: NaiveDateTime, is_deleted: bool) -> Option<usize> {
+    state.invoices.iter().position(|invoice| {
+        invoice.id == id && invoice.last_modification_date == last_modified && invoice.is_deleted == is_deleted
+    })
+}
+
+/// Posts `entries` against the invoice, dated at its last modification; nothing if there are none.
+fn post_invoice(
+    state: &mut InMemoryState,
+    invoice: &Invoice,
+    kind: LedgerKind,
+    description: String,
+    entries: &[NewLedgerEntry],
+) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let posting = NewLedgerTransaction {
+        kind,
+        description,
+        group_id: invoice.group_id,
+        invoice_id: Some(invoice.id),
+        payment_id: None,
+        created_date: invoice.last_modification_date,
+        entries: entries.to_vec(),
+    };
+    post_transaction(state, &posting)?;
+
+    Ok(())
+}
+
+/// Cancels the invoice's net effect so far: whatever its postings add up to per account, negated.
+fn reverse_invoice(state: &mut InMemoryState, invoice: &Invoice, description: String) -> Result<()> {
+    let mut entries: Vec<NewLedgerEntry> = Vec::new();
+    for entry in postings_of(state, invoice.id) {
+        let account = (entry.user_id, entry.supplier_id, entry.customer_id);
+        match entries.iter_mut().find(|net| (net.user_id, net.supplier_id, net.customer_id) == account) {
+            Some(net) => net.amount -= entry.amount,
+            None => entries.push(NewLedgerEntry {
+                user_id: entry.user_id,
+                amount: -entry.amount,
+                supplier_id: entry.supplier_id,
+                customer_id: entry.customer_id,
+            }),
+        }
+    }
+    entries.retain(|entry| entry.amount != 0);
+    post_invoice(state, invoice, LedgerKind::Reversal, description, &entries)
+}
+
+/// Every posting made against the invoice.
+fn postings_of(state: &InMemoryState, invoice_id: i32) -> impl Iterator<Item = &LedgerEntry> {
+    state.ledger_entries.iter().filter(move |entry| {
+        state
+            .ledger_transactions
+            .iter()
+            .any(|transaction| transaction.id == entry.transaction_id && transaction.invoice_id == Some(invoice_id))
+    })
+}
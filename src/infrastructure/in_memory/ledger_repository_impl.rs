@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::ledger::{
+        ledger_entity::{
+            BalanceDrift, LedgerAccount, LedgerEntry, LedgerTransaction, NewLedgerTransaction, UserLedgerEntry,
+        },
+        ledger_repository::LedgerRepository,
+    },
+    infrastructure::in_memory::{next_id, InMemoryDatabase, InMemoryState},
+};
+
+#[async_trait]
+impl LedgerRepository for InMemoryDatabase {
+    async fn create(&self, transaction: NewLedgerTransaction) -> Result<LedgerTransaction> {
+        post_transaction(&mut self.state(), &transaction)
+    }
+
+    async fn find_user_entries(&self, group_id: i32, user_id: i32) -> Result<Vec<UserLedgerEntry>> {
+        let state = self.state();
+        let mut entries: Vec<(i32, UserLedgerEntry)> = state
+            .ledger_entries
+            .iter()
+            .filter(|entry| entry.user_id == Some(user_id))
+            .filter_map(|entry| {
+                let transaction = state
+                    .ledger_transactions
+                    .iter()
+                    .find(|t| t.id == entry.transaction_id && t.group_id == group_id)?;
+                Some((
+                    entry.id,
+                    UserLedgerEntry {
+                        transaction_id: transaction.id,
+                        kind: transaction.kind,
+                        description: transaction.description.clone(),
+                        invoice_id: transaction.invoice_id,
+                        payment_id: transaction.payment_id,
+                        created_date: transaction.created_date,
+                        amount: entry.amount,
+                    },
+                ))
+            })
+            .collect();
+        entries.sort_by_key(|(id, entry)| (entry.created_date, entry.transaction_id, *id));
+
+        Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+    }
+
+    async fn find_drift(&self, group_id: i32) -> Result<Vec<BalanceDrift>> {
+        Ok(find_drift(&self.state(), group_id))
+    }
+
+    async fn rebuild_balances(&self, group_id: i32) -> Result<Vec<BalanceDrift>> {
+        let mut state = self.state();
+        let drifted = find_drift(&state, group_id);
+
+        for drift in &drifted {
+            set_balance(&mut state, drift.account, drift.id, drift.ledger_balance);
+        }
+
+        Ok(drifted)
+    }
+}
+
+/// Every account of the group whose cached balance differs from its postings in that group, users
+/// first, then suppliers, then customers.
+fn find_drift(state: &InMemoryState, group_id: i32) -> Vec<BalanceDrift> {
+    let posted: Vec<&LedgerEntry> = state
+        .ledger_entries
+        .iter()
+        .filter(|entry| {
+            state
+                .ledger_transactions
+                .iter()
+                .any(|transaction| transaction.id == entry.transaction_id && transaction.group_id == group_id)
+        })
+        .collect();
+    let total = |of: &dyn Fn(&LedgerEntry) -> bool| -> i64 {
+        posted.iter().filter(|entry| of(entry)).map(|entry| entry.amount).sum()
+    };
+    let in_group = |user_id: i32| {
+        state
+            .users
+            .iter()
+            .any(|user| user.id == user_id && user.group_id == group_id)
+    };
+
+    let mut accounts: Vec<(LedgerAccount, i32, i64, i64)> = Vec::new();
+    for user in state.users.iter().filter(|user| user.group_id == group_id) {
+        let posted = total(&|entry| entry.user_id == Some(user.id));
+        accounts.push((LedgerAccount::User, user.id, user.balance, posted));
+    }
+    for supplier in state.suppliers.iter().filter(|supplier| in_group(supplier.user_id)) {
+        let posted = total(&|entry| entry.supplier_id == Some(supplier.id));
+        accounts.push((LedgerAccount::Supplier, supplier.id, supplier.balance, posted));
+    }
+    for customer in state.customers.iter().filter(|customer| in_group(customer.user_id)) {
+        let posted = total(&|entry| entry.customer_id == Some(customer.id));
+        accounts.push((LedgerAccount::Customer, customer.id, customer.balance, posted));
+    }
+    accounts.sort_by_key(|(account, id, _, _)| (*account, *id));
+
+    accounts
+        .into_iter()
+        .filter(|(_, _, balance, ledger_balance)| balance != ledger_balance)
+        .map(|(account, id, balance, ledger_balance)| BalanceDrift {
+            account,
+            id,
+            balance,
+            ledger_balance,
+        })
+        .collect()
+}
+
+fn balance_of(state: &InMemoryState, account: LedgerAccount, id: i32) -> Option<i64> {
+    match account {
+        LedgerAccount::User => state.users.iter().find(|user| user.id == id).map(|user| user.balance),
+        LedgerAccount::Supplier => state
+            .suppliers
+            .iter()
+            .find(|supplier| supplier.id == id)
+            .map(|supplier| supplier.balance),
+        LedgerAccount::Customer => state
+            .customers
+            .iter()
+            .find(|customer| customer.id == id)
+            .map(|customer| customer.balance),
+    }
+}
+
+fn set_balance(state: &mut InMemoryState, account: LedgerAccount, id: i32, balance: i64) {
+    let cached = match account {
+        LedgerAccount::User => state
+            .users
+            .iter_mut()
+            .find(|user| user.id == id)
+            .map(|user| &mut user.balance),
+        LedgerAccount::Supplier => state
+            .suppliers
+            .iter_mut()
+            .find(|supplier| supplier.id == id)
+            .map(|supplier| &mut supplier.balance),
+        LedgerAccount::Customer => state
+            .customers
+            .iter_mut()
+            .find(|customer| customer.id == id)
+            .map(|customer| &mut customer.balance),
+    };
+    if let Some(cached) = cached {
+        *cached = balance;
+    }
+}
+
+/// Records the transaction and moves the cached `balance` of the users, suppliers and customers it
+/// posts to, like its Postgres counterpart. Everything is checked before the first write, so on error
+/// `state` is left untouched.
+pub(crate) fn post_transaction(
+    state: &mut InMemoryState,
+    transaction: &NewLedgerTransaction,
+) -> Result<LedgerTransaction> {
+    if !transaction.is_balanced() {
+        return Err(Error::internal("Ledger transaction does not balance"));
+    }
+
+    let mut balances: Vec<(LedgerAccount, i32, i64)> = Vec::new();
+    for entry in &transaction.entries {
+        let accounts = [
+            (LedgerAccount::User, entry.user_id),
+            (LedgerAccount::Supplier, entry.supplier_id),
+            (LedgerAccount::Customer, entry.customer_id),
+        ];
+        for (account, id) in accounts {
+            let Some(id) = id else {
+                continue;
+            };
+            let current = match balances.iter().find(|(a, i, _)| *a == account && *i == id) {
+                Some((_, _, balance)) => *balance,
+                None => balance_of(state, account, id)
+                    .ok_or_else(|| Error::internal(format!("{:?} {} does not exist", account, id)))?,
+            };
+            // the columns are `bigint`, so Postgres would reject this too
+            let balance = current
+                .checked_add(entry.amount)
+                .ok_or_else(|| Error::internal(format!("Balance of {:?} {} out of range", account, id)))?;
+
+            balances.retain(|(a, i, _)| !(*a == account && *i == id));
+            balances.push((account, id, balance));
+        }
+    }
+
+    let created = LedgerTransaction {
+        id: next_id(&state.ledger_transactions, |transaction| transaction.id),
+        kind: transaction.kind,
+        description: transaction.description.clone(),
+        group_id: transaction.group_id,
+        invoice_id: transaction.invoice_id,
+        payment_id: transaction.payment_id,
+        created_date: transaction.created_date,
+    };
+    state.ledger_transactions.push(created.clone());
+
+    for entry in &transaction.entries {
+        let id = next_id(&state.ledger_entries, |entry| entry.id);
+        state.ledger_entries.push(LedgerEntry {
+            id,
+            transaction_id: created.id,
+            user_id: entry.user_id,
+            amount: entry.amount,
+            supplier_id: entry.supplier_id,
+            customer_id: entry.customer_id,
+        });
+    }
+
+    for (account, id, balance) in balances {
+        set_balance(state, account, id, balance);
+    }
+
+    Ok(created)
+}
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        meal::{
+            meal_entity::{Meal, MealProduct, NewMeal},
+            meal_repository::MealRepository,
+        },
+        product::product_entity::Product,
+        version::stale,
+    },
+    infrastructure::in_memory::{next_id, InMemoryDatabase, InMemoryState},
+};
+
+#[async_trait]
+impl MealRepository for InMemoryDatabase {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Meal>> {
+        Ok(self
+            .state()
+            .meals
+            .iter()
+            .find(|meal| meal.id == id && meal.group_id == group_id)
+            .cloned())
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Meal>> {
+        let mut meals: Vec<Meal> = self
+            .state()
+            .meals
+            .iter()
+            .filter(|meal| meal.group_id == group_id)
+            .cloned()
+            .collect();
+        meals.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+
+        Ok(meals)
+    }
+
+    async fn find_products(&self, meal_id: i32) -> Result<Vec<Product>> {
+        let state = self.state();
+        let mut links: Vec<&MealProduct> = state.meal_products.iter().filter(|link| link.meal_id == meal_id).collect();
+        links.sort_by_key(|link| link.position);
+
+        Ok(links
+            .into_iter()
+            .filter_map(|link| state.products.iter().find(|product| product.id == link.product_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn create(&self, meal: NewMeal, product_ids: &[i32]) -> Result<Meal> {
+        let mut state = self.state();
+
+        for (index, product_id) in product_ids.iter().enumerate() {
+            if product_ids[..index].contains(product_id) {
+                return Err(Error::conflict("Product is already part of the meal"));
+            }
+        }
+
+        let meal = Meal {
+            id: next_id(&state.meals, |meal| meal.id),
+            name: meal.name,
+            description: meal.description,
+            group_id: meal.group_id,
+            version: 1,
+        };
+        state.meals.push(meal.clone());
+        state
+            .meal_products
+            .extend(product_ids.iter().enumerate().map(|(position, product_id)| MealProduct {
+                meal_id: meal.id,
+                product_id: *product_id,
+                position: position as i32,
+            }));
+
+        Ok(meal)
+    }
+
+    async fn add_product(&self, meal_id: i32, version: i32, product_id: i32, position: i32) -> Result<()> {
+        let mut state = self.state();
+
+        let meal_index = find_at_version(&state, meal_id, version)?;
+
+        if state
+            .meal_products
+            .iter()
+            .any(|link| link.meal_id == meal_id && link.product_id == product_id)
+        {
+            return Err(Error::conflict("Product is already part of the meal"));
+        }
+
+        for link in state
+            .meal_products
+            .iter_mut()
+            .filter(|link| link.meal_id == meal_id && link.position >= position)
+        {
+            link.position += 1;
+        }
+        state.meal_products.push(MealProduct {
+            meal_id,
+            product_id,
+            position,
+        });
+        state.meals[meal_index].version += 1;
+
+        Ok(())
+    }
+
+    async fn remove_product(&self, meal_id: i32, version: i32, product_id: i32) -> Result<bool> {
+        let mut state = self.state();
+
+        let meal_index = find_at_version(&state, meal_id, version)?;
+
+        let Some(index) = state
+            .meal_products
+            .iter()
+            .position(|link| link.meal_id == meal_id && link.product_id == product_id)
+        else {
+            return Ok(false);
+        };
+        let removed = state.meal_products.remove(index);
+
+        for link in state
+            .meal_products
+            .iter_mut()
+            .filter(|link| link.meal_id == meal_id && link.position > removed.position)
+        {
+            link.position -= 1;
+        }
+        state.meals[meal_index].version += 1;
+
+        Ok(true)
+    }
+}
+
+fn find_at_version(state: &InMemoryState, meal_id: i32, version: i32) -> Result<usize> {
+    state
+        .meals
+        .iter()
+        .position(|meal| meal.id == meal_id && meal.version == version)
+        .ok_or_else(|| stale("Meal"))
+}
@@ -0,0 +1,143 @@
+//! In-memory implementations of every repository trait, for tests that shouldn't need Postgres.
+//!
+//! All repositories share one [`InMemoryDatabase`], so cross-aggregate lookups (group membership,
+//! role permissions, the invoice report joins) behave like their SQL counterparts. Tests seed it
+//! by pushing rows through [`InMemoryDatabase::state`].
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use actix_web::web;
+
+use crate::domain::{
+    active_session::{active_session_entity::ActiveSession, active_session_repository::ActiveSessionRepository},
+    customer::{customer_entity::Customer, customer_repository::CustomerRepository},
+    group::{group_entity::Group, group_repository::GroupRepository},
+    invoice::{invoice_entity::Invoice, invoice_repository::InvoiceRepository},
+    invoice_details::invoice_details_entity::InvoiceDetails,
+    invoice_participant::invoice_participant_entity::InvoiceParticipant,
+    ledger::{
+        ledger_entity::{LedgerEntry, LedgerTransaction},
+        ledger_repository::LedgerRepository,
+    },
+    meal::{
+        meal_entity::{Meal, MealProduct},
+        meal_repository::MealRepository,
+    },
+    order::{order_entity::Order, order_repository::OrderRepository},
+    order_details::order_details_entity::OrderDetails,
+    payment::{payment_entity::Payment, payment_repository::PaymentRepository},
+    product::{product_entity::Product, product_repository::ProductRepository},
+    role::{permission::Permission, role_entity::Role, role_repository::RoleRepository},
+    stock::{
+        stock_entity::{Stock, StockAdjustment},
+        stock_repository::StockRepository,
+    },
+    supplier::{supplier_entity::Supplier, supplier_repository::SupplierRepository},
+    system_log::{system_log_entity::SystemLog, system_log_repository::SystemLogRepository},
+    user::{
+        email_confirmation_entity::EmailConfirmationToken, password_reset_entity::{PasswordResetRequest, PasswordResetToken},
+        user_entity::User, user_repository::UserRepository,
+    },
+};
+
+pub mod active_session_repository_impl;
+pub mod customer_repository_impl;
+pub mod group_repository_impl;
+pub mod invoice_repository_impl;
+pub mod ledger_repository_impl;
+pub mod meal_repository_impl;
+pub mod order_repository_impl;
+pub mod payment_repository_impl;
+pub mod product_repository_impl;
+pub mod role_repository_impl;
+pub mod stock_repository_impl;
+pub mod supplier_repository_impl;
+pub mod system_log_repository_impl;
+pub mod user_repository_impl;
+
+/// The tables backing the in-memory repositories, one `Vec` per table.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryState {
+    pub groups: Vec<Group>,
+    pub roles: Vec<Role>,
+    /// `(role_id, permission)` pairs, like `role_permission`.
+    pub role_permissions: Vec<(i32, Permission)>,
+    pub users: Vec<User>,
+    pub email_confirmation_tokens: Vec<EmailConfirmationToken>,
+    pub password_reset_tokens: Vec<PasswordResetToken>,
+    pub password_reset_requests: Vec<PasswordResetRequest>,
+    pub active_sessions: Vec<ActiveSession>,
+    pub customers: Vec<Customer>,
+    pub suppliers: Vec<Supplier>,
+    pub products: Vec<Product>,
+    pub stocks: Vec<Stock>,
+    pub stock_adjustments: Vec<StockAdjustment>,
+    pub meals: Vec<Meal>,
+    pub meal_products: Vec<MealProduct>,
+    pub invoices: Vec<Invoice>,
+    pub invoice_details: Vec<InvoiceDetails>,
+    pub invoice_participants: Vec<InvoiceParticipant>,
+    pub ledger_transactions: Vec<LedgerTransaction>,
+    pub ledger_entries: Vec<LedgerEntry>,
+    pub orders: Vec<Order>,
+    /// `(order_id, customer_id)` pairs, like `order_customer`.
+    pub order_customers: Vec<(i32, i32)>,
+    pub order_details: Vec<OrderDetails>,
+    pub payments: Vec<Payment>,
+    pub system_logs: Vec<SystemLog>,
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryDatabase {
+    state: Mutex<InMemoryState>,
+}
+
+impl InMemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the tables for reading or seeding. Don't hold the guard across an `.await`.
+    pub fn state(&self) -> MutexGuard<'_, InMemoryState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Next serial id for a table, like a Postgres `serial` column.
+pub(crate) fn next_id<T>(rows: &[T], id: impl Fn(&T) -> i32) -> i32 {
+    rows.iter().map(id).max().unwrap_or(0) + 1
+}
+
+/// Registers `db` as every repository trait, mirroring
+/// [`register_repositories`](crate::infrastructure::repositories_impl::register_repositories).
+pub fn register_repositories(cfg: &mut web::ServiceConfig, db: &Arc<InMemoryDatabase>) {
+    let users: Arc<dyn UserRepository> = db.clone();
+    let sessions: Arc<dyn ActiveSessionRepository> = db.clone();
+    let roles: Arc<dyn RoleRepository> = db.clone();
+    let groups: Arc<dyn GroupRepository> = db.clone();
+    let customers: Arc<dyn CustomerRepository> = db.clone();
+    let suppliers: Arc<dyn SupplierRepository> = db.clone();
+    let products: Arc<dyn ProductRepository> = db.clone();
+    let stocks: Arc<dyn StockRepository> = db.clone();
+    let meals: Arc<dyn MealRepository> = db.clone();
+    let invoices: Arc<dyn InvoiceRepository> = db.clone();
+    let ledger: Arc<dyn LedgerRepository> = db.clone();
+    let orders: Arc<dyn OrderRepository> = db.clone();
+    let payments: Arc<dyn PaymentRepository> = db.clone();
+    let system_logs: Arc<dyn SystemLogRepository> = db.clone();
+
+    cfg.app_data(web::Data::from(users))
+        .app_data(web::Data::from(sessions))
+        .app_data(web::Data::from(roles))
+        .app_data(web::Data::from(groups))
+        .app_data(web::Data::from(customers))
+        .app_data(web::Data::from(suppliers))
+        .app_data(web::Data::from(products))
+        .app_data(web::Data::from(stocks))
+        .app_data(web::Data::from(meals))
+        .app_data(web::Data::from(invoices))
+        .app_data(web::Data::from(ledger))
+        .app_data(web::Data::from(orders))
+        .app_data(web::Data::from(payments))
+        .app_data(web::Data::from(system_logs));
+}
@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        customer::customer_entity::Customer,
+        invoice::invoice_entity::{Invoice, InvoiceDraft},
+        order::{
+            order_entity::{NewOrder, Order},
+            order_repository::OrderRepository,
+            order_status::OrderStatus,
+        },
+        order_details::order_details_entity::OrderDetails,
+        system_log::system_log_entity::NewSystemLog,
+    },
+    infrastructure::in_memory::{
+        invoice_repository_impl::insert_invoice, next_id, system_log_repository_impl::insert_system_log,
+        InMemoryDatabase, InMemoryState,
+    },
+};
+
+#[async_trait]
+impl OrderRepository for InMemoryDatabase {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Order>> {
+        Ok(self
+            .state()
+            .orders
+            .iter()
+            .find(|order| order.id == id && order.group_id == group_id)
+            .cloned())
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Order>> {
+        let mut orders: Vec<Order> = self
+            .state()
+            .orders
+            .iter()
+            .filter(|order| order.group_id == group_id && !order.is_deleted)
+            .cloned()
+            .collect();
+        orders.sort_by(|a, b| b.created_date.cmp(&a.created_date).then(b.id.cmp(&a.id)));
+
+        Ok(orders)
+    }
+
+    async fn find_lines(&self, order_id: i32) -> Result<Vec<OrderDetails>> {
+        let mut lines: Vec<OrderDetails> = self
+            .state()
+            .order_details
+            .iter()
+            .filter(|line| line.order_id == order_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|line| line.id);
+
+        Ok(lines)
+    }
+
+    async fn find_expired_rounds(&self, now: NaiveDateTime) -> Result<Vec<Order>> {
+        let mut orders: Vec<Order> = self
+            .state()
+            .orders
+            .iter()
+            .filter(|order| {
+                order.status == OrderStatus::Open
+                    && !order.is_deleted
+                    && order.cutoff.is_some_and(|cutoff| cutoff <= now)
+            })
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| (order.cutoff, order.id));
+
+        Ok(orders)
+    }
+
+    async fn create(&self, order: NewOrder) -> Result<Order> {
+        Ok(insert_order(&mut self.state(), order, OrderStatus::Draft))
+    }
+
+    async fn open_round(&self, order: NewOrder, log: NewSystemLog) -> Result<Order> {
+        let mut state = self.state();
+        let created = insert_order(&mut state, order, OrderStatus::Open);
+        insert_system_log(&mut state, log);
+
+        Ok(created)
+    }
+
+    async fn add_line(
+        &self,
+        id: i32,
+        user_id: i32,
+        stock_id: i32,
+        quantity: Option<Decimal>,
+        now: NaiveDateTime,
+    ) -> Result<Option<OrderDetails>> {
+        let mut state = self.state();
+
+        let Some(index) = state.orders.iter().position(|order| order.id == id && order.takes_items(now)) else {
+            return Ok(None);
+        };
+
+        let existing = state
+            .customers
+            .iter()
+            .filter(|customer| customer.user_id == user_id)
+            .map(|customer| customer.id)
+            .min();
+        let customer_id = match existing {
+            Some(customer_id) => customer_id,
+            None => {
+                let customer_id = next_id(&state.customers, |customer| customer.id);
+                state.customers.push(Customer { id: customer_id, user_id, balance: 0 });
+                customer_id
+            }
+        };
+        if state
+            .order_details
+            .iter()
+            .any(|line| line.order_id == id && line.stock_id == stock_id && line.customer_id == Some(customer_id))
+        {
+            return Err(Error::conflict(format!("Stock {} is already on your part of this order", stock_id)));
+        }
+        if !state.order_customers.contains(&(id, customer_id)) {
+            state.order_customers.push((id, customer_id));
+        }
+
+        let line = OrderDetails {
+            id: next_id(&state.order_details, |line| line.id),
+            order_id: id,
+            stock_id,
+            customer_id: Some(customer_id),
+            quantity,
+        };
+        state.order_details.push(line.clone());
+        state.orders[index].last_modification_date = now;
+
+        Ok(Some(line))
+    }
+
+    async fn remove_line(&self, id: i32, line_id: i32, now: NaiveDateTime) -> Result<bool> {
+        let mut state = self.state();
+
+        let Some(index) = state.orders.iter().position(|order| order.id == id && order.takes_items(now)) else {
+            return Ok(false);
+        };
+        state.orders[index].last_modification_date = now;
+
+        let Some(position) = state.order_details.iter().position(|line| line.id == line_id && line.order_id == id)
+        else {
+            return Ok(true);
+        };
+        let removed = state.order_details.remove(position);
+
+        if let Some(customer_id) = removed.customer_id {
+            let still_ordering =
+                state.order_details.iter().any(|line| line.order_id == id && line.customer_id == Some(customer_id));
+            if !still_ordering {
+                state.order_customers.retain(|pair| *pair != (id, customer_id));
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn transition(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        from: OrderStatus,
+        to: OrderStatus,
+        now: NaiveDateTime,
+        log: NewSystemLog,
+    ) -> Result<Option<Order>> {
+        let mut state = self.state();
+
+        let Some(order) = state
+            .orders
+            .iter_mut()
+            .find(|order| order.id == id && order.last_modification_date == last_modified && order.status == from)
+        else {
+            return Ok(None);
+        };
+        order.status = to;
+        order.last_modification_date = now;
+        let order = order.clone();
+
+        insert_system_log(&mut state, log);
+
+        Ok(Some(order))
+    }
+
+    async fn invoice(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        invoices: &[InvoiceDraft],
+        now: NaiveDateTime,
+        log: NewSystemLog,
+    ) -> Result<Option<(Order, Vec<Invoice>)>> {
+        let mut state = self.state();
+
+        let Some(index) = state.orders.iter().position(|order| {
+            order.id == id && order.last_modification_date == last_modified && order.status == OrderStatus::Fulfilled
+        }) else {
+            return Ok(None);
+        };
+
+        // several invoices, so they go to a copy that only replaces the state once all of them succeeded
+        let mut draft = state.clone();
+        let created = invoices
+            .iter()
+            .map(|invoice| {
+                insert_invoice(&mut draft, &invoice.invoice, &invoice.stock, &invoice.participants, &invoice.entries)
+            })
+            .collect::<Result<Vec<Invoice>>>()?;
+
+        let order = &mut draft.orders[index];
+        order.status = OrderStatus::Invoiced;
+        order.last_modification_date = now;
+        let order = order.clone();
+
+        insert_system_log(&mut draft, log);
+        *state = draft;
+
+        Ok(Some((order, created)))
+    }
+}
+
+fn insert_order(state: &mut InMemoryState, order: NewOrder, status: OrderStatus) -> Order {
+    let created = Order {
+        id: next_id(&state.orders, |order| order.id),
+        is_deleted: false,
+        deleted_by: 0,
+        created_date: order.created_date,
+        last_modification_date: order.created_date,
+        group_id: order.group_id,
+        status,
+        created_by: Some(order.created_by),
+        round_date: order.round_date,
+        cutoff: order.cutoff,
+    };
+    state.orders.push(created.clone());
+
+    created
+}
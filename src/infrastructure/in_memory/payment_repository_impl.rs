@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        ledger::ledger_entity::{LedgerTransaction, NewLedgerTransaction},
+        payment::{
+            payment_entity::{NewPayment, Payment, PaymentStatus},
+            payment_repository::PaymentRepository,
+        },
+        system_log::system_log_entity::NewSystemLog,
+    },
+    infrastructure::in_memory::{
+        ledger_repository_impl::post_transaction, next_id, system_log_repository_impl::insert_system_log,
+        InMemoryDatabase,
+    },
+};
+
+#[async_trait]
+impl PaymentRepository for InMemoryDatabase {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Payment>> {
+        Ok(self
+            .state()
+            .payments
+            .iter()
+            .find(|payment| payment.id == id && payment.group_id == group_id)
+            .cloned())
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Payment>> {
+        let mut payments: Vec<Payment> = self
+            .state()
+            .payments
+            .iter()
+            .filter(|payment| payment.group_id == group_id)
+            .cloned()
+            .collect();
+        payments.sort_by(|a, b| b.created_date.cmp(&a.created_date).then(b.id.cmp(&a.id)));
+
+        Ok(payments)
+    }
+
+    async fn create(&self, payment: NewPayment) -> Result<Payment> {
+        let mut state = self.state();
+        let created = Payment {
+            id: next_id(&state.payments, |payment| payment.id),
+            payer_id: payment.payer_id,
+            payee_id: payment.payee_id,
+            group_id: payment.group_id,
+            amount: payment.amount,
+            method: payment.method,
+            note: payment.note,
+            status: PaymentStatus::Pending,
+            created_date: payment.created_date,
+            resolved_date: None,
+        };
+        state.payments.push(created.clone());
+
+        Ok(created)
+    }
+
+    async fn create_confirmed(
+        &self,
+        payments: Vec<(NewPayment, NewLedgerTransaction)>,
+        now: NaiveDateTime,
+    ) -> Result<Vec<LedgerTransaction>> {
+        let mut state = self.state();
+        // booked on a copy that only replaces the tables once every payment went through
+        let mut draft = state.clone();
+
+        let mut created = Vec::with_capacity(payments.len());
+        for (payment, mut transaction) in payments {
+            let id = next_id(&draft.payments, |payment| payment.id);
+            draft.payments.push(Payment {
+                id,
+                payer_id: payment.payer_id,
+                payee_id: payment.payee_id,
+                group_id: payment.group_id,
+                amount: payment.amount,
+                method: payment.method,
+                note: payment.note,
+                status: PaymentStatus::Confirmed,
+                created_date: payment.created_date,
+                resolved_date: Some(now),
+            });
+
+            transaction.payment_id = Some(id);
+            created.push(post_transaction(&mut draft, &transaction)?);
+        }
+        *state = draft;
+
+        Ok(created)
+    }
+
+    async fn confirm(
+        &self,
+        id: i32,
+        now: NaiveDateTime,
+        transaction: NewLedgerTransaction,
+        log: NewSystemLog,
+    ) -> Result<Option<Payment>> {
+        let mut state = self.state();
+
+        if !state.payments.iter().any(|payment| payment.id == id && payment.status == PaymentStatus::Pending) {
+            return Ok(None);
+        }
+        // leaves the tables untouched if it fails, so it goes first
+        post_transaction(&mut state, &transaction)?;
+        insert_system_log(&mut state, log);
+
+        let payment = state.payments.iter_mut().find(|payment| payment.id == id).unwrap();
+        payment.status = PaymentStatus::Confirmed;
+        payment.resolved_date = Some(now);
+
+        Ok(Some(payment.clone()))
+    }
+
+    async fn reject(&self, id: i32, now: NaiveDateTime, log: NewSystemLog) -> Result<Option<Payment>> {
+        let mut state = self.state();
+
+        let Some(payment) = state
+            .payments
+            .iter_mut()
+            .find(|payment| payment.id == id && payment.status == PaymentStatus::Pending)
+        else {
+            return Ok(None);
+        };
+        payment.status = PaymentStatus::Rejected;
+        payment.resolved_date = Some(now);
+        let payment = payment.clone();
+
+        insert_system_log(&mut state, log);
+
+        Ok(Some(payment))
+    }
+}
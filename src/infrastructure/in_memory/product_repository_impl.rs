@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::product::{product_entity::Product, product_repository::ProductRepository},
+    infrastructure::in_memory::{next_id, InMemoryDatabase},
+};
+
+#[async_trait]
+impl ProductRepository for InMemoryDatabase {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Product>> {
+        Ok(self.state().products.iter().find(|product| product.id == id).cloned())
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Product>> {
+        let mut products: Vec<Product> = self
+            .state()
+            .products
+            .iter()
+            .filter(|product| product.group_id == group_id)
+            .cloned()
+            .collect();
+        products.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+
+        Ok(products)
+    }
+
+    async fn create(&self, name: &str, group_id: i32) -> Result<Product> {
+        let mut state = self.state();
+        let product = Product {
+            id: next_id(&state.products, |product| product.id),
+            name: name.to_string(),
+            group_id,
+        };
+        state.products.push(product.clone());
+
+        Ok(product)
+    }
+}
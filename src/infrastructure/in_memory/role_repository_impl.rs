@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::role::{permission::Permission, role_entity::Role, role_repository::RoleRepository},
+    infrastructure::in_memory::InMemoryDatabase,
+};
+
+#[async_trait]
+impl RoleRepository for InMemoryDatabase {
+    async fn find_all(&self) -> Result<Vec<Role>> {
+        let mut roles = self.state().roles.clone();
+        roles.sort_by_key(|role| role.id);
+
+        Ok(roles)
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Role>> {
+        Ok(self.state().roles.iter().find(|role| role.id == id).cloned())
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Role>> {
+        Ok(self.state().roles.iter().find(|role| role.name == name).cloned())
+    }
+
+    async fn find_user_permissions(&self, user_id: i32) -> Result<Vec<Permission>> {
+        let state = self.state();
+        let Some(role_id) = state.users.iter().find(|user| user.id == user_id).map(|user| user.role_id) else {
+            return Ok(Vec::new());
+        };
+
+        let mut permissions: Vec<Permission> = state
+            .role_permissions
+            .iter()
+            .filter(|(role, _)| *role == role_id)
+            .map(|(_, permission)| *permission)
+            .collect();
+        permissions.sort_by_key(|permission| permission.as_str());
+
+        Ok(permissions)
+    }
+
+    async fn user_has_permission(&self, user_id: i32, permission: Permission) -> Result<bool> {
+        Ok(self.find_user_permissions(user_id).await?.contains(&permission))
+    }
+}
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        stock::{
+            stock_entity::{NewStock, Stock, StockAdjustment, StockCount},
+            stock_repository::StockRepository,
+        },
+        version::stale,
+    },
+    infrastructure::in_memory::{next_id, InMemoryDatabase},
+};
+
+#[async_trait]
+impl StockRepository for InMemoryDatabase {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Stock>> {
+        Ok(self.state().stocks.iter().find(|stock| stock.id == id).cloned())
+    }
+
+    async fn find_in_group(&self, group_id: i32, id: i32) -> Result<Option<Stock>> {
+        let state = self.state();
+        Ok(state
+            .stocks
+            .iter()
+            .find(|stock| {
+                stock.id == id
+                    && state
+                        .products
+                        .iter()
+                        .any(|product| product.id == stock.product_id && product.group_id == group_id)
+            })
+            .cloned())
+    }
+
+    async fn find_by_product(&self, product_id: i32) -> Result<Vec<Stock>> {
+        let mut stocks: Vec<Stock> = self
+            .state()
+            .stocks
+            .iter()
+            .filter(|stock| stock.product_id == product_id)
+            .cloned()
+            .collect();
+        stocks.sort_by_key(|stock| stock.id);
+
+        Ok(stocks)
+    }
+
+    async fn find_available_by_group(&self, group_id: i32) -> Result<Vec<Stock>> {
+        let state = self.state();
+        let mut stocks: Vec<Stock> = state
+            .stocks
+            .iter()
+            .filter(|stock| {
+                !stock.consumed
+                    && state
+                        .products
+                        .iter()
+                        .any(|product| product.id == stock.product_id && product.group_id == group_id)
+            })
+            .cloned()
+            .collect();
+        stocks.sort_by_key(|stock| stock.id);
+
+        Ok(stocks)
+    }
+
+    async fn find_adjustments(&self, stock_id: i32) -> Result<Vec<StockAdjustment>> {
+        let mut adjustments: Vec<StockAdjustment> = self
+            .state()
+            .stock_adjustments
+            .iter()
+            .filter(|adjustment| adjustment.stock_id == stock_id)
+            .cloned()
+            .collect();
+        adjustments.sort_by_key(|adjustment| adjustment.id);
+
+        Ok(adjustments)
+    }
+
+    async fn create(&self, stock: NewStock) -> Result<Stock> {
+        let mut state = self.state();
+        let created = Stock {
+            id: next_id(&state.stocks, |stock| stock.id),
+            price: stock.price,
+            consumed: false,
+            product_id: stock.product_id,
+            supplier_id: stock.supplier_id,
+            unit: stock.unit,
+            quantity: stock.quantity,
+            remaining: stock.quantity,
+            purchased_date: stock.purchased_date,
+            version: 1,
+        };
+        state.stocks.push(created.clone());
+
+        Ok(created)
+    }
+
+    async fn adjust(&self, id: i32, version: i32, count: StockCount) -> Result<Option<(Stock, StockAdjustment)>> {
+        let mut state = self.state();
+
+        let Some(stock) = state.stocks.iter_mut().find(|stock| stock.id == id) else {
+            return Ok(None);
+        };
+        if stock.version != version {
+            return Err(stale("Stock"));
+        }
+        let change = count.remaining - stock.remaining;
+        stock.remaining = count.remaining;
+        stock.consumed = count.remaining.is_zero();
+        stock.version += 1;
+        let stock = stock.clone();
+
+        let adjustment = StockAdjustment {
+            id: next_id(&state.stock_adjustments, |adjustment| adjustment.id),
+            stock_id: id,
+            change,
+            reason: count.reason,
+            user_id: count.user_id,
+            created_date: count.created_date,
+        };
+        state.stock_adjustments.push(adjustment.clone());
+
+        Ok(Some((stock, adjustment)))
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+
+use crate::{
+    application::error::Result,
+    domain::supplier::{supplier_entity::Supplier, supplier_repository::SupplierRepository},
+    infrastructure::in_memory::InMemoryDatabase,
+};
+
+#[async_trait]
+impl SupplierRepository for InMemoryDatabase {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Supplier>> {
+        Ok(self.state().suppliers.iter().find(|supplier| supplier.id == id).cloned())
+    }
+
+    async fn find_by_user(&self, user_id: i32) -> Result<Option<Supplier>> {
+        Ok(self.state().suppliers.iter().find(|supplier| supplier.user_id == user_id).cloned())
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Supplier>> {
+        let state = self.state();
+        let mut suppliers: Vec<Supplier> = state
+            .suppliers
+            .iter()
+            .filter(|supplier| {
+                state
+                    .users
+                    .iter()
+                    .any(|user| user.id == supplier.user_id && user.group_id == group_id)
+            })
+            .cloned()
+            .collect();
+        suppliers.sort_by_key(|supplier| supplier.id);
+
+        Ok(suppliers)
+    }
+}
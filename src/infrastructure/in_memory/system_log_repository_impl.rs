@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::{
+    application::error::Result,
+    domain::system_log::{
+        system_log_entity::{NewSystemLog, SystemLog},
+        system_log_repository::SystemLogRepository,
+    },
+    infrastructure::in_memory::{next_id, InMemoryDatabase, InMemoryState},
+};
+
+#[async_trait]
+impl SystemLogRepository for InMemoryDatabase {
+    async fn create(&self, entry: NewSystemLog) -> Result<SystemLog> {
+        Ok(insert_system_log(&mut self.state(), entry))
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<SystemLog>> {
+        let mut logs: Vec<SystemLog> = self
+            .state()
+            .system_logs
+            .iter()
+            .filter(|log| log.group_id == group_id)
+            .cloned()
+            .collect();
+        logs.sort_by(|a, b| b.date.cmp(&a.date).then(b.id.cmp(&a.id)));
+
+        Ok(logs)
+    }
+}
+
+/// Appends a log entry, for repositories that log as part of a larger write.
+pub(crate) fn insert_system_log(state: &mut InMemoryState, entry: NewSystemLog) -> SystemLog {
+    let log = SystemLog {
+        id: next_id(&state.system_logs, |log| log.id),
+        transaction_type: entry.transaction_type,
+        description: entry.description,
+        date: Utc::now().naive_utc(),
+        user_id: entry.user_id,
+        group_id: entry.group_id,
+    };
+    state.system_logs.push(log.clone());
+
+    log
+}
@@ -0,0 +1,282 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        user::{
+            email_confirmation_entity::EmailConfirmationToken,
+            password_reset_entity::{PasswordResetRequest, PasswordResetToken},
+            user_entity::{NewUser, User},
+            user_repository::UserRepository,
+        },
+        version::stale,
+    },
+    infrastructure::in_memory::{next_id, InMemoryDatabase, InMemoryState},
+};
+
+#[async_trait]
+impl UserRepository for InMemoryDatabase {
+    async fn find_by_id(&self, id: i32) -> Result<Option<User>> {
+        Ok(self.state().users.iter().find(|user| user.id == id).cloned())
+    }
+
+    async fn find_active_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|user| user.email == email && user.is_active)
+            .cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(self.state().users.iter().find(|user| user.email == email).cloned())
+    }
+
+    async fn find_by_display_id(&self, user_display_id: &str) -> Result<Option<User>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|user| user.user_display_id == user_display_id)
+            .cloned())
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<User>> {
+        Ok(sorted_by_name(
+            self.state().users.iter().filter(|user| user.group_id == group_id).cloned().collect(),
+        ))
+    }
+
+    async fn search_by_name(&self, group_id: i32, name: &str) -> Result<Vec<User>> {
+        let name = name.to_lowercase();
+
+        Ok(sorted_by_name(
+            self.state()
+                .users
+                .iter()
+                .filter(|user| user.group_id == group_id && user.name.to_lowercase().contains(&name))
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    async fn find_active_by_group(&self, group_id: i32) -> Result<Vec<User>> {
+        let mut users: Vec<User> = self
+            .state()
+            .users
+            .iter()
+            .filter(|user| user.group_id == group_id && user.is_active)
+            .cloned()
+            .collect();
+        users.sort_by_key(|user| (user.balance, user.id));
+
+        Ok(users)
+    }
+
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<()> {
+        if let Some(user) = self.state().users.iter_mut().find(|user| user.id == user_id) {
+            user.password = password_hash.to_string();
+        }
+
+        Ok(())
+    }
+
+    async fn create(&self, user: NewUser) -> Result<User> {
+        let mut state = self.state();
+        insert_user(&mut state, user)
+    }
+
+    async fn update(&self, user: User) -> Result<User> {
+        let mut state = self.state();
+        check_unique(&state, user.id, &user.email, &user.user_display_id)?;
+
+        let existing = state
+            .users
+            .iter_mut()
+            .find(|existing| existing.id == user.id && existing.version == user.version)
+            .ok_or_else(|| stale("User"))?;
+        *existing = User {
+            balance: existing.balance,
+            version: existing.version + 1,
+            ..user
+        };
+
+        Ok(existing.clone())
+    }
+
+    async fn create_email_confirmation(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<()> {
+        let mut state = self.state();
+        let token = EmailConfirmationToken {
+            id: next_id(&state.email_confirmation_tokens, |token| token.id),
+            user_id,
+            token_hash: token_hash.to_string(),
+            expires_at,
+            used_at: None,
+            created_date: Utc::now().naive_utc(),
+        };
+        state.email_confirmation_tokens.push(token);
+
+        Ok(())
+    }
+
+    async fn create_with_confirmation(
+        &self,
+        user: NewUser,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<User> {
+        let mut state = self.state();
+        let user = insert_user(&mut state, user)?;
+        let token = EmailConfirmationToken {
+            id: next_id(&state.email_confirmation_tokens, |token| token.id),
+            user_id: user.id,
+            token_hash: token_hash.to_string(),
+            expires_at,
+            used_at: None,
+            created_date: Utc::now().naive_utc(),
+        };
+        state.email_confirmation_tokens.push(token);
+
+        Ok(user)
+    }
+
+    async fn delete_unconfirmed(&self, user_id: i32) -> Result<()> {
+        let mut state = self.state();
+
+        state.email_confirmation_tokens.retain(|token| token.user_id != user_id);
+        state.users.retain(|user| user.id != user_id || user.email_confirmed);
+
+        Ok(())
+    }
+
+    async fn confirm_email(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<i32>> {
+        let mut state = self.state();
+
+        let Some(token) = state
+            .email_confirmation_tokens
+            .iter_mut()
+            .find(|token| token.token_hash == token_hash && token.used_at.is_none() && token.expires_at > now)
+        else {
+            return Ok(None);
+        };
+        token.used_at = Some(now);
+        let user_id = token.user_id;
+
+        if let Some(user) = state.users.iter_mut().find(|user| user.id == user_id) {
+            user.email_confirmed = true;
+        }
+
+        Ok(Some(user_id))
+    }
+
+    async fn count_password_reset_requests_since(&self, email: &str, since: NaiveDateTime) -> Result<i64> {
+        Ok(self
+            .state()
+            .password_reset_requests
+            .iter()
+            .filter(|request| request.email == email && request.created_date > since)
+            .count() as i64)
+    }
+
+    async fn create_password_reset_request(&self, email: &str) -> Result<()> {
+        let mut state = self.state();
+        let request = PasswordResetRequest {
+            id: next_id(&state.password_reset_requests, |request| request.id),
+            email: email.to_string(),
+            created_date: Utc::now().naive_utc(),
+        };
+        state.password_reset_requests.push(request);
+
+        Ok(())
+    }
+
+    async fn create_password_reset(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<()> {
+        let mut state = self.state();
+        let token = PasswordResetToken {
+            id: next_id(&state.password_reset_tokens, |token| token.id),
+            user_id,
+            token_hash: token_hash.to_string(),
+            expires_at,
+            used_at: None,
+            created_date: Utc::now().naive_utc(),
+        };
+        state.password_reset_tokens.push(token);
+
+        Ok(())
+    }
+
+    async fn reset_password(&self, token_hash: &str, password_hash: &str, now: NaiveDateTime) -> Result<Option<i32>> {
+        let mut state = self.state();
+
+        let Some(user_id) = state
+            .password_reset_tokens
+            .iter()
+            .find(|token| token.token_hash == token_hash && token.used_at.is_none() && token.expires_at > now)
+            .map(|token| token.user_id)
+        else {
+            return Ok(None);
+        };
+
+        if let Some(user) = state.users.iter_mut().find(|user| user.id == user_id) {
+            user.password = password_hash.to_string();
+        }
+        for token in state
+            .password_reset_tokens
+            .iter_mut()
+            .filter(|token| token.user_id == user_id && token.used_at.is_none())
+        {
+            token.used_at = Some(now);
+        }
+        for session in state
+            .active_sessions
+            .iter_mut()
+            .filter(|session| session.user_id == user_id && session.revoked_at.is_none())
+        {
+            session.revoked_at = Some(now);
+        }
+
+        Ok(Some(user_id))
+    }
+}
+
+fn sorted_by_name(mut users: Vec<User>) -> Vec<User> {
+    users.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    users
+}
+
+/// Mirrors the `user_email_key` and `user_user_display_id_key` unique constraints.
+fn check_unique(state: &InMemoryState, user_id: i32, email: &str, user_display_id: &str) -> Result<()> {
+    let others = || state.users.iter().filter(|existing| existing.id != user_id);
+
+    if others().any(|existing| existing.email == email) {
+        return Err(Error::conflict("Email is already registered"));
+    }
+    if others().any(|existing| existing.user_display_id == user_display_id) {
+        return Err(Error::conflict("User display ID is already taken"));
+    }
+
+    Ok(())
+}
+
+fn insert_user(state: &mut InMemoryState, user: NewUser) -> Result<User> {
+    check_unique(state, 0, &user.email, &user.user_display_id)?;
+
+    let user = User {
+        id: next_id(&state.users, |user| user.id),
+        name: user.name,
+        password: user.password,
+        email: user.email,
+        email_confirmed: user.email_confirmed,
+        user_display_id: user.user_display_id,
+        balance: user.balance,
+        is_active: user.is_active,
+        role_id: user.role_id,
+        group_id: user.group_id,
+        version: 1,
+    };
+    state.users.push(user.clone());
+
+    Ok(user)
+}
@@ -0,0 +1,80 @@
+use std::env;
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, errors::Error as JwtError, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::user::user_entity::User;
+
+/// Payload of an access token issued on login.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub user_id: i32,
+    /// `active_session.id` the token was issued for; revoking the session invalidates the token.
+    pub session_id: i32,
+    pub role_id: i32,
+    pub group_id: i32,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Signing keys and token lifetimes, shared with the app as `web::Data<JwtConfig>`.
+#[derive(Clone)]
+pub struct JwtConfig {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    access_token_ttl: Duration,
+    refresh_token_ttl: Duration,
+}
+
+impl JwtConfig {
+    pub fn new(secret: &str, access_token_ttl: Duration, refresh_token_ttl: Duration) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            access_token_ttl,
+            refresh_token_ttl,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+        let minutes = env::var("JWT_ACCESS_TOKEN_MINUTES")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(15);
+        let days = env::var("JWT_REFRESH_TOKEN_DAYS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(30);
+
+        Self::new(&secret, Duration::minutes(minutes), Duration::days(days))
+    }
+
+    pub fn access_token_ttl(&self) -> Duration {
+        self.access_token_ttl
+    }
+
+    pub fn refresh_token_ttl(&self) -> Duration {
+        self.refresh_token_ttl
+    }
+
+    pub fn create_access_token(&self, user: &User, session_id: i32) -> Result<String, JwtError> {
+        let now = Utc::now();
+        let claims = Claims {
+            user_id: user.id,
+            session_id,
+            role_id: user.role_id,
+            group_id: user.group_id,
+            iat: now.timestamp(),
+            exp: (now + self.access_token_ttl).timestamp(),
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key)
+    }
+
+    /// Checks the signature and expiry of `token` and returns its claims.
+    pub fn verify_access_token(&self, token: &str) -> Result<Claims, JwtError> {
+        decode::<Claims>(token, &self.decoding_key, &Validation::default()).map(|data| data.claims)
+    }
+}
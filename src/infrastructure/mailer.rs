@@ -0,0 +1,59 @@
+use std::{env, path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail transport, shared with the app as `web::Data<dyn Mailer>`.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: MailMessage) -> std::io::Result<()>;
+}
+
+/// Prints messages to stdout. Used when no outbox file is configured.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: MailMessage) -> std::io::Result<()> {
+        println!("[mail] to: {} | subject: {}\n{}", message.to, message.subject, message.body);
+        Ok(())
+    }
+}
+
+/// Appends messages to a local file so they can be inspected without an SMTP server.
+pub struct FileMailer {
+    path: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: MailMessage) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        let entry = format!(
+            "To: {}\nSubject: {}\n\n{}\n\n----\n",
+            message.to, message.subject, message.body
+        );
+        file.write_all(entry.as_bytes()).await
+    }
+}
+
+/// Uses `MAIL_OUTBOX` as a [`FileMailer`] path when set, otherwise falls back to [`LogMailer`].
+pub fn mailer_from_env() -> Arc<dyn Mailer> {
+    match env::var("MAIL_OUTBOX") {
+        Ok(path) if !path.is_empty() => Arc::new(FileMailer::new(path)),
+        _ => Arc::new(LogMailer),
+    }
+}
@@ -1,4 +1,9 @@
 // src/infrastructure/mod.rs
 pub mod database;
+pub mod in_memory;
 pub mod jwt;
-pub mod repositories_impl;
\ No newline at end of file
+pub mod mailer;
+pub mod password;
+pub mod repositories_impl;
+pub mod round_closer;
+pub mod token;
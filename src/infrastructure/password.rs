@@ -0,0 +1,58 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, Error as HashError, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+
+/// Outcome of checking a password against the value stored in `"user".password`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordCheck {
+    pub valid: bool,
+    /// The stored value is plaintext or was hashed with outdated parameters and should be replaced.
+    pub needs_rehash: bool,
+}
+
+fn hasher() -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+}
+
+pub fn hash_password(password: &str) -> Result<String, HashError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(hasher().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+/// Verifies `password` against `stored`.
+///
+/// Rows created before hashing was introduced hold the plaintext password; those are still
+/// accepted once and flagged for rehashing so they get upgraded on the next successful login.
+pub fn verify_password(password: &str, stored: &str) -> PasswordCheck {
+    let Ok(parsed) = PasswordHash::new(stored) else {
+        return PasswordCheck {
+            valid: constant_time_eq(password.as_bytes(), stored.as_bytes()),
+            needs_rehash: true,
+        };
+    };
+
+    let hasher = hasher();
+    let valid = hasher.verify_password(password.as_bytes(), &parsed).is_ok();
+
+    PasswordCheck {
+        valid,
+        needs_rehash: valid && !uses_current_params(&parsed, &hasher),
+    }
+}
+
+fn uses_current_params(parsed: &PasswordHash, hasher: &Argon2) -> bool {
+    let current = hasher.params();
+
+    parsed.algorithm == Algorithm::Argon2id.ident()
+        && parsed.version == Some(Version::V0x13.into())
+        && Params::try_from(parsed).is_ok_and(|params| {
+            params.m_cost() == current.m_cost()
+                && params.t_cost() == current.t_cost()
+                && params.p_cost() == current.p_cost()
+        })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::active_session::{
+        active_session_entity::{ActiveSession, NewActiveSession},
+        active_session_repository::ActiveSessionRepository,
+    },
+};
+
+pub struct PgActiveSessionRepository {
+    pool: PgPool,
+}
+
+impl PgActiveSessionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ActiveSessionRepository for PgActiveSessionRepository {
+    async fn create(&self, session: NewActiveSession) -> Result<ActiveSession> {
+        sqlx::query_as::<_, ActiveSession>(
+            "INSERT INTO active_session (user_id, group_id, refresh_token_hash, user_agent, ip_address, created_date, last_seen_date, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $6, $7)
+             RETURNING *",
+        )
+        .bind(session.user_id)
+        .bind(session.group_id)
+        .bind(session.refresh_token_hash)
+        .bind(session.user_agent)
+        .bind(session.ip_address)
+        .bind(session.created_date)
+        .bind(session.expires_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        now: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<Option<ActiveSession>> {
+        sqlx::query_as::<_, ActiveSession>(
+            "UPDATE active_session
+             SET refresh_token_hash = $1, last_seen_date = $2, expires_at = $3,
+                 user_agent = COALESCE($4, user_agent), ip_address = COALESCE($5, ip_address)
+             WHERE refresh_token_hash = $6 AND revoked_at IS NULL AND expires_at > $2
+             RETURNING *",
+        )
+        .bind(new_refresh_token_hash)
+        .bind(now)
+        .bind(expires_at)
+        .bind(user_agent)
+        .bind(ip_address)
+        .bind(refresh_token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn touch(
+        &self,
+        session_id: i32,
+        user_id: i32,
+        now: NaiveDateTime,
+        stale_before: NaiveDateTime,
+    ) -> Result<bool> {
+        // only a stale session is written to; a live one seen since is just read
+        sqlx::query_scalar(
+            "WITH live AS (
+                 SELECT id, last_seen_date FROM active_session
+                 WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL AND expires_at > $1
+             ), touched AS (
+                 UPDATE active_session s SET last_seen_date = $1
+                 FROM live
+                 WHERE s.id = live.id AND live.last_seen_date < $4
+             )
+             SELECT EXISTS (SELECT 1 FROM live)",
+        )
+        .bind(now)
+        .bind(session_id)
+        .bind(user_id)
+        .bind(stale_before)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_live_by_user(&self, user_id: i32, now: NaiveDateTime) -> Result<Vec<ActiveSession>> {
+        sqlx::query_as::<_, ActiveSession>(
+            "SELECT * FROM active_session
+             WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+             ORDER BY last_seen_date DESC",
+        )
+        .bind(user_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn revoke(&self, user_id: i32, session_id: i32, now: NaiveDateTime) -> Result<bool> {
+        let revoked = sqlx::query(
+            "UPDATE active_session SET revoked_at = $1 WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL",
+        )
+        .bind(now)
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(revoked.rows_affected() == 1)
+    }
+
+    async fn revoke_all(&self, user_id: i32, now: NaiveDateTime) -> Result<u64> {
+        let revoked = sqlx::query("UPDATE active_session SET revoked_at = $1 WHERE user_id = $2 AND revoked_at IS NULL")
+            .bind(now)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(revoked.rows_affected())
+    }
+
+    async fn revoke_others(&self, user_id: i32, keep_session_id: i32, now: NaiveDateTime) -> Result<u64> {
+        let revoked = sqlx::query(
+            "UPDATE active_session SET revoked_at = $1 WHERE user_id = $2 AND id <> $3 AND revoked_at IS NULL",
+        )
+        .bind(now)
+        .bind(user_id)
+        .bind(keep_session_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(revoked.rows_affected())
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::customer::{customer_entity::Customer, customer_repository::CustomerRepository},
+};
+
+pub struct PgCustomerRepository {
+    pool: PgPool,
+}
+
+impl PgCustomerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CustomerRepository for PgCustomerRepository {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Customer>> {
+        sqlx::query_as::<_, Customer>("SELECT * FROM customer WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_user(&self, user_id: i32) -> Result<Vec<Customer>> {
+        sqlx::query_as::<_, Customer>("SELECT * FROM customer WHERE user_id = $1 ORDER BY id")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+}
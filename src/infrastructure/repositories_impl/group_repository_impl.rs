@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::group::{group_entity::Group, group_repository::GroupRepository},
+};
+
+pub struct PgGroupRepository {
+    pool: PgPool,
+}
+
+impl PgGroupRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl GroupRepository for PgGroupRepository {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Group>> {
+        sqlx::query_as::<_, Group>("SELECT * FROM \"group\" WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn is_member(&self, user_id: i32, group_id: i32) -> Result<bool> {
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM \"user\" WHERE id = $1 AND group_id = $2 AND is_active = true)",
+        )
+        .bind(user_id)
+        .bind(group_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+}
@@ -0,0 +1,405 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        invoice::{
+            invoice_entity::{Invoice, InvoiceChanges, NewInvoice},
+            invoice_repository::InvoiceRepository,
+        },
+        invoice_details::invoice_details_entity::InvoiceDetails,
+        invoice_participant::invoice_participant_entity::{InvoiceParticipant, NewInvoiceParticipant},
+        ledger::ledger_entity::{LedgerKind, NewLedgerEntry, NewLedgerTransaction},
+        stock::stock_entity::StockUse,
+    },
+    infrastructure::repositories_impl::ledger_repository_impl::post_transaction,
+    interfaces::dtos::invoice_dto::InvoiceRow,
+};
+
+pub struct PgInvoiceRepository {
+    pool: PgPool,
+}
+
+impl PgInvoiceRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl InvoiceRepository for PgInvoiceRepository {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Invoice>> {
+        sqlx::query_as::<_, Invoice>("SELECT * FROM invoice WHERE id = $1 AND group_id = $2")
+            .bind(id)
+            .bind(group_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_meal(&self, group_id: i32, meal_id: i32) -> Result<Vec<Invoice>> {
+        sqlx::query_as::<_, Invoice>(
+            "SELECT * FROM invoice
+             WHERE group_id = $1 AND meal_id = $2 AND is_deleted = false
+             ORDER BY created_date DESC, id DESC",
+        )
+        .bind(group_id)
+        .bind(meal_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_deleted(&self, group_id: i32) -> Result<Vec<Invoice>> {
+        sqlx::query_as::<_, Invoice>(
+            "SELECT * FROM invoice
+             WHERE group_id = $1 AND is_deleted = true
+             ORDER BY last_modification_date DESC, id DESC",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_report_rows(
+        &self,
+        group_id: i32,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<InvoiceRow>> {
+        sqlx::query_as::<_, InvoiceRow>(
+            "SELECT invoice.id, price, created_date, u.id AS supplier_id, u.name AS supplier_name,
+                    string_agg(DISTINCT p.name, ', ' ORDER BY p.name) AS meal
+             FROM invoice
+             INNER JOIN invoice_details ind ON invoice.id = ind.invoice_id
+             INNER JOIN meal ON meal.id = invoice.meal_id
+             INNER JOIN meal_product mp ON meal.id = mp.meal_id
+             INNER JOIN product p ON p.id = mp.product_id
+             INNER JOIN supplier s ON s.id = invoice.supplier_id
+             INNER JOIN \"user\" u ON u.id = s.user_id
+             WHERE invoice.group_id = $1 AND is_deleted = false AND created_date::date BETWEEN $2 AND $3
+             GROUP BY invoice.id, price, created_date, u.name, meal.id, u.id, invoice.supplier_id",
+        )
+        .bind(group_id)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_participants(&self, invoice_id: i32) -> Result<Vec<InvoiceParticipant>> {
+        sqlx::query_as::<_, InvoiceParticipant>(
+            "SELECT * FROM invoice_participant WHERE invoice_id = $1 ORDER BY user_id",
+        )
+        .bind(invoice_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_billed_customers(&self, invoice_id: i32) -> Result<Vec<(i32, i32)>> {
+        sqlx::query_as::<_, (i32, i32)>(
+            "SELECT DISTINCT e.user_id, e.customer_id
+             FROM ledger_entry e
+             INNER JOIN ledger_transaction t ON t.id = e.transaction_id
+             WHERE t.invoice_id = $1 AND e.user_id IS NOT NULL AND e.customer_id IS NOT NULL
+             ORDER BY e.user_id, e.customer_id",
+        )
+        .bind(invoice_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_details(&self, invoice_id: i32) -> Result<Vec<InvoiceDetails>> {
+        sqlx::query_as::<_, InvoiceDetails>("SELECT * FROM invoice_details WHERE invoice_id = $1 ORDER BY id")
+            .bind(invoice_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn create(
+        &self,
+        invoice: NewInvoice,
+        stock: &[StockUse],
+        participants: &[NewInvoiceParticipant],
+        entries: &[NewLedgerEntry],
+    ) -> Result<Invoice> {
+        let mut tx = self.pool.begin().await?;
+        let created = insert_invoice(&mut tx, &invoice, stock, participants, entries).await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    async fn soft_delete(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        deleted_by: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<Invoice>> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = sqlx::query_as::<_, Invoice>(
+            "UPDATE invoice SET is_deleted = true, deleted_by = $3, last_modification_date = $4
+             WHERE id = $1 AND last_modification_date = $2 AND is_deleted = false
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(last_modified)
+        .bind(deleted_by)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(deleted) = deleted else {
+            return Ok(None);
+        };
+
+        reverse_invoice(&mut tx, &deleted, format!("Invoice {} deleted", deleted.id)).await?;
+
+        // what the invoice drew goes back on its lots, never more than was bought of one
+        sqlx::query(
+            "UPDATE stock
+             SET remaining = LEAST(stock.quantity, stock.remaining + used.quantity),
+                 consumed = (LEAST(stock.quantity, stock.remaining + used.quantity) = 0),
+                 version = version + 1
+             FROM invoice_details used
+             WHERE used.invoice_id = $1 AND stock.id = used.stock_id",
+        )
+        .bind(deleted.id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(deleted))
+    }
+
+    async fn restore(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        now: NaiveDateTime,
+        entries: &[NewLedgerEntry],
+    ) -> Result<Option<Invoice>> {
+        let mut tx = self.pool.begin().await?;
+
+        let restored = sqlx::query_as::<_, Invoice>(
+            "UPDATE invoice SET is_deleted = false, deleted_by = 0, last_modification_date = $3
+             WHERE id = $1 AND last_modification_date = $2 AND is_deleted = true
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(last_modified)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(restored) = restored else {
+            return Ok(None);
+        };
+
+        let drawn: Vec<(i32, Decimal)> =
+            sqlx::query_as("SELECT stock_id, quantity FROM invoice_details WHERE invoice_id = $1")
+                .bind(restored.id)
+                .fetch_all(&mut *tx)
+                .await?;
+        let (stock_ids, quantities): (Vec<i32>, Vec<Decimal>) = drawn.into_iter().unzip();
+        if !draw_stock(&mut tx, &stock_ids, &quantities).await? {
+            return Err(Error::conflict(format!(
+                "Stock of invoice {} has been used since it was deleted",
+                restored.id
+            )));
+        }
+
+        let description = format!("Invoice {} restored", restored.id);
+        post_invoice(&mut tx, &restored, LedgerKind::Invoice, description, entries).await?;
+
+        tx.commit().await?;
+
+        Ok(Some(restored))
+    }
+
+    async fn update(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        changes: InvoiceChanges,
+        participants: &[NewInvoiceParticipant],
+        entries: &[NewLedgerEntry],
+    ) -> Result<Option<Invoice>> {
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query_as::<_, Invoice>(
+            "UPDATE invoice SET price = $3, supplier_id = $4, last_modification_date = $5
+             WHERE id = $1 AND last_modification_date = $2 AND is_deleted = false
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(last_modified)
+        .bind(changes.price)
+        .bind(changes.supplier_id)
+        .bind(changes.last_modification_date)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(updated) = updated else {
+            return Ok(None);
+        };
+
+        reverse_invoice(&mut tx, &updated, format!("Invoice {} edited", updated.id)).await?;
+
+        sqlx::query("DELETE FROM invoice_participant WHERE invoice_id = $1")
+            .bind(updated.id)
+            .execute(&mut *tx)
+            .await?;
+        insert_participants(&mut tx, updated.id, participants).await?;
+        post_invoice(&mut tx, &updated, LedgerKind::Invoice, format!("Invoice {}", updated.id), entries).await?;
+
+        tx.commit().await?;
+
+        Ok(Some(updated))
+    }
+}
+
+/// Inserts the invoice with its details and participants, draws the stock and posts `entries`.
+/// Fails with a conflict if any stock row has less left than is used; the caller's transaction must
+/// then be dropped.
+pub(crate) async fn insert_invoice(
+    tx: &mut Transaction<'_, Postgres>,
+    invoice: &NewInvoice,
+    stock: &[StockUse],
+    participants: &[NewInvoiceParticipant],
+    entries: &[NewLedgerEntry],
+) -> Result<Invoice> {
+    let stock_ids: Vec<i32> = stock.iter().map(|used| used.stock_id).collect();
+    let quantities: Vec<Decimal> = stock.iter().map(|used| used.quantity).collect();
+    let costs: Vec<i64> = stock.iter().map(|used| used.cost).collect();
+
+    if !draw_stock(tx, &stock_ids, &quantities).await? {
+        return Err(Error::conflict("Stock has already been consumed"));
+    }
+
+    let created = sqlx::query_as::<_, Invoice>(
+        "INSERT INTO invoice
+             (price, is_deleted, deleted_by, created_date, last_modification_date, meal_id, group_id, supplier_id,
+              order_id)
+         VALUES ($1, false, 0, $2, $2, $3, $4, $5, $6)
+         RETURNING *",
+    )
+    .bind(invoice.price)
+    .bind(invoice.created_date)
+    .bind(invoice.meal_id)
+    .bind(invoice.group_id)
+    .bind(invoice.supplier_id)
+    .bind(invoice.order_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO invoice_details (invoice_id, stock_id, quantity, cost)
+         SELECT $1, * FROM unnest($2::int[], $3::numeric[], $4::bigint[])",
+    )
+    .bind(created.id)
+    .bind(&stock_ids)
+    .bind(&quantities)
+    .bind(&costs)
+    .execute(&mut **tx)
+    .await?;
+
+    insert_participants(tx, created.id, participants).await?;
+    post_invoice(tx, &created, LedgerKind::Invoice, format!("Invoice {}", created.id), entries).await?;
+
+    Ok(created)
+}
+
+/// Takes `quantities` off the lots `stock_ids`; false if any of them has less left, and the caller's
+/// transaction must then be dropped.
+async fn draw_stock(tx: &mut Transaction<'_, Postgres>, stock_ids: &[i32], quantities: &[Decimal]) -> Result<bool> {
+    // the row locks taken here also keep a concurrent invoice from drawing the same stock
+    let drawn = sqlx::query(
+        "UPDATE stock
+         SET remaining = remaining - used.quantity, consumed = (remaining = used.quantity), version = version + 1
+         FROM unnest($1::int[], $2::numeric[]) AS used (id, quantity)
+         WHERE stock.id = used.id AND stock.remaining >= used.quantity",
+    )
+    .bind(stock_ids)
+    .bind(quantities)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    Ok(drawn == stock_ids.len() as u64)
+}
+
+async fn insert_participants(
+    tx: &mut Transaction<'_, Postgres>,
+    invoice_id: i32,
+    participants: &[NewInvoiceParticipant],
+) -> Result<()> {
+    let user_ids: Vec<i32> = participants.iter().map(|participant| participant.user_id).collect();
+    let amounts: Vec<i64> = participants.iter().map(|participant| participant.amount).collect();
+    sqlx::query(
+        "INSERT INTO invoice_participant (invoice_id, user_id, amount)
+         SELECT $1, * FROM unnest($2::int[], $3::bigint[])",
+    )
+    .bind(invoice_id)
+    .bind(user_ids)
+    .bind(amounts)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Posts `entries` against the invoice, dated at its last modification; nothing if there are none.
+async fn post_invoice(
+    tx: &mut Transaction<'_, Postgres>,
+    invoice: &Invoice,
+    kind: LedgerKind,
+    description: String,
+    entries: &[NewLedgerEntry],
+) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let posting = NewLedgerTransaction {
+        kind,
+        description,
+        group_id: invoice.group_id,
+        invoice_id: Some(invoice.id),
+        payment_id: None,
+        created_date: invoice.last_modification_date,
+        entries: entries.to_vec(),
+    };
+    post_transaction(tx, &posting).await?;
+
+    Ok(())
+}
+
+/// Cancels the invoice's net effect so far: whatever its postings add up to per account, negated.
+async fn reverse_invoice(tx: &mut Transaction<'_, Postgres>, invoice: &Invoice, description: String) -> Result<()> {
+    let entries = sqlx::query_as::<_, NewLedgerEntry>(
+        "SELECT e.user_id, (-sum(e.amount))::bigint AS amount, e.supplier_id, e.customer_id
+         FROM ledger_entry e
+         INNER JOIN ledger_transaction t ON t.id = e.transaction_id
+         WHERE t.invoice_id = $1
+         GROUP BY e.user_id, e.supplier_id, e.customer_id
+         HAVING sum(e.amount) <> 0
+         ORDER BY e.user_id, e.supplier_id, e.customer_id",
+    )
+    .bind(invoice.id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    post_invoice(tx, invoice, LedgerKind::Reversal, description, &entries).await
+}
@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    application::error::{Error, Result},
+    domain::ledger::{
+        ledger_entity::{BalanceDrift, LedgerAccount, LedgerTransaction, NewLedgerTransaction, UserLedgerEntry},
+        ledger_repository::LedgerRepository,
+    },
+};
+
+pub struct PgLedgerRepository {
+    pool: PgPool,
+}
+
+impl PgLedgerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LedgerRepository for PgLedgerRepository {
+    async fn create(&self, transaction: NewLedgerTransaction) -> Result<LedgerTransaction> {
+        let mut tx = self.pool.begin().await?;
+        let created = post_transaction(&mut tx, &transaction).await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    async fn find_user_entries(&self, group_id: i32, user_id: i32) -> Result<Vec<UserLedgerEntry>> {
+        sqlx::query_as::<_, UserLedgerEntry>(
+            "SELECT t.id AS transaction_id, t.kind, t.description, t.invoice_id, t.payment_id, t.created_date, e.amount
+             FROM ledger_entry e
+             JOIN ledger_transaction t ON t.id = e.transaction_id
+             WHERE t.group_id = $1 AND e.user_id = $2
+             ORDER BY t.created_date, t.id, e.id",
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_drift(&self, group_id: i32) -> Result<Vec<BalanceDrift>> {
+        sqlx::query_as::<_, BalanceDrift>(DRIFT)
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn rebuild_balances(&self, group_id: i32) -> Result<Vec<BalanceDrift>> {
+        let mut tx = self.pool.begin().await?;
+
+        // postings move these rows' balances, so holding them keeps the sums still while we compare;
+        // taken in the order postings take them
+        for lock in [
+            "SELECT 1 FROM \"user\" u WHERE u.group_id = $1 FOR UPDATE",
+            "SELECT 1 FROM supplier s JOIN \"user\" u ON u.id = s.user_id WHERE u.group_id = $1 FOR UPDATE OF s",
+            "SELECT 1 FROM customer c JOIN \"user\" u ON u.id = c.user_id WHERE u.group_id = $1 FOR UPDATE OF c",
+        ] {
+            sqlx::query(lock).bind(group_id).execute(&mut *tx).await?;
+        }
+
+        let drifted = sqlx::query_as::<_, BalanceDrift>(DRIFT)
+            .bind(group_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        for (account, table) in [
+            (LedgerAccount::User, "\"user\""),
+            (LedgerAccount::Supplier, "supplier"),
+            (LedgerAccount::Customer, "customer"),
+        ] {
+            let (ids, balances): (Vec<i32>, Vec<i64>) = drifted
+                .iter()
+                .filter(|drift| drift.account == account)
+                .map(|drift| (drift.id, drift.ledger_balance))
+                .unzip();
+            if ids.is_empty() {
+                continue;
+            }
+            sqlx::query(&format!(
+                "UPDATE {table} a SET balance = t.balance
+                 FROM unnest($1::int[], $2::bigint[]) AS t(id, balance)
+                 WHERE a.id = t.id"
+            ))
+            .bind(&ids)
+            .bind(&balances)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(drifted)
+    }
+}
+
+/// Every account of the group `$1` whose cached balance differs from its postings in that group.
+const DRIFT: &str = "
+    WITH posted AS (
+        SELECT e.user_id, e.supplier_id, e.customer_id, e.amount
+        FROM ledger_entry e
+        JOIN ledger_transaction t ON t.id = e.transaction_id
+        WHERE t.group_id = $1
+    ),
+    account AS (
+        SELECT 1 AS rank, 'user'::varchar AS account, u.id, u.balance,
+               coalesce((SELECT sum(p.amount) FROM posted p WHERE p.user_id = u.id), 0)::bigint AS ledger_balance
+        FROM \"user\" u
+        WHERE u.group_id = $1
+        UNION ALL
+        SELECT 2, 'supplier'::varchar, s.id, s.balance,
+               coalesce((SELECT sum(p.amount) FROM posted p WHERE p.supplier_id = s.id), 0)::bigint
+        FROM supplier s
+        JOIN \"user\" u ON u.id = s.user_id
+        WHERE u.group_id = $1
+        UNION ALL
+        SELECT 3, 'customer'::varchar, c.id, c.balance,
+               coalesce((SELECT sum(p.amount) FROM posted p WHERE p.customer_id = c.id), 0)::bigint
+        FROM customer c
+        JOIN \"user\" u ON u.id = c.user_id
+        WHERE u.group_id = $1
+    )
+    SELECT account, id, balance, ledger_balance
+    FROM account
+    WHERE balance <> ledger_balance
+    ORDER BY rank, id";
+
+/// Writes the transaction and its entries inside `tx` and moves the cached `balance` of the users,
+/// suppliers and customers they post to, so callers can book it atomically with whatever it records.
+pub(crate) async fn post_transaction(
+    tx: &mut Transaction<'_, Postgres>,
+    transaction: &NewLedgerTransaction,
+) -> Result<LedgerTransaction> {
+    if !transaction.is_balanced() {
+        return Err(Error::internal("Ledger transaction does not balance"));
+    }
+
+    let created = sqlx::query_as::<_, LedgerTransaction>(
+        "INSERT INTO ledger_transaction (kind, description, group_id, invoice_id, payment_id, created_date)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING *",
+    )
+    .bind(transaction.kind)
+    .bind(&transaction.description)
+    .bind(transaction.group_id)
+    .bind(transaction.invoice_id)
+    .bind(transaction.payment_id)
+    .bind(transaction.created_date)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let user_ids: Vec<Option<i32>> = transaction.entries.iter().map(|entry| entry.user_id).collect();
+    let amounts: Vec<i64> = transaction.entries.iter().map(|entry| entry.amount).collect();
+    let supplier_ids: Vec<Option<i32>> = transaction.entries.iter().map(|entry| entry.supplier_id).collect();
+    let customer_ids: Vec<Option<i32>> = transaction.entries.iter().map(|entry| entry.customer_id).collect();
+
+    sqlx::query(
+        "INSERT INTO ledger_entry (transaction_id, user_id, amount, supplier_id, customer_id)
+         SELECT $1, * FROM unnest($2::int[], $3::bigint[], $4::int[], $5::int[])",
+    )
+    .bind(created.id)
+    .bind(&user_ids)
+    .bind(&amounts)
+    .bind(&supplier_ids)
+    .bind(&customer_ids)
+    .execute(&mut **tx)
+    .await?;
+
+    for (table, ids) in [
+        ("\"user\"", &user_ids),
+        ("supplier", &supplier_ids),
+        ("customer", &customer_ids),
+    ] {
+        if ids.iter().all(Option::is_none) {
+            continue;
+        }
+        sqlx::query(&format!(
+            "UPDATE {table} a SET balance = a.balance + c.amount
+             FROM (SELECT id, sum(amount) AS amount FROM unnest($1::int[], $2::bigint[]) AS t(id, amount)
+                   WHERE id IS NOT NULL
+                   GROUP BY id) c
+             WHERE a.id = c.id"
+        ))
+        .bind(ids)
+        .bind(&amounts)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(created)
+}
@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        meal::{
+            meal_entity::{Meal, NewMeal},
+            meal_repository::MealRepository,
+        },
+        product::product_entity::Product,
+        version::stale,
+    },
+};
+
+pub struct PgMealRepository {
+    pool: PgPool,
+}
+
+impl PgMealRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MealRepository for PgMealRepository {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Meal>> {
+        sqlx::query_as::<_, Meal>("SELECT * FROM meal WHERE id = $1 AND group_id = $2")
+            .bind(id)
+            .bind(group_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Meal>> {
+        sqlx::query_as::<_, Meal>("SELECT * FROM meal WHERE group_id = $1 ORDER BY name, id")
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_products(&self, meal_id: i32) -> Result<Vec<Product>> {
+        sqlx::query_as::<_, Product>(
+            "SELECT p.* FROM meal_product mp
+             JOIN product p ON p.id = mp.product_id
+             WHERE mp.meal_id = $1
+             ORDER BY mp.position",
+        )
+        .bind(meal_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn create(&self, meal: NewMeal, product_ids: &[i32]) -> Result<Meal> {
+        let mut tx = self.pool.begin().await?;
+
+        let meal = sqlx::query_as::<_, Meal>(
+            "INSERT INTO meal (name, description, group_id) VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(meal.name)
+        .bind(meal.description)
+        .bind(meal.group_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO meal_product (meal_id, product_id, position)
+             SELECT $1, product_id, (ordinality - 1)::int FROM unnest($2::int[]) WITH ORDINALITY AS t(product_id, ordinality)",
+        )
+        .bind(meal.id)
+        .bind(product_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_meal_product_conflict)?;
+
+        tx.commit().await?;
+
+        Ok(meal)
+    }
+
+    async fn add_product(&self, meal_id: i32, version: i32, product_id: i32, position: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        // the row lock also serializes concurrent edits of the same meal so positions stay contiguous
+        bump_version(&mut tx, meal_id, version).await?;
+
+        sqlx::query("UPDATE meal_product SET position = position + 1 WHERE meal_id = $1 AND position >= $2")
+            .bind(meal_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO meal_product (meal_id, product_id, position) VALUES ($1, $2, $3)")
+            .bind(meal_id)
+            .bind(product_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_meal_product_conflict)?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_product(&self, meal_id: i32, version: i32, product_id: i32) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        bump_version(&mut tx, meal_id, version).await?;
+
+        let position: Option<i32> =
+            sqlx::query_scalar("DELETE FROM meal_product WHERE meal_id = $1 AND product_id = $2 RETURNING position")
+                .bind(meal_id)
+                .bind(product_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        let Some(position) = position else {
+            return Ok(false);
+        };
+
+        sqlx::query("UPDATE meal_product SET position = position - 1 WHERE meal_id = $1 AND position > $2")
+            .bind(meal_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+}
+
+fn map_meal_product_conflict(e: sqlx::Error) -> Error {
+    match e.as_database_error().and_then(|db_error| db_error.constraint()) {
+        Some("meal_product_pkey") => Error::conflict("Product is already part of the meal"),
+        _ => e.into(),
+    }
+}
+
+async fn bump_version(tx: &mut Transaction<'_, Postgres>, meal_id: i32, version: i32) -> Result<()> {
+    let bumped = sqlx::query("UPDATE meal SET version = version + 1 WHERE id = $1 AND version = $2")
+        .bind(meal_id)
+        .bind(version)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+
+    if bumped == 0 {
+        return Err(stale("Meal"));
+    }
+    Ok(())
+}
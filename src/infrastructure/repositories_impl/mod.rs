@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use actix_web::web;
+use sqlx::PgPool;
+
+use crate::domain::{
+    active_session::active_session_repository::ActiveSessionRepository, customer::customer_repository::CustomerRepository,
+    group::group_repository::GroupRepository, invoice::invoice_repository::InvoiceRepository,
+    ledger::ledger_repository::LedgerRepository,
+    meal::meal_repository::MealRepository, order::order_repository::OrderRepository,
+    payment::payment_repository::PaymentRepository,
+    product::product_repository::ProductRepository, role::role_repository::RoleRepository,
+    stock::stock_repository::StockRepository, supplier::supplier_repository::SupplierRepository,
+    system_log::system_log_repository::SystemLogRepository, user::user_repository::UserRepository,
+};
+
+pub mod active_session_repository_impl;
+pub mod customer_repository_impl;
+pub mod group_repository_impl;
+pub mod invoice_repository_impl;
+pub mod ledger_repository_impl;
+pub mod meal_repository_impl;
+pub mod order_repository_impl;
+pub mod payment_repository_impl;
+pub mod product_repository_impl;
+pub mod role_repository_impl;
+pub mod stock_repository_impl;
+pub mod supplier_repository_impl;
+pub mod system_log_repository_impl;
+pub mod user_repository_impl;
+
+use active_session_repository_impl::PgActiveSessionRepository;
+use customer_repository_impl::PgCustomerRepository;
+use group_repository_impl::PgGroupRepository;
+use invoice_repository_impl::PgInvoiceRepository;
+use ledger_repository_impl::PgLedgerRepository;
+use meal_repository_impl::PgMealRepository;
+use order_repository_impl::PgOrderRepository;
+use payment_repository_impl::PgPaymentRepository;
+use product_repository_impl::PgProductRepository;
+use role_repository_impl::PgRoleRepository;
+use stock_repository_impl::PgStockRepository;
+use supplier_repository_impl::PgSupplierRepository;
+use system_log_repository_impl::PgSystemLogRepository;
+use user_repository_impl::PgUserRepository;
+
+/// Registers the Postgres implementation of every repository trait as `web::Data<dyn XRepository>`,
+/// which is what controllers and extractors look up.
+pub fn register_repositories(cfg: &mut web::ServiceConfig, pool: &PgPool) {
+    let users: Arc<dyn UserRepository> = Arc::new(PgUserRepository::new(pool.clone()));
+    let sessions: Arc<dyn ActiveSessionRepository> = Arc::new(PgActiveSessionRepository::new(pool.clone()));
+    let roles: Arc<dyn RoleRepository> = Arc::new(PgRoleRepository::new(pool.clone()));
+    let groups: Arc<dyn GroupRepository> = Arc::new(PgGroupRepository::new(pool.clone()));
+    let customers: Arc<dyn CustomerRepository> = Arc::new(PgCustomerRepository::new(pool.clone()));
+    let suppliers: Arc<dyn SupplierRepository> = Arc::new(PgSupplierRepository::new(pool.clone()));
+    let products: Arc<dyn ProductRepository> = Arc::new(PgProductRepository::new(pool.clone()));
+    let stocks: Arc<dyn StockRepository> = Arc::new(PgStockRepository::new(pool.clone()));
+    let meals: Arc<dyn MealRepository> = Arc::new(PgMealRepository::new(pool.clone()));
+    let invoices: Arc<dyn InvoiceRepository> = Arc::new(PgInvoiceRepository::new(pool.clone()));
+    let ledger: Arc<dyn LedgerRepository> = Arc::new(PgLedgerRepository::new(pool.clone()));
+    let orders: Arc<dyn OrderRepository> = Arc::new(PgOrderRepository::new(pool.clone()));
+    let payments: Arc<dyn PaymentRepository> = Arc::new(PgPaymentRepository::new(pool.clone()));
+    let system_logs: Arc<dyn SystemLogRepository> = Arc::new(PgSystemLogRepository::new(pool.clone()));
+
+    cfg.app_data(web::Data::from(users))
+        .app_data(web::Data::from(sessions))
+        .app_data(web::Data::from(roles))
+        .app_data(web::Data::from(groups))
+        .app_data(web::Data::from(customers))
+        .app_data(web::Data::from(suppliers))
+        .app_data(web::Data::from(products))
+        .app_data(web::Data::from(stocks))
+        .app_data(web::Data::from(meals))
+        .app_data(web::Data::from(invoices))
+        .app_data(web::Data::from(ledger))
+        .app_data(web::Data::from(orders))
+        .app_data(web::Data::from(payments))
+        .app_data(web::Data::from(system_logs));
+}
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        invoice::invoice_entity::{Invoice, InvoiceDraft},
+        order::{
+            order_entity::{NewOrder, Order},
+            order_repository::OrderRepository,
+            order_status::OrderStatus,
+        },
+        order_details::order_details_entity::OrderDetails,
+        system_log::system_log_entity::NewSystemLog,
+    },
+    infrastructure::repositories_impl::{
+        invoice_repository_impl::insert_invoice, system_log_repository_impl::insert_system_log,
+    },
+};
+
+pub struct PgOrderRepository {
+    pool: PgPool,
+}
+
+impl PgOrderRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OrderRepository for PgOrderRepository {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Order>> {
+        sqlx::query_as::<_, Order>("SELECT * FROM \"order\" WHERE id = $1 AND group_id = $2")
+            .bind(id)
+            .bind(group_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Order>> {
+        sqlx::query_as::<_, Order>(
+            "SELECT * FROM \"order\" WHERE group_id = $1 AND is_deleted = false ORDER BY created_date DESC, id DESC",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_lines(&self, order_id: i32) -> Result<Vec<OrderDetails>> {
+        sqlx::query_as::<_, OrderDetails>("SELECT * FROM order_details WHERE order_id = $1 ORDER BY id")
+            .bind(order_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_expired_rounds(&self, now: NaiveDateTime) -> Result<Vec<Order>> {
+        sqlx::query_as::<_, Order>(
+            "SELECT * FROM \"order\"
+             WHERE status = 'open' AND cutoff <= $1 AND is_deleted = false
+             ORDER BY cutoff, id",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn create(&self, order: NewOrder) -> Result<Order> {
+        insert_order(&self.pool, &order, OrderStatus::Draft).await
+    }
+
+    async fn open_round(&self, order: NewOrder, log: NewSystemLog) -> Result<Order> {
+        let mut tx = self.pool.begin().await?;
+
+        let order = insert_order(&mut *tx, &order, OrderStatus::Open).await?;
+        insert_system_log(&mut *tx, log).await?;
+
+        tx.commit().await?;
+
+        Ok(order)
+    }
+
+    async fn add_line(
+        &self,
+        id: i32,
+        user_id: i32,
+        stock_id: i32,
+        quantity: Option<Decimal>,
+        now: NaiveDateTime,
+    ) -> Result<Option<OrderDetails>> {
+        let mut tx = self.pool.begin().await?;
+
+        // bumping the version locks the order row, so the cut-off lock and other members wait for us
+        if !touch_open_order(&mut tx, id, now).await? {
+            return Ok(None);
+        }
+
+        let existing: Option<i32> = sqlx::query_scalar("SELECT id FROM customer WHERE user_id = $1 ORDER BY id LIMIT 1")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let customer_id = match existing {
+            Some(customer_id) => customer_id,
+            None => {
+                sqlx::query_scalar("INSERT INTO customer (user_id, balance) VALUES ($1, 0) RETURNING id")
+                    .bind(user_id)
+                    .fetch_one(&mut *tx)
+                    .await?
+            }
+        };
+
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM order_details WHERE order_id = $1 AND stock_id = $2 AND customer_id = $3)",
+        )
+        .bind(id)
+        .bind(stock_id)
+        .bind(customer_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken {
+            return Err(Error::conflict(format!("Stock {} is already on your part of this order", stock_id)));
+        }
+
+        sqlx::query("INSERT INTO order_customer (order_id, customer_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(id)
+            .bind(customer_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let line = sqlx::query_as::<_, OrderDetails>(
+            "INSERT INTO order_details (order_id, stock_id, customer_id, quantity) VALUES ($1, $2, $3, $4) RETURNING *",
+        )
+        .bind(id)
+        .bind(stock_id)
+        .bind(customer_id)
+        .bind(quantity)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(line))
+    }
+
+    async fn remove_line(&self, id: i32, line_id: i32, now: NaiveDateTime) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        if !touch_open_order(&mut tx, id, now).await? {
+            return Ok(false);
+        }
+
+        let customer_id: Option<Option<i32>> =
+            sqlx::query_scalar("DELETE FROM order_details WHERE id = $1 AND order_id = $2 RETURNING customer_id")
+                .bind(line_id)
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        if let Some(Some(customer_id)) = customer_id {
+            sqlx::query(
+                "DELETE FROM order_customer
+                 WHERE order_id = $1 AND customer_id = $2
+                   AND NOT EXISTS (SELECT 1 FROM order_details WHERE order_id = $1 AND customer_id = $2)",
+            )
+            .bind(id)
+            .bind(customer_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn transition(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        from: OrderStatus,
+        to: OrderStatus,
+        now: NaiveDateTime,
+        log: NewSystemLog,
+    ) -> Result<Option<Order>> {
+        let mut tx = self.pool.begin().await?;
+
+        let order = sqlx::query_as::<_, Order>(
+            "UPDATE \"order\" SET status = $4, last_modification_date = $5
+             WHERE id = $1 AND last_modification_date = $2 AND status = $3
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(last_modified)
+        .bind(from)
+        .bind(to)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if order.is_some() {
+            insert_system_log(&mut *tx, log).await?;
+            tx.commit().await?;
+        }
+
+        Ok(order)
+    }
+
+    async fn invoice(
+        &self,
+        id: i32,
+        last_modified: NaiveDateTime,
+        invoices: &[InvoiceDraft],
+        now: NaiveDateTime,
+        log: NewSystemLog,
+    ) -> Result<Option<(Order, Vec<Invoice>)>> {
+        let mut tx = self.pool.begin().await?;
+
+        // claiming the order first keeps a concurrent conversion from billing it again
+        let order = sqlx::query_as::<_, Order>(
+            "UPDATE \"order\" SET status = 'invoiced', last_modification_date = $3
+             WHERE id = $1 AND last_modification_date = $2 AND status = 'fulfilled'
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(last_modified)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(order) = order else {
+            return Ok(None);
+        };
+
+        let mut created = Vec::with_capacity(invoices.len());
+        for invoice in invoices {
+            created.push(
+                insert_invoice(&mut tx, &invoice.invoice, &invoice.stock, &invoice.participants, &invoice.entries)
+                    .await?,
+            );
+        }
+        insert_system_log(&mut *tx, log).await?;
+
+        tx.commit().await?;
+
+        Ok(Some((order, created)))
+    }
+}
+
+async fn insert_order<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    order: &NewOrder,
+    status: OrderStatus,
+) -> Result<Order> {
+    sqlx::query_as::<_, Order>(
+        "INSERT INTO \"order\"
+             (is_deleted, deleted_by, created_date, last_modification_date, group_id, status,
+              created_by, round_date, cutoff)
+         VALUES (false, 0, $1, $1, $2, $3, $4, $5, $6)
+         RETURNING *",
+    )
+    .bind(order.created_date)
+    .bind(order.group_id)
+    .bind(status)
+    .bind(order.created_by)
+    .bind(order.round_date)
+    .bind(order.cutoff)
+    .fetch_one(executor)
+    .await
+    .map_err(Error::from)
+}
+
+/// Bumps the order's version if it still takes items at `now`, holding its row lock until `tx` ends.
+async fn touch_open_order(tx: &mut Transaction<'_, Postgres>, id: i32, now: NaiveDateTime) -> Result<bool> {
+    let touched = sqlx::query(
+        "UPDATE \"order\" SET last_modification_date = $2
+         WHERE id = $1 AND status = 'open' AND (cutoff IS NULL OR cutoff > $2)",
+    )
+    .bind(id)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(touched.rows_affected() == 1)
+}
@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        ledger::ledger_entity::{LedgerTransaction, NewLedgerTransaction},
+        payment::{
+            payment_entity::{NewPayment, Payment},
+            payment_repository::PaymentRepository,
+        },
+        system_log::system_log_entity::NewSystemLog,
+    },
+    infrastructure::repositories_impl::{
+        ledger_repository_impl::post_transaction, system_log_repository_impl::insert_system_log,
+    },
+};
+
+pub struct PgPaymentRepository {
+    pool: PgPool,
+}
+
+impl PgPaymentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PaymentRepository for PgPaymentRepository {
+    async fn find_by_id(&self, group_id: i32, id: i32) -> Result<Option<Payment>> {
+        sqlx::query_as::<_, Payment>("SELECT * FROM payment WHERE id = $1 AND group_id = $2")
+            .bind(id)
+            .bind(group_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Payment>> {
+        sqlx::query_as::<_, Payment>("SELECT * FROM payment WHERE group_id = $1 ORDER BY created_date DESC, id DESC")
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn create(&self, payment: NewPayment) -> Result<Payment> {
+        sqlx::query_as::<_, Payment>(
+            "INSERT INTO payment (payer_id, payee_id, group_id, amount, method, note, status, created_date)
+             VALUES ($1, $2, $3, $4, $5, $6, 'pending', $7)
+             RETURNING *",
+        )
+        .bind(payment.payer_id)
+        .bind(payment.payee_id)
+        .bind(payment.group_id)
+        .bind(payment.amount)
+        .bind(payment.method)
+        .bind(payment.note)
+        .bind(payment.created_date)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn create_confirmed(
+        &self,
+        payments: Vec<(NewPayment, NewLedgerTransaction)>,
+        now: NaiveDateTime,
+    ) -> Result<Vec<LedgerTransaction>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut created = Vec::with_capacity(payments.len());
+        for (payment, mut transaction) in payments {
+            let payment_id: i32 = sqlx::query_scalar(
+                "INSERT INTO payment
+                     (payer_id, payee_id, group_id, amount, method, note, status, created_date, resolved_date)
+                 VALUES ($1, $2, $3, $4, $5, $6, 'confirmed', $7, $8)
+                 RETURNING id",
+            )
+            .bind(payment.payer_id)
+            .bind(payment.payee_id)
+            .bind(payment.group_id)
+            .bind(payment.amount)
+            .bind(payment.method)
+            .bind(payment.note)
+            .bind(payment.created_date)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            transaction.payment_id = Some(payment_id);
+            created.push(post_transaction(&mut tx, &transaction).await?);
+        }
+
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    async fn confirm(
+        &self,
+        id: i32,
+        now: NaiveDateTime,
+        transaction: NewLedgerTransaction,
+        log: NewSystemLog,
+    ) -> Result<Option<Payment>> {
+        let mut tx = self.pool.begin().await?;
+
+        let payment = sqlx::query_as::<_, Payment>(
+            "UPDATE payment SET status = 'confirmed', resolved_date = $2
+             WHERE id = $1 AND status = 'pending'
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(payment) = payment else {
+            return Ok(None);
+        };
+
+        post_transaction(&mut tx, &transaction).await?;
+        insert_system_log(&mut *tx, log).await?;
+
+        tx.commit().await?;
+
+        Ok(Some(payment))
+    }
+
+    async fn reject(&self, id: i32, now: NaiveDateTime, log: NewSystemLog) -> Result<Option<Payment>> {
+        let mut tx = self.pool.begin().await?;
+
+        let payment = sqlx::query_as::<_, Payment>(
+            "UPDATE payment SET status = 'rejected', resolved_date = $2
+             WHERE id = $1 AND status = 'pending'
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if payment.is_some() {
+            insert_system_log(&mut *tx, log).await?;
+            tx.commit().await?;
+        }
+
+        Ok(payment)
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::product::{product_entity::Product, product_repository::ProductRepository},
+};
+
+pub struct PgProductRepository {
+    pool: PgPool,
+}
+
+impl PgProductRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ProductRepository for PgProductRepository {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Product>> {
+        sqlx::query_as::<_, Product>("SELECT * FROM product WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Product>> {
+        sqlx::query_as::<_, Product>("SELECT * FROM product WHERE group_id = $1 ORDER BY name, id")
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn create(&self, name: &str, group_id: i32) -> Result<Product> {
+        sqlx::query_as::<_, Product>("INSERT INTO product (name, group_id) VALUES ($1, $2) RETURNING *")
+            .bind(name)
+            .bind(group_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+}
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::role::{permission::Permission, role_entity::Role, role_repository::RoleRepository},
+};
+
+pub struct PgRoleRepository {
+    pool: PgPool,
+}
+
+impl PgRoleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RoleRepository for PgRoleRepository {
+    async fn find_all(&self) -> Result<Vec<Role>> {
+        sqlx::query_as::<_, Role>("SELECT * FROM role ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Role>> {
+        sqlx::query_as::<_, Role>("SELECT * FROM role WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Role>> {
+        sqlx::query_as::<_, Role>("SELECT * FROM role WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_user_permissions(&self, user_id: i32) -> Result<Vec<Permission>> {
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT rp.permission FROM role_permission rp
+             JOIN \"user\" u ON u.role_id = rp.role_id
+             WHERE u.id = $1
+             ORDER BY rp.permission",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names.iter().filter_map(|name| Permission::parse(name)).collect())
+    }
+
+    async fn user_has_permission(&self, user_id: i32, permission: Permission) -> Result<bool> {
+        sqlx::query_scalar(
+            "SELECT EXISTS (
+                SELECT 1 FROM role_permission rp
+                JOIN \"user\" u ON u.role_id = rp.role_id
+                WHERE u.id = $1 AND rp.permission = $2
+            )",
+        )
+        .bind(user_id)
+        .bind(permission.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+}
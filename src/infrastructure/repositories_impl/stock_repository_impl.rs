@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        stock::{
+            stock_entity::{NewStock, Stock, StockAdjustment, StockCount},
+            stock_repository::StockRepository,
+        },
+        version::stale,
+    },
+};
+
+pub struct PgStockRepository {
+    pool: PgPool,
+}
+
+impl PgStockRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl StockRepository for PgStockRepository {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Stock>> {
+        sqlx::query_as::<_, Stock>("SELECT * FROM stock WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_in_group(&self, group_id: i32, id: i32) -> Result<Option<Stock>> {
+        sqlx::query_as::<_, Stock>(
+            "SELECT s.* FROM stock s
+             JOIN product p ON p.id = s.product_id
+             WHERE s.id = $1 AND p.group_id = $2",
+        )
+        .bind(id)
+        .bind(group_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_by_product(&self, product_id: i32) -> Result<Vec<Stock>> {
+        sqlx::query_as::<_, Stock>("SELECT * FROM stock WHERE product_id = $1 ORDER BY id")
+            .bind(product_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_available_by_group(&self, group_id: i32) -> Result<Vec<Stock>> {
+        sqlx::query_as::<_, Stock>(
+            "SELECT s.* FROM stock s
+             JOIN product p ON p.id = s.product_id
+             WHERE p.group_id = $1 AND s.consumed = false
+             ORDER BY s.id",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_adjustments(&self, stock_id: i32) -> Result<Vec<StockAdjustment>> {
+        sqlx::query_as::<_, StockAdjustment>("SELECT * FROM stock_adjustment WHERE stock_id = $1 ORDER BY id")
+            .bind(stock_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn create(&self, stock: NewStock) -> Result<Stock> {
+        sqlx::query_as::<_, Stock>(
+            "INSERT INTO stock (price, consumed, product_id, supplier_id, unit, quantity, remaining, purchased_date)
+             VALUES ($1, false, $2, $3, $4, $5, $5, $6)
+             RETURNING *",
+        )
+        .bind(stock.price)
+        .bind(stock.product_id)
+        .bind(stock.supplier_id)
+        .bind(stock.unit)
+        .bind(stock.quantity)
+        .bind(stock.purchased_date)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn adjust(&self, id: i32, version: i32, count: StockCount) -> Result<Option<(Stock, StockAdjustment)>> {
+        let mut tx = self.pool.begin().await?;
+
+        let before: Option<(Decimal, i32)> =
+            sqlx::query_as("SELECT remaining, version FROM stock WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((before, current)) = before else {
+            return Ok(None);
+        };
+        if current != version {
+            return Err(stale("Stock"));
+        }
+
+        let stock = sqlx::query_as::<_, Stock>(
+            "UPDATE stock SET remaining = $2, consumed = ($2 = 0), version = version + 1 WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(count.remaining)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let adjustment = sqlx::query_as::<_, StockAdjustment>(
+            "INSERT INTO stock_adjustment (stock_id, change, reason, user_id, created_date)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(count.remaining - before)
+        .bind(&count.reason)
+        .bind(count.user_id)
+        .bind(count.created_date)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some((stock, adjustment)))
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::supplier::{supplier_entity::Supplier, supplier_repository::SupplierRepository},
+};
+
+pub struct PgSupplierRepository {
+    pool: PgPool,
+}
+
+impl PgSupplierRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SupplierRepository for PgSupplierRepository {
+    async fn find_by_id(&self, id: i32) -> Result<Option<Supplier>> {
+        sqlx::query_as::<_, Supplier>("SELECT * FROM supplier WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_user(&self, user_id: i32) -> Result<Option<Supplier>> {
+        sqlx::query_as::<_, Supplier>("SELECT * FROM supplier WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<Supplier>> {
+        sqlx::query_as::<_, Supplier>(
+            "SELECT s.* FROM supplier s
+             JOIN \"user\" u ON u.id = s.user_id
+             WHERE u.group_id = $1
+             ORDER BY s.id",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+}
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::system_log::{
+        system_log_entity::{NewSystemLog, SystemLog},
+        system_log_repository::SystemLogRepository,
+    },
+};
+
+pub struct PgSystemLogRepository {
+    pool: PgPool,
+}
+
+impl PgSystemLogRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SystemLogRepository for PgSystemLogRepository {
+    async fn create(&self, entry: NewSystemLog) -> Result<SystemLog> {
+        insert_system_log(&self.pool, entry).await
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<SystemLog>> {
+        sqlx::query_as::<_, SystemLog>("SELECT * FROM system_log WHERE group_id = $1 ORDER BY date DESC, id DESC")
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+}
+
+/// Inserts a log entry on `executor`, so it can be written in the same transaction as what it logs.
+pub(crate) async fn insert_system_log<'e, E: sqlx::PgExecutor<'e>>(executor: E, entry: NewSystemLog) -> Result<SystemLog> {
+    sqlx::query_as::<_, SystemLog>(
+        "INSERT INTO system_log (transaction_type, description, date, user_id, group_id)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING *",
+    )
+    .bind(entry.transaction_type)
+    .bind(entry.description)
+    .bind(Utc::now().naive_utc())
+    .bind(entry.user_id)
+    .bind(entry.group_id)
+    .fetch_one(executor)
+    .await
+    .map_err(Error::from)
+}
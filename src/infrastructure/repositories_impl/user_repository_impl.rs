@@ -0,0 +1,323 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+
+use crate::{
+    application::error::{Error, Result},
+    domain::{
+        user::{
+            email_confirmation_entity::EmailConfirmationToken,
+            password_reset_entity::PasswordResetToken,
+            user_entity::{NewUser, User},
+            user_repository::UserRepository,
+        },
+        version::stale,
+    },
+};
+
+pub struct PgUserRepository {
+    pool: PgPool,
+}
+
+impl PgUserRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepository {
+    async fn find_by_id(&self, id: i32) -> Result<Option<User>> {
+        sqlx::query_as::<_, User>("SELECT * FROM \"user\" WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_active_by_email(&self, email: &str) -> Result<Option<User>> {
+        sqlx::query_as::<_, User>("SELECT * FROM \"user\" WHERE email = $1 AND is_active = true")
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        sqlx::query_as::<_, User>("SELECT * FROM \"user\" WHERE email = $1")
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_display_id(&self, user_display_id: &str) -> Result<Option<User>> {
+        sqlx::query_as::<_, User>("SELECT * FROM \"user\" WHERE user_display_id = $1")
+            .bind(user_display_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn find_by_group(&self, group_id: i32) -> Result<Vec<User>> {
+        sqlx::query_as::<_, User>("SELECT * FROM \"user\" WHERE group_id = $1 ORDER BY name, id")
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn search_by_name(&self, group_id: i32, name: &str) -> Result<Vec<User>> {
+        // strpos rather than LIKE so `%` and `_` in the search are matched literally
+        sqlx::query_as::<_, User>(
+            "SELECT * FROM \"user\"
+             WHERE group_id = $1 AND strpos(lower(name), lower($2)) > 0
+             ORDER BY name, id",
+        )
+        .bind(group_id)
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn find_active_by_group(&self, group_id: i32) -> Result<Vec<User>> {
+        sqlx::query_as::<_, User>(
+            "SELECT * FROM \"user\"
+             WHERE group_id = $1 AND is_active = true
+             ORDER BY balance ASC, id ASC",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Error::from)
+    }
+
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<()> {
+        sqlx::query("UPDATE \"user\" SET password = $1 WHERE id = $2")
+            .bind(password_hash)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn create(&self, user: NewUser) -> Result<User> {
+        insert_user(&self.pool, user).await
+    }
+
+    async fn update(&self, user: User) -> Result<User> {
+        sqlx::query_as::<_, User>(
+            "UPDATE \"user\"
+             SET name = $1, password = $2, email = $3, email_confirmed = $4, user_display_id = $5,
+                 is_active = $6, role_id = $7, group_id = $8, version = version + 1
+             WHERE id = $9 AND version = $10
+             RETURNING *",
+        )
+        .bind(&user.name)
+        .bind(&user.password)
+        .bind(&user.email)
+        .bind(user.email_confirmed)
+        .bind(&user.user_display_id)
+        .bind(user.is_active)
+        .bind(user.role_id)
+        .bind(user.group_id)
+        .bind(user.id)
+        .bind(user.version)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_user_conflict)?
+        .ok_or_else(|| stale("User"))
+    }
+
+    async fn create_email_confirmation(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO email_confirmation_token (user_id, token_hash, expires_at, created_date)
+             VALUES ($1, $2, $3, now() AT TIME ZONE 'utc')",
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_with_confirmation(
+        &self,
+        user: NewUser,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<User> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = insert_user(&mut *tx, user).await?;
+
+        sqlx::query(
+            "INSERT INTO email_confirmation_token (user_id, token_hash, expires_at, created_date)
+             VALUES ($1, $2, $3, now() AT TIME ZONE 'utc')",
+        )
+        .bind(user.id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    async fn delete_unconfirmed(&self, user_id: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM email_confirmation_token WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM \"user\" WHERE id = $1 AND email_confirmed = false")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn confirm_email(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<i32>> {
+        let mut tx = self.pool.begin().await?;
+
+        let confirmation = sqlx::query_as::<_, EmailConfirmationToken>(
+            "UPDATE email_confirmation_token SET used_at = $1
+             WHERE token_hash = $2 AND used_at IS NULL AND expires_at > $1
+             RETURNING *",
+        )
+        .bind(now)
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(confirmation) = confirmation else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE \"user\" SET email_confirmed = true WHERE id = $1")
+            .bind(confirmation.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(confirmation.user_id))
+    }
+
+    async fn count_password_reset_requests_since(&self, email: &str, since: NaiveDateTime) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM password_reset_request WHERE email = $1 AND created_date > $2")
+            .bind(email)
+            .bind(since)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn create_password_reset_request(&self, email: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO password_reset_request (email, created_date)
+             VALUES ($1, now() AT TIME ZONE 'utc')",
+        )
+        .bind(email)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_password_reset(&self, user_id: i32, token_hash: &str, expires_at: NaiveDateTime) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO password_reset_token (user_id, token_hash, expires_at, created_date)
+             VALUES ($1, $2, $3, now() AT TIME ZONE 'utc')",
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn reset_password(&self, token_hash: &str, password_hash: &str, now: NaiveDateTime) -> Result<Option<i32>> {
+        let mut tx = self.pool.begin().await?;
+
+        let reset = sqlx::query_as::<_, PasswordResetToken>(
+            "UPDATE password_reset_token SET used_at = $1
+             WHERE token_hash = $2 AND used_at IS NULL AND expires_at > $1
+             RETURNING *",
+        )
+        .bind(now)
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(reset) = reset else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE \"user\" SET password = $1 WHERE id = $2")
+            .bind(password_hash)
+            .bind(reset.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE password_reset_token SET used_at = $1 WHERE user_id = $2 AND used_at IS NULL")
+            .bind(now)
+            .bind(reset.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE active_session SET revoked_at = $1 WHERE user_id = $2 AND revoked_at IS NULL")
+            .bind(now)
+            .bind(reset.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(reset.user_id))
+    }
+}
+
+async fn insert_user<'e, E>(executor: E, user: NewUser) -> Result<User>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, User>(
+        "INSERT INTO \"user\" (name, balance, is_active, role_id, group_id, password, email, email_confirmed, user_display_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING *",
+    )
+    .bind(&user.name)
+    .bind(user.balance)
+    .bind(user.is_active)
+    .bind(user.role_id)
+    .bind(user.group_id)
+    .bind(&user.password)
+    .bind(&user.email)
+    .bind(user.email_confirmed)
+    .bind(&user.user_display_id)
+    .fetch_one(executor)
+    .await
+    .map_err(map_user_conflict)
+}
+
+fn map_user_conflict(e: sqlx::Error) -> Error {
+    match e.as_database_error().and_then(|db_error| db_error.constraint()) {
+        Some("user_email_key") => Error::conflict("Email is already registered"),
+        Some("user_user_display_id_key") => Error::conflict("User display ID is already taken"),
+        _ => e.into(),
+    }
+}
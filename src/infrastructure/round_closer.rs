@@ -0,0 +1,41 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{NaiveDateTime, Utc};
+
+use crate::{
+    application::error::Result,
+    domain::order::{order_entity::Order, order_repository::OrderRepository, order_service::OrderService},
+    interfaces::websocket::ws_server::{GroupEvent, WsServer},
+};
+
+/// Locks ordering rounds once their cut-off passes, checking every `every` on the current runtime.
+pub fn spawn_round_closer(orders: Arc<dyn OrderRepository>, events: Arc<WsServer>, every: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut ticks = actix_web::rt::time::interval(every);
+        loop {
+            ticks.tick().await;
+            match close_expired_rounds(Utc::now().naive_utc(), orders.as_ref(), &events).await {
+                Ok(locked) if !locked.is_empty() => {
+                    println!("[rounds] locked {} order(s) at their cut-off", locked.len())
+                }
+                Ok(_) => {}
+                Err(error) => eprintln!("[rounds] locking expired rounds failed: {}", error),
+            }
+        }
+    });
+}
+
+/// One run of the closer: locks the rounds past their cut-off at `now` and tells each group's
+/// connected members.
+pub async fn close_expired_rounds(
+    now: NaiveDateTime,
+    orders: &dyn OrderRepository,
+    events: &WsServer,
+) -> Result<Vec<Order>> {
+    let locked = OrderService::lock_expired_rounds(now, orders).await?;
+    for order in &locked {
+        events.broadcast(order.group_id, GroupEvent::order_status(order));
+    }
+
+    Ok(locked)
+}
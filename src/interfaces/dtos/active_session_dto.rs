@@ -1,12 +1,30 @@
+use crate::domain::active_session::active_session_entity::ActiveSession;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ActiveSessionDto {
     pub id: i32,
     pub user_id: i32,
     pub group_id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_date: chrono::NaiveDateTime,
+    pub last_seen_date: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    pub current: bool,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct CreateActiveSessionDto {
-    pub user_id: i32,
-    pub group_id: i32,
-} 
\ No newline at end of file
+impl ActiveSessionDto {
+    pub fn from_session(session: ActiveSession, current_session_id: i32) -> Self {
+        ActiveSessionDto {
+            current: session.id == current_session_id,
+            id: session.id,
+            user_id: session.user_id,
+            group_id: session.group_id,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_date: session.created_date,
+            last_seen_date: session.last_seen_date,
+            expires_at: session.expires_at,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct LoginDto {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct TokenDto {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct RefreshTokenDto {
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct ConfirmEmailDto {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordDto {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordDto {
+    #[validate(length(min = 1))]
+    pub token: String,
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub password: String,
+}
@@ -2,11 +2,11 @@
 pub struct CustomerDto {
     pub id: i32,
     pub user_id: i32,
-    pub balance: i32,
+    pub balance: i64,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CreateCustomerDto {
     pub user_id: i32,
-    pub balance: i32,
+    pub balance: i64,
 } 
\ No newline at end of file
@@ -1,7 +1,25 @@
+use rust_decimal::Decimal;
+
+use crate::domain::invoice_details::invoice_details_entity::InvoiceDetails;
+
+/// A stock row an invoice used: how much of it, in its unit, and what that cost.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct InvoiceDetailsDto {
     pub invoice_id: i32,
-    pub product_id: i32,
+    pub stock_id: i32,
+    pub quantity: Decimal,
+    pub cost: i64,
+}
+
+impl From<InvoiceDetails> for InvoiceDetailsDto {
+    fn from(details: InvoiceDetails) -> Self {
+        InvoiceDetailsDto {
+            invoice_id: details.invoice_id,
+            stock_id: details.stock_id,
+            quantity: details.quantity,
+            cost: details.cost,
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
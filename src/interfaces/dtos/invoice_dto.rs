@@ -1,9 +1,23 @@
 use std::collections::HashMap;
 
-use crate::interfaces::dtos::supplier_dto::SupplierDto;
+use crate::{
+    domain::{
+        invoice::{
+            invoice_entity::{Invoice, InvoiceCost, InvoiceWithParticipants},
+            split_strategy::SplitStrategy,
+        },
+        invoice_participant::invoice_participant_entity::InvoiceParticipant,
+    },
+    interfaces::dtos::{
+        invoice_details_dto::InvoiceDetailsDto,
+        stock_dto::{ProductUseDto, StockUseDto},
+        supplier_dto::SupplierDto,
+    },
+};
 use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct InvoiceRow {
@@ -17,8 +31,8 @@ pub struct InvoiceRow {
 
 #[derive(Debug, Deserialize)]
 pub struct InvoiceFilter {
-    pub start_date: String,
-    pub end_date: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,6 +43,169 @@ pub struct InvoiceDto {
     pub invoice_id: i32,
 }
 
+/// A single invoice as stored, without the report's supplier and meal resolution.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceSummaryDto {
+    pub id: i32,
+    pub price: i64,
+    pub created_date: NaiveDateTime,
+    pub last_modification_date: NaiveDateTime,
+    pub meal_id: i32,
+    pub supplier_id: i32,
+    pub group_id: i32,
+    pub order_id: Option<i32>,
+}
+
+impl From<Invoice> for InvoiceSummaryDto {
+    fn from(invoice: Invoice) -> Self {
+        InvoiceSummaryDto {
+            id: invoice.id,
+            price: invoice.price,
+            created_date: invoice.created_date,
+            last_modification_date: invoice.last_modification_date,
+            meal_id: invoice.meal_id,
+            supplier_id: invoice.supplier_id,
+            group_id: invoice.group_id,
+            order_id: invoice.order_id,
+        }
+    }
+}
+
+/// An invoice in the trash, with who deleted it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeletedInvoiceDto {
+    #[serde(flatten)]
+    pub invoice: InvoiceSummaryDto,
+    pub deleted_by: i32,
+}
+
+impl From<Invoice> for DeletedInvoiceDto {
+    fn from(invoice: Invoice) -> Self {
+        DeletedInvoiceDto {
+            deleted_by: invoice.deleted_by,
+            invoice: invoice.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceParticipantDto {
+    pub user_id: i32,
+    pub amount: i64,
+}
+
+impl From<InvoiceParticipant> for InvoiceParticipantDto {
+    fn from(participant: InvoiceParticipant) -> Self {
+        InvoiceParticipantDto {
+            user_id: participant.user_id,
+            amount: participant.amount,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceWithParticipantsDto {
+    #[serde(flatten)]
+    pub invoice: InvoiceSummaryDto,
+    pub participants: Vec<InvoiceParticipantDto>,
+}
+
+impl From<InvoiceWithParticipants> for InvoiceWithParticipantsDto {
+    fn from(invoice: InvoiceWithParticipants) -> Self {
+        InvoiceWithParticipantsDto {
+            invoice: invoice.invoice.into(),
+            participants: invoice.participants.into_iter().map(InvoiceParticipantDto::from).collect(),
+        }
+    }
+}
+
+/// What an invoice charged against what the stock it used cost.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceCostDto {
+    pub invoice_id: i32,
+    pub meal_id: i32,
+    pub price: i64,
+    pub cost_of_goods: i64,
+    pub details: Vec<InvoiceDetailsDto>,
+}
+
+impl From<InvoiceCost> for InvoiceCostDto {
+    fn from(cost: InvoiceCost) -> Self {
+        InvoiceCostDto {
+            invoice_id: cost.invoice.id,
+            meal_id: cost.invoice.meal_id,
+            price: cost.invoice.price,
+            cost_of_goods: cost.cost_of_goods(),
+            details: cost.details.into_iter().map(InvoiceDetailsDto::from).collect(),
+        }
+    }
+}
+
+/// The totals of a meal's live invoices, with each invoice's own.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MealCostDto {
+    pub meal_id: i32,
+    pub price: i64,
+    pub cost_of_goods: i64,
+    pub invoices: Vec<InvoiceCostDto>,
+}
+
+impl MealCostDto {
+    pub fn new(meal_id: i32, costs: Vec<InvoiceCost>) -> Self {
+        let invoices: Vec<InvoiceCostDto> = costs.into_iter().map(InvoiceCostDto::from).collect();
+        MealCostDto {
+            meal_id,
+            price: invoices.iter().map(|invoice| invoice.price).sum(),
+            cost_of_goods: invoices.iter().map(|invoice| invoice.cost_of_goods).sum(),
+            invoices,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateInvoiceParticipantDto {
+    pub user_id: i32,
+    /// Weight under `shares`, amount under `exact`, percent under `percentage`; absent under `equal`.
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateInvoiceDto {
+    pub meal_id: i32,
+    pub supplier_id: i32,
+    /// Unconsumed stock rows of the group that went into the meal, all that is left of each.
+    #[serde(default)]
+    pub stock_ids: Vec<i32>,
+    /// Stock rows that went into the meal in part, with how much of each.
+    #[serde(default)]
+    pub stock_uses: Vec<StockUseDto>,
+    /// Products that went into the meal, drawn from the group's oldest lots of each first.
+    #[serde(default)]
+    pub product_uses: Vec<ProductUseDto>,
+    /// Overrides the sum of the stock prices.
+    #[validate(range(min = 0, message = "price must not be negative"))]
+    pub price: Option<i64>,
+    #[serde(default)]
+    pub split: SplitStrategy,
+    /// Active members of the group who share the price.
+    #[validate(length(min = 1, message = "An invoice needs at least one participant"))]
+    pub participants: Vec<CreateInvoiceParticipantDto>,
+}
+
+/// Fields left out keep their value; meal and stock can't be changed.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateInvoiceDto {
+    pub supplier_id: Option<i32>,
+    #[validate(range(min = 0, message = "price must not be negative"))]
+    pub price: Option<i64>,
+    /// How the new participants split the price; ignored without them.
+    #[serde(default)]
+    pub split: SplitStrategy,
+    /// Replaces the participants; required when the price changes.
+    #[validate(length(min = 1, message = "An invoice needs at least one participant"))]
+    pub participants: Option<Vec<CreateInvoiceParticipantDto>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InvoiceResponse {
     pub date: String,
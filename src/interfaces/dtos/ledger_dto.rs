@@ -0,0 +1,106 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::domain::ledger::{
+    ledger_entity::{LedgerHistoryEntry, LedgerKind, LedgerTransaction},
+    settlement::{Settlement, Transfer},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LedgerHistoryDto {
+    pub transaction_id: i32,
+    pub kind: LedgerKind,
+    pub description: String,
+    pub invoice_id: Option<i32>,
+    pub payment_id: Option<i32>,
+    pub date: NaiveDateTime,
+    pub amount: i64,
+    /// The user's balance right after this posting.
+    pub balance: i64,
+}
+
+impl From<LedgerHistoryEntry> for LedgerHistoryDto {
+    fn from(history: LedgerHistoryEntry) -> Self {
+        LedgerHistoryDto {
+            transaction_id: history.entry.transaction_id,
+            kind: history.entry.kind,
+            description: history.entry.description,
+            invoice_id: history.entry.invoice_id,
+            payment_id: history.entry.payment_id,
+            date: history.entry.created_date,
+            amount: history.entry.amount,
+            balance: history.balance,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LedgerTransactionDto {
+    pub id: i32,
+    pub kind: LedgerKind,
+    pub description: String,
+    pub invoice_id: Option<i32>,
+    pub payment_id: Option<i32>,
+    pub date: NaiveDateTime,
+}
+
+impl From<LedgerTransaction> for LedgerTransactionDto {
+    fn from(transaction: LedgerTransaction) -> Self {
+        LedgerTransactionDto {
+            id: transaction.id,
+            kind: transaction.kind,
+            description: transaction.description,
+            invoice_id: transaction.invoice_id,
+            payment_id: transaction.payment_id,
+            date: transaction.created_date,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdjustmentEntryDto {
+    /// Absent for the group's own account.
+    pub user_id: Option<i32>,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAdjustmentDto {
+    #[validate(length(min = 1, message = "A description is required"))]
+    pub description: String,
+    #[validate(length(min = 2, message = "An adjustment needs at least two entries"))]
+    pub entries: Vec<AdjustmentEntryDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnsettledBalanceDto {
+    pub user_id: i32,
+    pub balance: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettlementDto {
+    pub transfers: Vec<Transfer>,
+    /// Balances the transfers can't clear because the active members' balances don't sum to zero.
+    pub unsettled: Vec<UnsettledBalanceDto>,
+}
+
+impl From<Settlement> for SettlementDto {
+    fn from(settlement: Settlement) -> Self {
+        SettlementDto {
+            transfers: settlement.transfers,
+            unsettled: settlement
+                .unsettled
+                .into_iter()
+                .map(|(user_id, balance)| UnsettledBalanceDto { user_id, balance })
+                .collect(),
+        }
+    }
+}
+
+/// The plan returned by the settlement endpoint, sent back to book it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordSettlementDto {
+    pub transfers: Vec<Transfer>,
+}
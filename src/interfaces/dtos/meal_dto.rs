@@ -1,10 +1,45 @@
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::{domain::meal::meal_entity::MealWithProducts, interfaces::dtos::product_dto::ProductDto};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
 pub struct MealDto {
     pub id: i32,
-    pub product_id: i32,
+    pub name: String,
+    pub description: String,
+    pub group_id: i32,
+    /// In the meal's order.
+    pub products: Vec<ProductDto>,
+}
+
+impl From<MealWithProducts> for MealDto {
+    fn from(meal: MealWithProducts) -> Self {
+        MealDto {
+            id: meal.meal.id,
+            name: meal.meal.name,
+            description: meal.meal.description,
+            group_id: meal.meal.group_id,
+            products: meal.products.into_iter().map(ProductDto::from).collect(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
 pub struct CreateMealDto {
+    #[validate(length(min = 1, message = "Name is required"))]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Products of the meal, in order.
+    #[validate(length(min = 1, message = "A meal needs at least one product"))]
+    pub product_ids: Vec<i32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct AddMealProductDto {
     pub product_id: i32,
-} 
\ No newline at end of file
+    /// Zero-based index to insert at; appended when absent.
+    #[validate(range(min = 0, message = "position must not be negative"))]
+    pub position: Option<i32>,
+}
@@ -1,3 +1,5 @@
+pub mod active_session_dto;
+pub mod auth_dto;
 pub mod role_dto;
 pub mod user_dto; 
 pub mod product_dto;
@@ -5,4 +7,9 @@ pub mod meal_dto;
 pub mod response_dto;
 pub mod invoice_dto;
 pub mod invoice_details_dto;
+pub mod ledger_dto;
+pub mod payment_dto;
 pub mod supplier_dto;
+pub mod order_dto;
+pub mod order_details_dto;
+pub mod stock_dto;
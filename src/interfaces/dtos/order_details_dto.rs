@@ -1,12 +1,28 @@
+use crate::domain::order_details::order_details_entity::OrderDetails;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OrderDetailsDto {
     pub id: i32,
     pub order_id: i32,
     pub stock_id: i32,
+    pub customer_id: Option<i32>,
+    pub quantity: Option<rust_decimal::Decimal>,
+}
+
+impl From<OrderDetails> for OrderDetailsDto {
+    fn from(line: OrderDetails) -> Self {
+        OrderDetailsDto {
+            id: line.id,
+            order_id: line.order_id,
+            stock_id: line.stock_id,
+            customer_id: line.customer_id,
+            quantity: line.quantity,
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CreateOrderDetailsDto {
     pub order_id: i32,
     pub stock_id: i32,
-} 
\ No newline at end of file
+}
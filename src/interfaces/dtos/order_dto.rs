@@ -1,3 +1,5 @@
+use crate::domain::order::{order_entity::Order, order_status::OrderStatus};
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OrderDto {
     pub id: i32,
@@ -6,9 +8,51 @@ pub struct OrderDto {
     pub created_date: chrono::NaiveDateTime,
     pub last_modification_date: chrono::NaiveDateTime,
     pub group_id: i32,
+    pub status: OrderStatus,
+    pub created_by: Option<i32>,
+    pub round_date: Option<chrono::NaiveDate>,
+    pub cutoff: Option<chrono::NaiveDateTime>,
+}
+
+impl From<Order> for OrderDto {
+    fn from(order: Order) -> Self {
+        OrderDto {
+            id: order.id,
+            is_deleted: order.is_deleted,
+            deleted_by: order.deleted_by,
+            created_date: order.created_date,
+            last_modification_date: order.last_modification_date,
+            group_id: order.group_id,
+            status: order.status,
+            created_by: order.created_by,
+            round_date: order.round_date,
+            cutoff: order.cutoff,
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CreateOrderDto {
     pub group_id: i32,
-} 
\ No newline at end of file
+}
+
+/// Bills a fulfilled order; the invoices it makes are for `meal_id`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct InvoiceOrderDto {
+    pub meal_id: i32,
+}
+
+/// Opens an ordering round for `date` that members can add to until `cutoff` (UTC).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct OpenRoundDto {
+    pub date: chrono::NaiveDate,
+    pub cutoff: chrono::NaiveDateTime,
+}
+
+/// Puts some of the group's stock on the order for the calling member; all that is left of the row
+/// when billed if `quantity` is left out.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AddOrderItemDto {
+    pub stock_id: i32,
+    pub quantity: Option<rust_decimal::Decimal>,
+}
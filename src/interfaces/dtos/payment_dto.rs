@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::domain::payment::payment_entity::{Payment, PaymentMethod, PaymentStatus};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentDto {
+    pub id: i32,
+    pub payer_id: i32,
+    pub payee_id: i32,
+    pub group_id: i32,
+    pub amount: i64,
+    pub method: PaymentMethod,
+    pub note: String,
+    pub status: PaymentStatus,
+    pub created_date: NaiveDateTime,
+    pub resolved_date: Option<NaiveDateTime>,
+}
+
+impl From<Payment> for PaymentDto {
+    fn from(payment: Payment) -> Self {
+        PaymentDto {
+            id: payment.id,
+            payer_id: payment.payer_id,
+            payee_id: payment.payee_id,
+            group_id: payment.group_id,
+            amount: payment.amount,
+            method: payment.method,
+            note: payment.note,
+            status: payment.status,
+            created_date: payment.created_date,
+            resolved_date: payment.resolved_date,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreatePaymentDto {
+    pub payee_id: i32,
+    #[validate(range(min = 1, message = "amount must be positive"))]
+    pub amount: i64,
+    pub method: PaymentMethod,
+    #[serde(default)]
+    #[validate(length(max = 500, message = "note must be at most 500 characters"))]
+    pub note: String,
+}
@@ -1,12 +1,26 @@
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+use utoipa::ToSchema;
+
+use crate::domain::product::product_entity::Product;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
 pub struct ProductDto {
     pub id: i32,
     pub name: String,
     pub group_id: i32,
 }
 
+impl From<Product> for ProductDto {
+    fn from(product: Product) -> Self {
+        ProductDto {
+            id: product.id,
+            name: product.name,
+            group_id: product.group_id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CreateProductDto {
     pub name: String,
     pub group_id: i32,
-} 
\ No newline at end of file
+}
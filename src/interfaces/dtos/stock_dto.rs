@@ -1,14 +1,91 @@
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::domain::stock::stock_entity::{Stock, StockAdjustment, Unit};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StockDto {
     pub id: i32,
     pub price: i64,
     pub consumed: bool,
     pub product_id: i32,
+    pub supplier_id: Option<i32>,
+    pub unit: Unit,
+    pub quantity: Decimal,
+    pub remaining: Decimal,
+    pub purchased_date: NaiveDateTime,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+impl From<Stock> for StockDto {
+    fn from(stock: Stock) -> Self {
+        StockDto {
+            id: stock.id,
+            price: stock.price,
+            consumed: stock.consumed,
+            product_id: stock.product_id,
+            supplier_id: stock.supplier_id,
+            unit: stock.unit,
+            quantity: stock.quantity,
+            remaining: stock.remaining,
+            purchased_date: stock.purchased_date,
+        }
+    }
+}
+
+/// A purchase of `quantity` `unit` of one of the group's products, for `price` in total.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateStockDto {
+    pub product_id: i32,
+    pub supplier_id: Option<i32>,
+    #[validate(range(min = 0, max = 1_000_000_000, message = "price must be between 0 and 1000000000"))]
     pub price: i64,
-    pub consumed: bool,
+    pub quantity: Decimal,
+    pub unit: Unit,
+}
+
+/// Sets what is left of a lot to what was counted; `reason` says why it differs.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AdjustStockDto {
+    pub remaining: Decimal,
+    #[validate(length(min = 1, max = 255, message = "reason must be 1 to 255 characters"))]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockAdjustmentDto {
+    pub id: i32,
+    pub stock_id: i32,
+    pub change: Decimal,
+    pub reason: String,
+    pub user_id: i32,
+    pub created_date: NaiveDateTime,
+}
+
+impl From<StockAdjustment> for StockAdjustmentDto {
+    fn from(adjustment: StockAdjustment) -> Self {
+        StockAdjustmentDto {
+            id: adjustment.id,
+            stock_id: adjustment.stock_id,
+            change: adjustment.change,
+            reason: adjustment.reason,
+            user_id: adjustment.user_id,
+            created_date: adjustment.created_date,
+        }
+    }
+}
+
+/// `quantity` of a stock row, in its unit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockUseDto {
+    pub stock_id: i32,
+    pub quantity: Decimal,
+}
+
+/// `quantity` of a product, in the unit it is stocked in, taken from its oldest lots first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductUseDto {
     pub product_id: i32,
-} 
\ No newline at end of file
+    pub quantity: Decimal,
+}
@@ -1,43 +1,105 @@
-use chrono::NaiveDateTime;
 use utoipa::ToSchema;
 use validator::Validate;
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema, sqlx::FromRow)]
+use crate::domain::user::user_entity::User;
+
+/// A user as returned by the API. The password hash is never part of it.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
 pub struct UserDto {
     pub id: i32,
     pub name: String,
-    pub password: String,
     pub email: String,
     pub email_confirmed: bool,
     pub user_display_id: String,
-    pub balance: i32,
+    pub balance: i64,
     pub is_active: bool,
     pub role_id: i32,
     pub group_id: i32,
 }
 
+impl From<User> for UserDto {
+    fn from(user: User) -> Self {
+        UserDto {
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            email_confirmed: user.email_confirmed,
+            user_display_id: user.user_display_id,
+            balance: user.balance,
+            is_active: user.is_active,
+            role_id: user.role_id,
+            group_id: user.group_id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema, sqlx::FromRow)]
 pub struct UserDisplayDto {
     pub id: i32,
     pub name: String,
-    pub balance: i32,
+    pub balance: i64,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate)]
+/// A user created by a manager. The account joins the manager's own group and starts with a zero balance.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
 pub struct CreateUserDto {
+    #[validate(length(min = 1, message = "Name is required"))]
     pub name: String,
     #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
     pub password: String,
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
+    #[serde(default)]
     pub email_confirmed: bool,
     #[validate(length(
         min = 3,
         message = "User display ID must be at least 3 characters long"
     ))]
     pub user_display_id: String,
-    pub balance: i32,
+    #[serde(default = "default_true")]
     pub is_active: bool,
     pub role_id: i32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Partial update for `PATCH /user/{id}`; absent fields are left unchanged.
+///
+/// `role_id`, `is_active` and `email_confirmed` need the `user:manage` permission, and changing
+/// your own password needs `current_password`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct UpdateUserDto {
+    #[validate(length(min = 1, message = "Name is required"))]
+    pub name: Option<String>,
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+    #[validate(length(
+        min = 3,
+        message = "User display ID must be at least 3 characters long"
+    ))]
+    pub user_display_id: Option<String>,
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub password: Option<String>,
+    pub current_password: Option<String>,
+    pub email_confirmed: Option<bool>,
+    pub is_active: Option<bool>,
+    pub role_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Validate, ToSchema)]
+pub struct RegisterUserDto {
+    #[validate(length(min = 1, message = "Name is required"))]
+    pub name: String,
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub password: String,
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+    #[validate(length(
+        min = 3,
+        message = "User display ID must be at least 3 characters long"
+    ))]
+    pub user_display_id: String,
     pub group_id: i32,
 }
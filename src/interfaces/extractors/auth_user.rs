@@ -0,0 +1,91 @@
+use std::{future::Future, pin::Pin};
+
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest};
+
+use crate::{
+    application::error::Error,
+    domain::active_session::{
+        active_session_repository::ActiveSessionRepository, active_session_service::ActiveSessionService,
+    },
+    infrastructure::jwt::{Claims, JwtConfig},
+};
+
+/// The subprotocol a browser offers alongside its access token to open a websocket, since it can't
+/// set headers on the upgrade: `new WebSocket(url, ["bearer", token])`.
+pub const WEBSOCKET_BEARER_PROTOCOL: &str = "bearer";
+
+/// The caller identified by a valid `Authorization: Bearer <token>` header, or for a websocket
+/// upgrade by the token offered after [`WEBSOCKET_BEARER_PROTOCOL`] in `Sec-WebSocket-Protocol`.
+///
+/// Adding it as a handler argument makes the route reject unauthenticated calls, and calls
+/// whose session has been revoked or has expired, with 401.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: i32,
+    pub session_id: i32,
+    pub role_id: i32,
+    pub group_id: i32,
+}
+
+impl FromRequest for AuthUser {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let claims = verify_bearer_token(req);
+        let sessions = req
+            .app_data::<web::Data<dyn ActiveSessionRepository>>()
+            .expect("ActiveSessionRepository must be registered as app data")
+            .clone();
+
+        Box::pin(async move {
+            let claims = claims?;
+            let active = ActiveSessionService::touch_session(claims.session_id, claims.user_id, sessions.get_ref()).await?;
+
+            if !active {
+                return Err(Error::unauthorized("Invalid or expired token"));
+            }
+
+            Ok(AuthUser {
+                user_id: claims.user_id,
+                session_id: claims.session_id,
+                role_id: claims.role_id,
+                group_id: claims.group_id,
+            })
+        })
+    }
+}
+
+fn verify_bearer_token(req: &HttpRequest) -> Result<Claims, Error> {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| websocket_bearer_token(req))
+        .ok_or_else(|| Error::unauthorized("Missing bearer token"))?;
+
+    let config = req
+        .app_data::<web::Data<JwtConfig>>()
+        .expect("JwtConfig must be registered as app data");
+
+    config
+        .verify_access_token(token.trim())
+        .map_err(|_| Error::unauthorized("Invalid or expired token"))
+}
+
+/// The token a websocket upgrade offers as the subprotocol after [`WEBSOCKET_BEARER_PROTOCOL`].
+pub(crate) fn websocket_bearer_token(req: &HttpRequest) -> Option<&str> {
+    let upgrade = req.headers().get(header::UPGRADE).and_then(|value| value.to_str().ok());
+    if !upgrade.is_some_and(|value| value.eq_ignore_ascii_case("websocket")) {
+        return None;
+    }
+    let mut protocols = req
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())?
+        .split(',')
+        .map(str::trim);
+    protocols.find(|protocol| *protocol == WEBSOCKET_BEARER_PROTOCOL)?;
+    protocols.next()
+}
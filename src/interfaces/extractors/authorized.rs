@@ -0,0 +1,71 @@
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+
+use crate::{
+    application::error::Error,
+    domain::role::{permission::Permission, role_repository::RoleRepository, role_service::RoleService},
+    interfaces::extractors::auth_user::AuthUser,
+};
+
+/// Marker type naming the permission a route requires, see [`perm`].
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+/// An [`AuthUser`] whose role grants `P::PERMISSION`; anyone else gets a 403.
+///
+/// Declared per route as a handler argument, e.g. `auth: Authorized<perm::InvoiceDelete>`.
+#[derive(Debug, Clone)]
+pub struct Authorized<P: RequiredPermission> {
+    pub user: AuthUser,
+    _permission: PhantomData<P>,
+}
+
+impl<P: RequiredPermission + 'static> FromRequest for Authorized<P> {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = AuthUser::from_request(req, payload);
+        let roles = req
+            .app_data::<web::Data<dyn RoleRepository>>()
+            .expect("RoleRepository must be registered as app data")
+            .clone();
+
+        Box::pin(async move {
+            let user = auth.await?;
+            let allowed = RoleService::user_has_permission(user.user_id, P::PERMISSION, roles.get_ref()).await?;
+
+            if !allowed {
+                return Err(Error::forbidden(format!("Missing permission {}", P::PERMISSION.as_str())));
+            }
+
+            Ok(Authorized {
+                user,
+                _permission: PhantomData,
+            })
+        })
+    }
+}
+
+/// Permission markers for [`Authorized`].
+pub mod perm {
+    use super::RequiredPermission;
+    use crate::domain::role::permission::Permission;
+
+    macro_rules! permission_markers {
+        ($($name:ident),* $(,)?) => {
+            $(
+                #[derive(Debug, Clone)]
+                pub struct $name;
+
+                impl RequiredPermission for $name {
+                    const PERMISSION: Permission = Permission::$name;
+                }
+            )*
+        };
+    }
+
+    permission_markers!(InvoiceCreate, InvoiceDelete, UserManage, GroupAdmin, StockPurchase);
+}
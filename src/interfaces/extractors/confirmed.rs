@@ -0,0 +1,70 @@
+use std::{future::Future, pin::Pin};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+
+use crate::{
+    application::error::Error,
+    domain::user::{user_repository::UserRepository, user_service::UserService},
+    interfaces::extractors::{
+        auth_user::AuthUser,
+        authorized::{Authorized, RequiredPermission},
+        group_member::GroupMember,
+    },
+};
+
+/// An extractor that identifies the calling user.
+pub trait Caller {
+    fn caller(&self) -> &AuthUser;
+}
+
+impl Caller for AuthUser {
+    fn caller(&self) -> &AuthUser {
+        self
+    }
+}
+
+impl<P: RequiredPermission> Caller for Authorized<P> {
+    fn caller(&self) -> &AuthUser {
+        &self.user
+    }
+}
+
+impl Caller for GroupMember {
+    fn caller(&self) -> &AuthUser {
+        &self.user
+    }
+}
+
+/// `T`, extracted as usual, for a caller whose email address has been confirmed.
+///
+/// Financial actions wrap their usual guard in it, e.g. `Confirmed<Authorized<perm::InvoiceCreate>>`
+/// or `Confirmed<GroupMember>`, so unconfirmed accounts get a 403.
+#[derive(Debug, Clone)]
+pub struct Confirmed<T>(pub T);
+
+impl<T> FromRequest for Confirmed<T>
+where
+    T: FromRequest<Error = Error> + Caller + 'static,
+{
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let inner = T::from_request(req, payload);
+        let users = req
+            .app_data::<web::Data<dyn UserRepository>>()
+            .expect("UserRepository must be registered as app data")
+            .clone();
+
+        Box::pin(async move {
+            let inner = inner.await?;
+            let confirmed = UserService::is_email_confirmed(inner.caller().user_id, users.get_ref()).await?;
+
+            if !confirmed {
+                return Err(Error::forbidden("Email address must be confirmed first"));
+            }
+
+            Ok(Confirmed(inner))
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use std::{future::Future, pin::Pin};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+
+use crate::{
+    application::error::Error,
+    domain::group::{group_repository::GroupRepository, group_service::GroupService},
+    interfaces::extractors::auth_user::AuthUser,
+};
+
+/// An [`AuthUser`] verified to belong to the group named by the route's `{group_id}` segment.
+///
+/// Group-scoped handlers must read the group from here instead of the raw path so every
+/// query they run is limited to a group the caller is a member of.
+#[derive(Debug, Clone)]
+pub struct GroupMember {
+    pub user: AuthUser,
+    pub group_id: i32,
+}
+
+impl FromRequest for GroupMember {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = AuthUser::from_request(req, payload);
+        let group_id = req.match_info().get("group_id").map(str::parse::<i32>);
+        let groups = req
+            .app_data::<web::Data<dyn GroupRepository>>()
+            .expect("GroupRepository must be registered as app data")
+            .clone();
+
+        Box::pin(async move {
+            let user = auth.await?;
+            let not_member = || Error::forbidden("Not a member of this group");
+            let group_id = match group_id {
+                Some(Ok(group_id)) => group_id,
+                Some(Err(_)) => return Err(not_member()),
+                None => return Err(Error::internal("Route has no {group_id} segment")),
+            };
+
+            if !GroupService::is_member(user.user_id, group_id, groups.get_ref()).await? {
+                return Err(not_member());
+            }
+
+            Ok(GroupMember { user, group_id })
+        })
+    }
+}
@@ -0,0 +1,44 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    dev::Payload,
+    http::header::{self, HeaderName},
+    FromRequest, HttpRequest,
+};
+
+use crate::{application::error::Error, domain::version::Version};
+
+/// The version named by the request's `If-Match` header, as handed out in an [`etag`].
+///
+/// Updates take it so a client can only change what it has seen: a missing header is a 428,
+/// a stale one a 412 once the service compares it to the row.
+#[derive(Debug, Clone, Copy)]
+pub struct IfMatch(pub Version);
+
+impl FromRequest for IfMatch {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let Some(value) = req.headers().get(header::IF_MATCH) else {
+            return ready(Err(Error::precondition_required(
+                "If-Match header with the ETag you last saw is required",
+            )));
+        };
+
+        let version = value
+            .to_str()
+            .ok()
+            .map(|value| value.trim().trim_start_matches("W/").trim_matches('"'))
+            .and_then(|value| value.parse().ok())
+            .map(IfMatch)
+            .ok_or_else(|| Error::validation("If-Match must be an ETag returned by this API"));
+
+        ready(version)
+    }
+}
+
+/// The `ETag` header to send along with the current state of a row.
+pub fn etag(version: Version) -> (HeaderName, String) {
+    (header::ETAG, format!("\"{}\"", version))
+}
@@ -0,0 +1,6 @@
+// src/interfaces/extractors/mod.rs
+pub mod auth_user;
+pub mod authorized;
+pub mod confirmed;
+pub mod group_member;
+pub mod if_match;
@@ -1,4 +1,5 @@
 // src/interfaces/mod.rs
 pub mod dtos;
+pub mod extractors;
 pub mod rest;
 pub mod websocket;
@@ -0,0 +1,223 @@
+use actix_web::{delete, get, http::header, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        active_session::{
+            active_session_entity::ActiveSession,
+            active_session_repository::ActiveSessionRepository,
+            active_session_service::{ActiveSessionService, SessionClient},
+        },
+        group::group_repository::GroupRepository,
+        role::{role_repository::RoleRepository, role_service::RoleService},
+        user::{user_entity::User, user_repository::UserRepository, user_service::UserService},
+    },
+    infrastructure::{jwt::JwtConfig, mailer::Mailer},
+    interfaces::{
+        dtos::{
+            active_session_dto::ActiveSessionDto,
+            auth_dto::{ConfirmEmailDto, ForgotPasswordDto, LoginDto, RefreshTokenDto, ResetPasswordDto, TokenDto},
+            response_dto::ApiResponse,
+            user_dto::{RegisterUserDto, UserDisplayDto},
+        },
+        extractors::auth_user::AuthUser,
+    },
+};
+
+fn session_client(req: &HttpRequest) -> SessionClient {
+    SessionClient {
+        user_agent: req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        ip_address: req.connection_info().realip_remote_addr().map(str::to_string),
+    }
+}
+
+fn token_dto(jwt: &JwtConfig, user: &User, session: &ActiveSession, refresh_token: String) -> Result<TokenDto> {
+    Ok(TokenDto {
+        access_token: jwt.create_access_token(user, session.id)?,
+        token_type: "Bearer".to_string(),
+        expires_in: jwt.access_token_ttl().num_seconds(),
+        refresh_token,
+    })
+}
+
+#[post("/auth/login")]
+pub async fn login(
+    users: web::Data<dyn UserRepository>,
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    jwt: web::Data<JwtConfig>,
+    req: HttpRequest,
+    payload: web::Json<LoginDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let user = UserService::authenticate(&payload.email, &payload.password, users.get_ref()).await?;
+    let (session, refresh_token) =
+        ActiveSessionService::create_session(&user, session_client(&req), jwt.refresh_token_ttl(), sessions.get_ref())
+            .await?;
+
+    let dto = token_dto(&jwt, &user, &session, refresh_token)?;
+    Ok(web::Json(ApiResponse::new(200, vec![dto], "")))
+}
+
+#[post("/auth/refresh")]
+pub async fn refresh(
+    users: web::Data<dyn UserRepository>,
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    jwt: web::Data<JwtConfig>,
+    req: HttpRequest,
+    payload: web::Json<RefreshTokenDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let (user, session, refresh_token) = ActiveSessionService::refresh_session(
+        &payload.refresh_token,
+        session_client(&req),
+        jwt.refresh_token_ttl(),
+        sessions.get_ref(),
+        users.get_ref(),
+    )
+    .await?;
+
+    let dto = token_dto(&jwt, &user, &session, refresh_token)?;
+    Ok(web::Json(ApiResponse::new(200, vec![dto], "")))
+}
+
+#[get("/auth/sessions")]
+pub async fn get_my_sessions(
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+) -> Result<impl Responder> {
+    let sessions = ActiveSessionService::get_user_sessions(auth.user_id, sessions.get_ref()).await?;
+    let dtos: Vec<ActiveSessionDto> = sessions
+        .into_iter()
+        .map(|session| ActiveSessionDto::from_session(session, auth.session_id))
+        .collect();
+
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+#[get("/auth/permissions")]
+pub async fn get_my_permissions(
+    roles: web::Data<dyn RoleRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+) -> Result<impl Responder> {
+    let permissions = RoleService::get_user_permissions(auth.user_id, roles.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, permissions, "")))
+}
+
+#[delete("/auth/sessions/{id}")]
+pub async fn revoke_session(
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    ActiveSessionService::revoke_session(auth.user_id, id.into_inner(), sessions.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
+
+#[post("/auth/logout")]
+pub async fn logout(
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+) -> Result<impl Responder> {
+    ActiveSessionService::revoke_session(auth.user_id, auth.session_id, sessions.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
+
+#[post("/auth/logout-all")]
+pub async fn logout_everywhere(
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+) -> Result<impl Responder> {
+    ActiveSessionService::revoke_all_sessions(auth.user_id, sessions.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
+
+#[post("/auth/register")]
+pub async fn register(
+    users: web::Data<dyn UserRepository>,
+    groups: web::Data<dyn GroupRepository>,
+    roles: web::Data<dyn RoleRepository>,
+    mailer: web::Data<dyn Mailer>,
+    _req: HttpRequest,
+    payload: web::Json<RegisterUserDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let user = UserService::register(
+        payload.into_inner(),
+        mailer.get_ref(),
+        users.get_ref(),
+        groups.get_ref(),
+        roles.get_ref(),
+    )
+    .await?;
+    let dto = UserDisplayDto {
+        id: user.id,
+        name: user.name,
+        balance: user.balance,
+    };
+
+    Ok(HttpResponse::Created().json(ApiResponse::new(201, vec![dto], "")))
+}
+
+#[post("/auth/confirm-email")]
+pub async fn confirm_email(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    payload: web::Json<ConfirmEmailDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    UserService::confirm_email(&payload.token, users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
+
+#[post("/auth/forgot-password")]
+pub async fn forgot_password(
+    users: web::Data<dyn UserRepository>,
+    mailer: web::Data<dyn Mailer>,
+    _req: HttpRequest,
+    payload: web::Json<ForgotPasswordDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    UserService::request_password_reset(&payload.email, mailer.get_ref(), users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
+
+#[post("/auth/reset-password")]
+pub async fn reset_password(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    payload: web::Json<ResetPasswordDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    UserService::reset_password(&payload.token, &payload.password, users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
+
+pub fn register_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(login);
+    cfg.service(refresh);
+    cfg.service(get_my_sessions);
+    cfg.service(get_my_permissions);
+    cfg.service(revoke_session);
+    cfg.service(logout);
+    cfg.service(logout_everywhere);
+    cfg.service(register);
+    cfg.service(confirm_email);
+    cfg.service(forgot_password);
+    cfg.service(reset_password);
+}
@@ -1,68 +1,216 @@
 use crate::{
-    domain::invoice::invoice_service::InvoiceService,
-    interfaces::dtos::{invoice_dto::{InvoiceFilter, InvoiceResponse}, response_dto::ApiResponse},
+    application::error::{Error, Result},
+    domain::{
+        invoice::{invoice_repository::InvoiceRepository, invoice_service::InvoiceService},
+        meal::meal_repository::MealRepository,
+        stock::stock_repository::StockRepository,
+        supplier::supplier_repository::SupplierRepository,
+        user::user_repository::UserRepository,
+    },
+    interfaces::{
+        dtos::{
+            invoice_dto::{
+                CreateInvoiceDto, DeletedInvoiceDto, InvoiceCostDto, InvoiceFilter, InvoiceSummaryDto,
+                InvoiceWithParticipantsDto, MealCostDto, UpdateInvoiceDto,
+            },
+            response_dto::ApiResponse,
+        },
+        extractors::{
+            authorized::{perm, Authorized},
+            confirmed::Confirmed,
+            group_member::GroupMember,
+            if_match::{etag, IfMatch},
+        },
+    },
 };
-use actix_web::{get, web, HttpRequest, Responder};
-use sqlx::PgPool;
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
 
 #[get("/invoice/group/{group_id}")]
 pub async fn get_invoice_report(
-    data: web::Data<PgPool>,
+    invoices: web::Data<dyn InvoiceRepository>,
     _req: HttpRequest,
-    group_id: web::Path<i32>,
+    member: GroupMember,
     filter: web::Json<InvoiceFilter>,
-) -> impl Responder {
-    match InvoiceService::get_invoice_report(
-        group_id.into_inner(),
-        filter.start_date.clone(),
-        filter.end_date.clone(),
-        data.get_ref().clone(),
-    )
-    .await
-    {
-        Ok(invoices) => {
-            web::Json(ApiResponse::new(200, invoices, ""))
-        }
-        Err(e) => web::Json(ApiResponse::new(
-            500,
-            Vec::<InvoiceResponse>::new(),
-            e.to_string(),
-        )),
+) -> Result<impl Responder> {
+    if filter.start_date > filter.end_date {
+        return Err(Error::validation("start_date must not be after end_date"));
     }
+
+    let report =
+        InvoiceService::get_invoice_report(member.group_id, filter.start_date, filter.end_date, invoices.get_ref())
+            .await?;
+    Ok(web::Json(ApiResponse::new(200, report, "")))
 }
 
-// #[get("/invoice/{id}/group/{group_id}")]
-// pub async fn get_invoice_by_id(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     path: web::Path<(i32, i32)>,
-// ) -> impl Responder {
-//     let (id, group_id) = path.into_inner();
-//     match InvoiceService::get_invoice_by_id(&data, id, group_id).await {
-//         Ok(Some(inv)) => {
-//             let dto: InvoiceDto = inv.into();
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<InvoiceDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<InvoiceDto>::new(), e.to_string())),
-//     }
-// }
+/// Creates an invoice in the caller's group; stock, supplier credit and participant debits
+/// are applied together or not at all.
+#[post("/invoice")]
+pub async fn create_invoice(
+    invoices: web::Data<dyn InvoiceRepository>,
+    meals: web::Data<dyn MealRepository>,
+    suppliers: web::Data<dyn SupplierRepository>,
+    stocks: web::Data<dyn StockRepository>,
+    users: web::Data<dyn UserRepository>,
+    Confirmed(auth): Confirmed<Authorized<perm::InvoiceCreate>>,
+    payload: web::Json<CreateInvoiceDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
 
-// #[get("/invoice/meal/{meal_id}/group/{group_id}")]
-// pub async fn get_invoices_by_meal_id(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     path: web::Path<(i32, i32)>,
-// ) -> impl Responder {
-//     let (meal_id, group_id) = path.into_inner();
-//     match InvoiceService::get_invoices_by_meal_id(&data, meal_id, group_id).await {
-//         Ok(invoices) => {
-//             let dtos: Vec<InvoiceDto> = invoices.into_iter().map(|inv| inv.into()).collect();
-//             web::Json(ApiResponse::new(200, dtos, ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<InvoiceDto>::new(), e.to_string())),
-//     }
-// }
+    let invoice = InvoiceService::create_invoice(
+        auth.user.group_id,
+        payload.into_inner(),
+        invoices.get_ref(),
+        meals.get_ref(),
+        suppliers.get_ref(),
+        stocks.get_ref(),
+        users.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Created()
+        .insert_header(etag(invoice.invoice.version()))
+        .json(ApiResponse::new(201, vec![InvoiceWithParticipantsDto::from(invoice)], "")))
+}
+
+#[get("/invoice/{id}/group/{group_id}")]
+pub async fn get_invoice_by_id(
+    invoices: web::Data<dyn InvoiceRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let invoice = InvoiceService::get_invoice(member.group_id, id, invoices.get_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(invoice.invoice.version()))
+        .json(ApiResponse::new(200, vec![InvoiceWithParticipantsDto::from(invoice)], "")))
+}
+
+#[get("/invoice/meal/{meal_id}/group/{group_id}")]
+pub async fn get_invoices_by_meal_id(
+    invoices: web::Data<dyn InvoiceRepository>,
+    meals: web::Data<dyn MealRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    meal_id: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (meal_id, _) = meal_id.into_inner();
+    let found =
+        InvoiceService::get_invoices_by_meal_id(member.group_id, meal_id, invoices.get_ref(), meals.get_ref()).await?;
+    let dtos: Vec<InvoiceSummaryDto> = found.into_iter().map(InvoiceSummaryDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+/// What the stock an invoice of the group used cost, lot by lot.
+#[get("/invoice/{id}/group/{group_id}/cost")]
+pub async fn get_invoice_cost(
+    invoices: web::Data<dyn InvoiceRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let cost = InvoiceService::get_invoice_cost(member.group_id, id, invoices.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![InvoiceCostDto::from(cost)], "")))
+}
+
+/// Cost of goods of a meal across its live invoices.
+#[get("/invoice/meal/{meal_id}/group/{group_id}/cost")]
+pub async fn get_meal_cost(
+    invoices: web::Data<dyn InvoiceRepository>,
+    meals: web::Data<dyn MealRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (meal_id, _) = path.into_inner();
+    let costs = InvoiceService::get_meal_cost(member.group_id, meal_id, invoices.get_ref(), meals.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![MealCostDto::new(meal_id, costs)], "")))
+}
+
+/// Deleted invoices of the group, most recently deleted first.
+#[get("/invoice/group/{group_id}/trash")]
+pub async fn get_deleted_invoices(
+    invoices: web::Data<dyn InvoiceRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let deleted = InvoiceService::get_deleted_invoices(member.group_id, invoices.get_ref()).await?;
+    let dtos: Vec<DeletedInvoiceDto> = deleted.into_iter().map(DeletedInvoiceDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+/// Edits an invoice of the caller's group, moving balances from the old split to the new one.
+#[patch("/invoice/{id}")]
+pub async fn update_invoice(
+    invoices: web::Data<dyn InvoiceRepository>,
+    suppliers: web::Data<dyn SupplierRepository>,
+    users: web::Data<dyn UserRepository>,
+    Confirmed(auth): Confirmed<Authorized<perm::InvoiceCreate>>,
+    IfMatch(expected): IfMatch,
+    id: web::Path<i32>,
+    payload: web::Json<UpdateInvoiceDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let invoice = InvoiceService::update_invoice(
+        auth.user.group_id,
+        id.into_inner(),
+        expected,
+        payload.into_inner(),
+        invoices.get_ref(),
+        suppliers.get_ref(),
+        users.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(invoice.invoice.version()))
+        .json(ApiResponse::new(200, vec![InvoiceWithParticipantsDto::from(invoice)], "")))
+}
+
+/// Soft-deletes an invoice of the caller's group and gives everyone back what it charged or credited.
+#[delete("/invoice/{id}")]
+pub async fn delete_invoice(
+    invoices: web::Data<dyn InvoiceRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<Authorized<perm::InvoiceDelete>>,
+    IfMatch(expected): IfMatch,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    let invoice = InvoiceService::delete_invoice(
+        auth.user.user_id,
+        auth.user.group_id,
+        id.into_inner(),
+        expected,
+        invoices.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(invoice.version()))
+        .json(ApiResponse::new(200, vec![DeletedInvoiceDto::from(invoice)], "")))
+}
+
+#[post("/invoice/{id}/restore")]
+pub async fn restore_invoice(
+    invoices: web::Data<dyn InvoiceRepository>,
+    suppliers: web::Data<dyn SupplierRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<Authorized<perm::InvoiceDelete>>,
+    IfMatch(expected): IfMatch,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    let invoice = InvoiceService::restore_invoice(
+        auth.user.group_id,
+        id.into_inner(),
+        expected,
+        invoices.get_ref(),
+        suppliers.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(invoice.invoice.version()))
+        .json(ApiResponse::new(200, vec![InvoiceWithParticipantsDto::from(invoice)], "")))
+}
 
 // #[get("/invoice/supplier/{supplier_id}/group/{group_id}")]
 // pub async fn get_invoices_by_supplier_id(
@@ -109,8 +257,15 @@ pub async fn get_invoice_report(
 
 pub fn register_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_invoice_report);
-    // cfg.service(get_invoice_by_id);
-    // cfg.service(get_invoices_by_meal_id);
+    cfg.service(create_invoice);
+    cfg.service(get_invoice_by_id);
+    cfg.service(get_invoices_by_meal_id);
+    cfg.service(get_invoice_cost);
+    cfg.service(get_meal_cost);
+    cfg.service(get_deleted_invoices);
+    cfg.service(update_invoice);
+    cfg.service(delete_invoice);
+    cfg.service(restore_invoice);
     // cfg.service(get_invoices_by_supplier_id);
     // cfg.service(get_invoices_by_product_id);
 }
@@ -0,0 +1,117 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        ledger::{ledger_repository::LedgerRepository, ledger_service::LedgerService},
+        payment::payment_repository::PaymentRepository,
+        user::user_repository::UserRepository,
+    },
+    interfaces::{
+        dtos::{
+            ledger_dto::{
+                CreateAdjustmentDto, LedgerHistoryDto, LedgerTransactionDto, RecordSettlementDto, SettlementDto,
+            },
+            response_dto::ApiResponse,
+        },
+        extractors::{
+            authorized::{perm, Authorized},
+            confirmed::Confirmed,
+            group_member::GroupMember,
+        },
+    },
+};
+
+/// Every posting to the user's account with the running balance after it.
+#[get("/ledger/user/{user_id}/group/{group_id}")]
+pub async fn get_user_history(
+    ledger: web::Data<dyn LedgerRepository>,
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (user_id, _) = path.into_inner();
+    let history = LedgerService::get_user_history(member.group_id, user_id, ledger.get_ref(), users.get_ref()).await?;
+    let dtos: Vec<LedgerHistoryDto> = history.into_iter().map(LedgerHistoryDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+/// Users, suppliers and customers of the caller's group whose balance disagrees with the ledger.
+#[get("/ledger/reconcile")]
+pub async fn get_balance_drift(
+    ledger: web::Data<dyn LedgerRepository>,
+    _req: HttpRequest,
+    auth: Authorized<perm::GroupAdmin>,
+) -> Result<impl Responder> {
+    let drift = LedgerService::find_drift(auth.user.group_id, ledger.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, drift, "")))
+}
+
+/// Resets drifted balances in the caller's group to the ledger's and lists what changed.
+#[post("/ledger/reconcile")]
+pub async fn rebuild_balances(
+    ledger: web::Data<dyn LedgerRepository>,
+    _req: HttpRequest,
+    auth: Authorized<perm::GroupAdmin>,
+) -> Result<impl Responder> {
+    let drift = LedgerService::rebuild_balances(auth.user.group_id, ledger.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, drift, "")))
+}
+
+#[post("/ledger/adjustment")]
+pub async fn create_adjustment(
+    ledger: web::Data<dyn LedgerRepository>,
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<Authorized<perm::GroupAdmin>>,
+    payload: web::Json<CreateAdjustmentDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let transaction =
+        LedgerService::create_adjustment(auth.user.group_id, payload.into_inner(), ledger.get_ref(), users.get_ref())
+            .await?;
+    Ok(HttpResponse::Created().json(ApiResponse::new(201, vec![LedgerTransactionDto::from(transaction)], "")))
+}
+
+/// Who should pay whom to bring every active member's balance to zero.
+#[get("/ledger/settlement/group/{group_id}")]
+pub async fn get_settlement(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let settlement = LedgerService::plan_settlement(member.group_id, users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![SettlementDto::from(settlement)], "")))
+}
+
+/// Books the settlement of the caller's group as confirmed payments.
+#[post("/ledger/settlement")]
+pub async fn record_settlement(
+    payments: web::Data<dyn PaymentRepository>,
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<Authorized<perm::GroupAdmin>>,
+    payload: web::Json<RecordSettlementDto>,
+) -> Result<impl Responder> {
+    let transactions = LedgerService::record_settlement(
+        auth.user.group_id,
+        payload.into_inner().transfers,
+        payments.get_ref(),
+        users.get_ref(),
+    )
+    .await?;
+    let dtos: Vec<LedgerTransactionDto> = transactions.into_iter().map(LedgerTransactionDto::from).collect();
+    Ok(HttpResponse::Created().json(ApiResponse::new(201, dtos, "")))
+}
+
+pub fn register_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_user_history);
+    cfg.service(get_balance_drift);
+    cfg.service(rebuild_balances);
+    cfg.service(create_adjustment);
+    cfg.service(get_settlement);
+    cfg.service(record_settlement);
+}
@@ -1,101 +1,112 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
 
-use actix_web::{web};
+use crate::{
+    application::error::Result,
+    domain::{
+        meal::{meal_repository::MealRepository, meal_service::MealService},
+        product::product_repository::ProductRepository,
+    },
+    interfaces::{
+        dtos::{
+            meal_dto::{AddMealProductDto, CreateMealDto, MealDto},
+            response_dto::ApiResponse,
+        },
+        extractors::{
+            group_member::GroupMember,
+            if_match::{etag, IfMatch},
+        },
+    },
+};
 
-// #[get("/meal")]
-// pub async fn get_all_meals(
-//     data: web::Data<DatabaseConnection>,
-//     _req: HttpRequest,
-// ) -> impl Responder {
-//     match MealService::get_all_meals(&data).await {
-//         Ok(meals) => {
-//             let dtos: Vec<MealDto> = meals.into_iter().map(|meal| MealDto { id: meal.id, product_id: meal.product_id }).collect();
-//             web::Json(ApiResponse::new(200, dtos, ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<MealDto>::new(), e.to_string())),
-//     }
-// }
+#[get("/meal/group/{group_id}")]
+pub async fn get_group_meals(
+    meals: web::Data<dyn MealRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let found = MealService::get_group_meals(member.group_id, meals.get_ref()).await?;
+    let dtos: Vec<MealDto> = found.into_iter().map(MealDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
 
-// #[get("/meal/{id}")]
-// pub async fn get_meal_by_id(
-//     data: web::Data<DatabaseConnection>,
-//     _req: HttpRequest,
-//     id: web::Path<i32>,
-// ) -> impl Responder {
-//     match MealService::get_meal_by_id(&data, id.into_inner()).await {
-//         Ok(Some(meal)) => {
-//             let dto = MealDto { id: meal.id, product_id: meal.product_id };
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<MealDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<MealDto>::new(), e.to_string())),
-//     }
-// }
+#[get("/meal/{id}/group/{group_id}")]
+pub async fn get_meal_by_id(
+    meals: web::Data<dyn MealRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let meal = MealService::get_meal(member.group_id, id, meals.get_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(meal.meal.version()))
+        .json(ApiResponse::new(200, vec![MealDto::from(meal)], "")))
+}
 
-// #[get("/meal/product/{product_id}")]
-// pub async fn get_meals_by_product_id(
-//     data: web::Data<DatabaseConnection>,
-//     _req: HttpRequest,
-//     product_id: web::Path<i32>,
-// ) -> impl Responder {
-//     match MealService::get_meals_by_product_id(&data, product_id.into_inner()).await {
-//         Ok(meals) => {
-//             let dtos: Vec<MealDto> = meals.into_iter().map(|meal| MealDto { id: meal.id, product_id: meal.product_id }).collect();
-//             web::Json(ApiResponse::new(200, dtos, ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<MealDto>::new(), e.to_string())),
-//     }
-// }
+#[post("/meal/group/{group_id}")]
+pub async fn create_meal(
+    meals: web::Data<dyn MealRepository>,
+    products: web::Data<dyn ProductRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    payload: web::Json<CreateMealDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
 
-// #[post("/meal")]
-// pub async fn create_meal(
-//     data: web::Data<DatabaseConnection>,
-//     _req: HttpRequest,
-//     payload: web::Json<CreateMealDto>,
-// ) -> impl Responder {
-//     match MealService::create_meal(&data, payload.product_id).await {
-//         Ok(meal) => {
-//             let dto = MealDto { id: meal.id, product_id: meal.product_id };
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<MealDto>::new(), e.to_string())),
-//     }
-// }
+    let meal =
+        MealService::create_meal(member.group_id, payload.into_inner(), meals.get_ref(), products.get_ref()).await?;
+    Ok(HttpResponse::Created()
+        .insert_header(etag(meal.meal.version()))
+        .json(ApiResponse::new(201, vec![MealDto::from(meal)], "")))
+}
 
-// #[put("/meal/{id}")]
-// pub async fn update_meal(
-//     data: web::Data<DatabaseConnection>,
-//     _req: HttpRequest,
-//     id: web::Path<i32>,
-//     payload: web::Json<CreateMealDto>,
-// ) -> impl Responder {
-//     match MealService::update_meal(&data, id.into_inner(), Some(payload.product_id)).await {
-//         Ok(Some(meal)) => {
-//             let dto = MealDto { id: meal.id, product_id: meal.product_id };
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<MealDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<MealDto>::new(), e.to_string())),
-//     }
-// }
+#[post("/meal/{id}/group/{group_id}/product")]
+pub async fn add_meal_product(
+    meals: web::Data<dyn MealRepository>,
+    products: web::Data<dyn ProductRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+    payload: web::Json<AddMealProductDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
 
-// #[delete("/meal/{id}")]
-// pub async fn delete_meal(
-//     data: web::Data<DatabaseConnection>,
-//     _req: HttpRequest,
-//     id: web::Path<i32>,
-// ) -> impl Responder {
-//     match MealService::delete_meal(&data, id.into_inner()).await {
-//         Ok(true) => web::Json(ApiResponse::new(200, Vec::<MealDto>::new(), "")),
-//         Ok(false) => web::Json(ApiResponse::new(404, Vec::<MealDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<MealDto>::new(), e.to_string())),
-//     }
-// }
+    let (id, _) = path.into_inner();
+    let meal = MealService::add_product(
+        member.group_id,
+        id,
+        expected,
+        payload.into_inner(),
+        meals.get_ref(),
+        products.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(meal.meal.version()))
+        .json(ApiResponse::new(200, vec![MealDto::from(meal)], "")))
+}
+
+#[delete("/meal/{id}/group/{group_id}/product/{product_id}")]
+pub async fn remove_meal_product(
+    meals: web::Data<dyn MealRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _, product_id) = path.into_inner();
+    let meal = MealService::remove_product(member.group_id, id, expected, product_id, meals.get_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(meal.meal.version()))
+        .json(ApiResponse::new(200, vec![MealDto::from(meal)], "")))
+}
 
 pub fn register_routes(cfg: &mut web::ServiceConfig) {
-    // cfg.service(get_all_meals);
-    // cfg.service(get_meal_by_id);
-    // cfg.service(get_meals_by_product_id);
-    // cfg.service(create_meal);
-    // cfg.service(update_meal);
-    // cfg.service(delete_meal);
-} 
\ No newline at end of file
+    cfg.service(get_group_meals);
+    cfg.service(get_meal_by_id);
+    cfg.service(create_meal);
+    cfg.service(add_meal_product);
+    cfg.service(remove_meal_product);
+}
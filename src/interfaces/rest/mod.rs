@@ -1,17 +1,39 @@
 use actix_web::web;
+
+use crate::application::error::Error;
+
+pub mod auth_controller;
+// the role, product and invoice details routes are still commented out, leaving `cfg` unused
+#[allow(unused_variables)]
 pub mod role_controller;
 pub mod user_controller;
+#[allow(unused_variables)]
 pub mod product_controller;
 pub mod meal_controller;
 pub mod invoice_controller;
+#[allow(unused_variables)]
 pub mod invoice_details_controller;
+pub mod ledger_controller;
+pub mod payment_controller;
+pub mod order_controller;
+pub mod stock_controller;
 
 pub fn register_route(cfg: &mut web::ServiceConfig) {
+    // malformed bodies and paths get the same envelope as every other error
+    cfg.app_data(web::JsonConfig::default().error_handler(|e, _| Error::validation(e.to_string()).into()))
+        .app_data(web::PathConfig::default().error_handler(|e, _| Error::validation(e.to_string()).into()))
+        .app_data(web::QueryConfig::default().error_handler(|e, _| Error::validation(e.to_string()).into()));
+
+    auth_controller::register_routes(cfg);
     role_controller::register_routes(cfg);
     user_controller::register_routes(cfg);
     product_controller::register_routes(cfg);
     meal_controller::register_routes(cfg);
     invoice_controller::register_routes(cfg);
     invoice_details_controller::register_routes(cfg);
+    ledger_controller::register_routes(cfg);
+    payment_controller::register_routes(cfg);
+    order_controller::register_routes(cfg);
+    stock_controller::register_routes(cfg);
     // Add other controllers here
 }
@@ -0,0 +1,280 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+
+use crate::{
+    application::error::Result,
+    domain::{
+        customer::customer_repository::CustomerRepository,
+        meal::meal_repository::MealRepository,
+        order::{
+            order_repository::OrderRepository,
+            order_service::{OrderActor, OrderService},
+            order_status::OrderStatus,
+        },
+        role::{permission::Permission, role_repository::RoleRepository, role_service::RoleService},
+        stock::stock_repository::StockRepository,
+        supplier::supplier_repository::SupplierRepository,
+        user::user_repository::UserRepository,
+        version::Version,
+    },
+    interfaces::{
+        dtos::{
+            invoice_dto::InvoiceWithParticipantsDto,
+            order_details_dto::OrderDetailsDto,
+            order_dto::{AddOrderItemDto, InvoiceOrderDto, OpenRoundDto, OrderDto},
+            response_dto::ApiResponse,
+        },
+        extractors::{
+            authorized::{perm, Authorized},
+            confirmed::Confirmed,
+            group_member::GroupMember,
+            if_match::{etag, IfMatch},
+        },
+        websocket::ws_server::{GroupEvent, WsServer},
+    },
+};
+
+#[get("/order/group/{group_id}")]
+pub async fn get_group_orders(
+    orders: web::Data<dyn OrderRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let found = OrderService::get_group_orders(member.group_id, orders.get_ref()).await?;
+    let dtos: Vec<OrderDto> = found.into_iter().map(OrderDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+#[get("/order/{id}/group/{group_id}")]
+pub async fn get_order_by_id(
+    orders: web::Data<dyn OrderRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let order = OrderService::get_order(member.group_id, id, orders.get_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(order.version()))
+        .json(ApiResponse::new(200, vec![OrderDto::from(order)], "")))
+}
+
+/// Starts a draft order for the group.
+#[post("/order/group/{group_id}")]
+pub async fn create_order(
+    orders: web::Data<dyn OrderRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let order = OrderService::create_order(member.user.user_id, member.group_id, orders.get_ref()).await?;
+    Ok(HttpResponse::Created()
+        .insert_header(etag(order.version()))
+        .json(ApiResponse::new(201, vec![OrderDto::from(order)], "")))
+}
+
+/// Opens an ordering round for a day; it locks by itself at the cut-off.
+#[post("/order/round/group/{group_id}")]
+pub async fn open_round(
+    orders: web::Data<dyn OrderRepository>,
+    events: web::Data<WsServer>,
+    _req: HttpRequest,
+    _auth: Authorized<perm::GroupAdmin>,
+    member: GroupMember,
+    payload: web::Json<OpenRoundDto>,
+) -> Result<impl Responder> {
+    let order =
+        OrderService::open_round(member.user.user_id, member.group_id, payload.into_inner(), orders.get_ref()).await?;
+    events.broadcast(order.group_id, GroupEvent::order_status(&order));
+    Ok(HttpResponse::Created()
+        .insert_header(etag(order.version()))
+        .json(ApiResponse::new(201, vec![OrderDto::from(order)], "")))
+}
+
+#[get("/order/{id}/group/{group_id}/item")]
+pub async fn get_order_items(
+    orders: web::Data<dyn OrderRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let lines = OrderService::get_items(member.group_id, id, orders.get_ref()).await?;
+    let dtos: Vec<OrderDetailsDto> = lines.into_iter().map(OrderDetailsDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+/// Adds an item for the calling member while the order is open and before its cut-off.
+#[post("/order/{id}/group/{group_id}/item")]
+pub async fn add_order_item(
+    orders: web::Data<dyn OrderRepository>,
+    stocks: web::Data<dyn StockRepository>,
+    _req: HttpRequest,
+    Confirmed(member): Confirmed<GroupMember>,
+    path: web::Path<(i32, i32)>,
+    payload: web::Json<AddOrderItemDto>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let line = OrderService::add_item(
+        member.user.user_id,
+        member.group_id,
+        id,
+        payload.into_inner(),
+        orders.get_ref(),
+        stocks.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(ApiResponse::new(201, vec![OrderDetailsDto::from(line)], "")))
+}
+
+/// Removes one of the calling member's own items, under the same rules as adding one.
+#[delete("/order/{id}/group/{group_id}/item/{line_id}")]
+pub async fn remove_order_item(
+    orders: web::Data<dyn OrderRepository>,
+    customers: web::Data<dyn CustomerRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _, line_id) = path.into_inner();
+    let lines = OrderService::remove_item(
+        member.user.user_id,
+        member.group_id,
+        id,
+        line_id,
+        orders.get_ref(),
+        customers.get_ref(),
+    )
+    .await?;
+    let dtos: Vec<OrderDetailsDto> = lines.into_iter().map(OrderDetailsDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+/// Opens a draft order so members can add to it.
+#[post("/order/{id}/group/{group_id}/open")]
+pub async fn open_order(
+    orders: web::Data<dyn OrderRepository>,
+    events: web::Data<WsServer>,
+    _req: HttpRequest,
+    roles: web::Data<dyn RoleRepository>,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    transition(orders, events, roles, member, expected, path, OrderStatus::Open).await
+}
+
+/// Closes an open order to further changes.
+#[post("/order/{id}/group/{group_id}/lock")]
+pub async fn lock_order(
+    orders: web::Data<dyn OrderRepository>,
+    events: web::Data<WsServer>,
+    _req: HttpRequest,
+    roles: web::Data<dyn RoleRepository>,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    transition(orders, events, roles, member, expected, path, OrderStatus::Locked).await
+}
+
+/// Records that the suppliers delivered a locked order.
+#[post("/order/{id}/group/{group_id}/fulfill")]
+pub async fn fulfill_order(
+    orders: web::Data<dyn OrderRepository>,
+    events: web::Data<WsServer>,
+    _req: HttpRequest,
+    roles: web::Data<dyn RoleRepository>,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    transition(orders, events, roles, member, expected, path, OrderStatus::Fulfilled).await
+}
+
+/// Bills a fulfilled order, one invoice per supplier, and marks it invoiced.
+#[allow(clippy::too_many_arguments)]
+#[post("/order/{id}/group/{group_id}/invoice")]
+pub async fn invoice_order(
+    orders: web::Data<dyn OrderRepository>,
+    meals: web::Data<dyn MealRepository>,
+    suppliers: web::Data<dyn SupplierRepository>,
+    stocks: web::Data<dyn StockRepository>,
+    customers: web::Data<dyn CustomerRepository>,
+    users: web::Data<dyn UserRepository>,
+    events: web::Data<WsServer>,
+    _req: HttpRequest,
+    _auth: Authorized<perm::InvoiceCreate>,
+    Confirmed(member): Confirmed<GroupMember>,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+    payload: web::Json<InvoiceOrderDto>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let (order, invoices) = OrderService::invoice_order(
+        member.user.user_id,
+        member.group_id,
+        id,
+        expected,
+        payload.into_inner(),
+        orders.get_ref(),
+        meals.get_ref(),
+        suppliers.get_ref(),
+        stocks.get_ref(),
+        customers.get_ref(),
+        users.get_ref(),
+    )
+    .await?;
+    events.broadcast(order.group_id, GroupEvent::order_status(&order));
+    let dtos: Vec<InvoiceWithParticipantsDto> = invoices.into_iter().map(InvoiceWithParticipantsDto::from).collect();
+    Ok(HttpResponse::Created().insert_header(etag(order.version())).json(ApiResponse::new(201, dtos, "")))
+}
+
+#[post("/order/{id}/group/{group_id}/cancel")]
+pub async fn cancel_order(
+    orders: web::Data<dyn OrderRepository>,
+    events: web::Data<WsServer>,
+    _req: HttpRequest,
+    roles: web::Data<dyn RoleRepository>,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    transition(orders, events, roles, member, expected, path, OrderStatus::Cancelled).await
+}
+
+async fn transition(
+    orders: web::Data<dyn OrderRepository>,
+    events: web::Data<WsServer>,
+    roles: web::Data<dyn RoleRepository>,
+    member: GroupMember,
+    expected: Version,
+    path: web::Path<(i32, i32)>,
+    next: OrderStatus,
+) -> Result<HttpResponse> {
+    let (id, _) = path.into_inner();
+    let actor = OrderActor {
+        user_id: member.user.user_id,
+        group_id: member.group_id,
+        can_manage: RoleService::user_has_permission(member.user.user_id, Permission::GroupAdmin, roles.get_ref())
+            .await?,
+    };
+    let order = OrderService::transition(actor, id, expected, next, orders.get_ref()).await?;
+    events.broadcast(order.group_id, GroupEvent::order_status(&order));
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(order.version()))
+        .json(ApiResponse::new(200, vec![OrderDto::from(order)], "")))
+}
+
+pub fn register_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_group_orders);
+    cfg.service(get_order_by_id);
+    cfg.service(create_order);
+    cfg.service(open_round);
+    cfg.service(get_order_items);
+    cfg.service(add_order_item);
+    cfg.service(remove_order_item);
+    cfg.service(open_order);
+    cfg.service(lock_order);
+    cfg.service(fulfill_order);
+    cfg.service(invoice_order);
+    cfg.service(cancel_order);
+}
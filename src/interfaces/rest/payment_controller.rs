@@ -0,0 +1,95 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        payment::{payment_repository::PaymentRepository, payment_service::PaymentService},
+        user::user_repository::UserRepository,
+    },
+    interfaces::{
+        dtos::{
+            payment_dto::{CreatePaymentDto, PaymentDto},
+            response_dto::ApiResponse,
+        },
+        extractors::{auth_user::AuthUser, confirmed::Confirmed, group_member::GroupMember},
+    },
+};
+
+/// Records a payment from the caller to another member of their group, pending the payee's confirmation.
+#[post("/payment")]
+pub async fn create_payment(
+    payments: web::Data<dyn PaymentRepository>,
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<AuthUser>,
+    payload: web::Json<CreatePaymentDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let payment = PaymentService::create_payment(
+        auth.user_id,
+        auth.group_id,
+        payload.into_inner(),
+        payments.get_ref(),
+        users.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(ApiResponse::new(201, vec![PaymentDto::from(payment)], "")))
+}
+
+#[get("/payment/group/{group_id}")]
+pub async fn get_group_payments(
+    payments: web::Data<dyn PaymentRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let payments = PaymentService::get_group_payments(member.group_id, payments.get_ref()).await?;
+    let dtos: Vec<PaymentDto> = payments.into_iter().map(PaymentDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+#[get("/payment/{id}/group/{group_id}")]
+pub async fn get_payment(
+    payments: web::Data<dyn PaymentRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let payment = PaymentService::get_payment(member.group_id, id, payments.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![PaymentDto::from(payment)], "")))
+}
+
+/// The payee confirms the money arrived, which posts it to both balances.
+#[post("/payment/{id}/confirm")]
+pub async fn confirm_payment(
+    payments: web::Data<dyn PaymentRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<AuthUser>,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    let payment =
+        PaymentService::confirm_payment(auth.user_id, auth.group_id, id.into_inner(), payments.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![PaymentDto::from(payment)], "")))
+}
+
+#[post("/payment/{id}/reject")]
+pub async fn reject_payment(
+    payments: web::Data<dyn PaymentRepository>,
+    _req: HttpRequest,
+    Confirmed(auth): Confirmed<AuthUser>,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    let payment =
+        PaymentService::reject_payment(auth.user_id, auth.group_id, id.into_inner(), payments.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![PaymentDto::from(payment)], "")))
+}
+
+pub fn register_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_payment);
+    cfg.service(get_group_payments);
+    cfg.service(get_payment);
+    cfg.service(confirm_payment);
+    cfg.service(reject_payment);
+}
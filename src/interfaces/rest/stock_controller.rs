@@ -0,0 +1,124 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
+
+use crate::{
+    application::error::Result,
+    domain::{
+        product::product_repository::ProductRepository,
+        stock::{stock_repository::StockRepository, stock_service::StockService},
+        supplier::supplier_repository::SupplierRepository,
+    },
+    interfaces::{
+        dtos::{
+            response_dto::ApiResponse,
+            stock_dto::{AdjustStockDto, CreateStockDto, StockAdjustmentDto, StockDto},
+        },
+        extractors::{
+            authorized::{perm, Authorized},
+            confirmed::Confirmed,
+            group_member::GroupMember,
+            if_match::{etag, IfMatch},
+        },
+    },
+};
+
+/// What the group has in stock: every lot with something left.
+#[get("/stock/group/{group_id}")]
+pub async fn get_inventory(
+    stocks: web::Data<dyn StockRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let found = StockService::get_inventory(member.group_id, stocks.get_ref()).await?;
+    let dtos: Vec<StockDto> = found.into_iter().map(StockDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+#[get("/stock/{id}/group/{group_id}")]
+pub async fn get_stock(
+    stocks: web::Data<dyn StockRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let stock = StockService::get_stock(member.group_id, id, stocks.get_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(stock.version()))
+        .json(ApiResponse::new(200, vec![StockDto::from(stock)], "")))
+}
+
+/// Records a purchase as a new lot.
+#[post("/stock/group/{group_id}")]
+pub async fn add_purchase(
+    stocks: web::Data<dyn StockRepository>,
+    products: web::Data<dyn ProductRepository>,
+    suppliers: web::Data<dyn SupplierRepository>,
+    _req: HttpRequest,
+    _auth: Authorized<perm::StockPurchase>,
+    Confirmed(member): Confirmed<GroupMember>,
+    payload: web::Json<CreateStockDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let stock = StockService::add_purchase(
+        member.group_id,
+        payload.into_inner(),
+        stocks.get_ref(),
+        products.get_ref(),
+        suppliers.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Created()
+        .insert_header(etag(stock.version()))
+        .json(ApiResponse::new(201, vec![StockDto::from(stock)], "")))
+}
+
+/// Corrects what is left of a lot after a count.
+#[post("/stock/{id}/group/{group_id}/adjust")]
+pub async fn adjust_stock(
+    stocks: web::Data<dyn StockRepository>,
+    _req: HttpRequest,
+    _auth: Authorized<perm::GroupAdmin>,
+    member: GroupMember,
+    IfMatch(expected): IfMatch,
+    path: web::Path<(i32, i32)>,
+    payload: web::Json<AdjustStockDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let (id, _) = path.into_inner();
+    let (stock, _) = StockService::adjust(
+        member.user.user_id,
+        member.group_id,
+        id,
+        expected,
+        payload.into_inner(),
+        stocks.get_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(stock.version()))
+        .json(ApiResponse::new(200, vec![StockDto::from(stock)], "")))
+}
+
+#[get("/stock/{id}/group/{group_id}/adjustment")]
+pub async fn get_adjustments(
+    stocks: web::Data<dyn StockRepository>,
+    _req: HttpRequest,
+    member: GroupMember,
+    path: web::Path<(i32, i32)>,
+) -> Result<impl Responder> {
+    let (id, _) = path.into_inner();
+    let adjustments = StockService::get_adjustments(member.group_id, id, stocks.get_ref()).await?;
+    let dtos: Vec<StockAdjustmentDto> = adjustments.into_iter().map(StockAdjustmentDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
+
+pub fn register_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_inventory);
+    cfg.service(get_stock);
+    cfg.service(add_purchase);
+    cfg.service(adjust_stock);
+    cfg.service(get_adjustments);
+}
@@ -1,179 +1,184 @@
-use actix_web::{get, web, HttpRequest, Responder};
-use sqlx::PgPool;
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Responder};
+use validator::Validate;
 
-use crate::{domain::user::user_service::UserService, interfaces::dtos::{response_dto::ApiResponse, user_dto::UserDisplayDto}};
+use crate::{
+    application::error::Result,
+    domain::{
+        active_session::active_session_repository::ActiveSessionRepository,
+        role::{permission::Permission, role_repository::RoleRepository, role_service::RoleService},
+        user::{
+            user_repository::UserRepository,
+            user_service::{UserActor, UserService},
+        },
+    },
+    infrastructure::mailer::Mailer,
+    interfaces::{
+        dtos::{
+            response_dto::ApiResponse,
+            user_dto::{CreateUserDto, UpdateUserDto, UserDto},
+        },
+        extractors::{
+            auth_user::AuthUser,
+            authorized::{perm, Authorized},
+            group_member::GroupMember,
+            if_match::{etag, IfMatch},
+        },
+    },
+};
 
-// #[get("/user")]
-// pub async fn get_all_users(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-// ) -> impl Responder {
-//     match UserService::get_all_users(&data).await {
-//         Ok(user) => {
-//             let dtos: Vec<UserDto> = user.into_iter().map(|user| user.into()).collect();
-//             web::Json(ApiResponse::new(200, dtos, ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+// Lookups are limited to the caller's own group; members of other groups read as not found.
 
-// #[get("/user/{id}")]
-// pub async fn get_user_by_id(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     id: web::Path<i32>,
-// ) -> impl Responder {
-//     match UserService::get_user_by_id(&data, id.into_inner()).await {
-//         Ok(Some(user)) => {
-//             let dto: UserDto = user.into();
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<UserDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+#[get("/user")]
+pub async fn get_all_users(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+) -> Result<impl Responder> {
+    let found = UserService::get_users(auth.group_id, users.get_ref()).await?;
+    let dtos: Vec<UserDto> = found.into_iter().map(UserDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
 
-// #[get("/user/email/{email}")]
-// pub async fn get_user_by_email(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     email: web::Path<String>,
-// ) -> impl Responder {
-//     match UserService::get_user_by_email(&data, &email.into_inner()).await {
-//         Ok(Some(user)) => {
-//             let dto: UserDto = user.into();
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<UserDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+#[get("/user/{id}")]
+pub async fn get_user_by_id(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    let user = UserService::get_user_by_id(auth.group_id, id.into_inner(), users.get_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(user.version()))
+        .json(ApiResponse::new(200, vec![UserDto::from(user)], "")))
+}
 
-// #[get("/user/display/{display_id}")]
-// pub async fn get_user_by_display_id(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     display_id: web::Path<String>,
-// ) -> impl Responder {
-//     match UserService::get_user_by_display_id(&data, &display_id.into_inner()).await {
-//         Ok(Some(user)) => {
-//             let dto: UserDto = user.into();
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<UserDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+#[get("/user/email/{email}")]
+pub async fn get_user_by_email(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+    email: web::Path<String>,
+) -> Result<impl Responder> {
+    let user = UserService::get_user_by_email(auth.group_id, &email.into_inner(), users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![UserDto::from(user)], "")))
+}
 
-// #[get("/user/search/{name}")]
-// pub async fn get_users_by_name(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     name: web::Path<String>,
-// ) -> impl Responder {
-//     match UserService::get_user_by_name(&data, &name.into_inner()).await {
-//         Ok(user) => {
-//             let dtos: Vec<UserDto> = user.into_iter().map(|user| user.into()).collect();
-//             web::Json(ApiResponse::new(200, dtos, ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+#[get("/user/display/{display_id}")]
+pub async fn get_user_by_display_id(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+    display_id: web::Path<String>,
+) -> Result<impl Responder> {
+    let user = UserService::get_user_by_display_id(auth.group_id, &display_id.into_inner(), users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, vec![UserDto::from(user)], "")))
+}
+
+#[get("/user/search/{name}")]
+pub async fn get_users_by_name(
+    users: web::Data<dyn UserRepository>,
+    _req: HttpRequest,
+    auth: AuthUser,
+    name: web::Path<String>,
+) -> Result<impl Responder> {
+    let found = UserService::get_users_by_name(auth.group_id, &name.into_inner(), users.get_ref()).await?;
+    let dtos: Vec<UserDto> = found.into_iter().map(UserDto::from).collect();
+    Ok(web::Json(ApiResponse::new(200, dtos, "")))
+}
 
 #[get("/user/indebt/{group_id}")]
 pub async fn get_user_indebt(
-    data: web::Data<PgPool>,
+    users: web::Data<dyn UserRepository>,
     _req: HttpRequest,
-    group_id: web::Path<i32>,
-) -> impl Responder {
-    match UserService::get_user_indebt(group_id.into_inner(), data.get_ref().clone()).await {
-        Ok(users) => {
-            web::Json(ApiResponse::new(200, users, ""))
-        }
-        Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDisplayDto>::new(), e.to_string())),
-    }
+    member: GroupMember,
+) -> Result<impl Responder> {
+    let users = UserService::get_user_indebt(member.group_id, users.get_ref()).await?;
+    Ok(web::Json(ApiResponse::new(200, users, "")))
+}
+
+#[post("/user")]
+pub async fn create_user(
+    users: web::Data<dyn UserRepository>,
+    roles: web::Data<dyn RoleRepository>,
+    _req: HttpRequest,
+    auth: Authorized<perm::UserManage>,
+    payload: web::Json<CreateUserDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
+
+    let user =
+        UserService::create_user(auth.user.group_id, payload.into_inner(), users.get_ref(), roles.get_ref()).await?;
+    Ok(HttpResponse::Created()
+        .insert_header(etag(user.version()))
+        .json(ApiResponse::new(201, vec![UserDto::from(user)], "")))
 }
 
-// #[post("/user")]
-// pub async fn create_user(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     payload: web::Json<CreateUserDto>,
-// ) -> impl Responder {
-//     match UserService::create_user(
-//         &data,
-//         payload.name.clone(),
-//         payload.password.clone(),
-//         payload.email.clone(),
-//         payload.email_confirmed,
-//         payload.user_display_id.clone(),
-//         payload.balance,
-//         payload.is_active,
-//         payload.role_id,
-//         payload.group_id,
-//     )
-//     .await
-//     {
-//         Ok(user) => {
-//             let dto: UserDto = user.into();
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+#[patch("/user/{id}")]
+#[allow(clippy::too_many_arguments)]
+pub async fn update_user(
+    users: web::Data<dyn UserRepository>,
+    roles: web::Data<dyn RoleRepository>,
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    mailer: web::Data<dyn Mailer>,
+    auth: AuthUser,
+    IfMatch(expected): IfMatch,
+    id: web::Path<i32>,
+    payload: web::Json<UpdateUserDto>,
+) -> Result<impl Responder> {
+    payload.validate()?;
 
-// #[put("/user/{id}")]
-// pub async fn update_user(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     id: web::Path<i32>,
-//     payload: web::Json<CreateUserDto>,
-// ) -> impl Responder {
-//     match UserService::update_user(
-//         &data,
-//         id.into_inner(),
-//         Some(payload.name.clone()),
-//         Some(payload.password.clone()),
-//         Some(payload.email.clone()),
-//         Some(payload.email_confirmed),
-//         Some(payload.user_display_id.clone()),
-//         Some(payload.balance),
-//         Some(payload.is_active),
-//         Some(payload.role_id),
-//         Some(payload.group_id),
-//     )
-//     .await
-//     {
-//         Ok(Some(user)) => {
-//             let dto: UserDto = user.into();
-//             web::Json(ApiResponse::new(200, vec![dto], ""))
-//         }
-//         Ok(None) => web::Json(ApiResponse::new(404, Vec::<UserDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+    let actor = UserActor {
+        user_id: auth.user_id,
+        session_id: auth.session_id,
+        group_id: auth.group_id,
+        can_manage: RoleService::user_has_permission(auth.user_id, Permission::UserManage, roles.get_ref()).await?,
+    };
+    let user = UserService::update_user(
+        actor,
+        id.into_inner(),
+        expected,
+        payload.into_inner(),
+        mailer.get_ref(),
+        users.get_ref(),
+        roles.get_ref(),
+        sessions.get_ref(),
+    )
+    .await?;
 
-// #[delete("/user/{id}")]
-// pub async fn delete_user(
-//     data: web::Data<PgPool>,
-//     _req: HttpRequest,
-//     id: web::Path<i32>,
-// ) -> impl Responder {
-//     match UserService::delete_user(&data, id.into_inner()).await {
-//         Ok(true) => web::Json(ApiResponse::new(200, Vec::<UserDto>::new(), "")),
-//         Ok(false) => web::Json(ApiResponse::new(404, Vec::<UserDto>::new(), "Not found")),
-//         Err(e) => web::Json(ApiResponse::new(500, Vec::<UserDto>::new(), e.to_string())),
-//     }
-// }
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(user.version()))
+        .json(ApiResponse::new(200, vec![UserDto::from(user)], "")))
+}
+
+/// Deactivates rather than deletes, see [`UserService::deactivate_user`].
+#[delete("/user/{id}")]
+pub async fn delete_user(
+    users: web::Data<dyn UserRepository>,
+    sessions: web::Data<dyn ActiveSessionRepository>,
+    _req: HttpRequest,
+    auth: Authorized<perm::UserManage>,
+    IfMatch(expected): IfMatch,
+    id: web::Path<i32>,
+) -> Result<impl Responder> {
+    let actor = UserActor {
+        user_id: auth.user.user_id,
+        session_id: auth.user.session_id,
+        group_id: auth.user.group_id,
+        can_manage: true,
+    };
+    let user =
+        UserService::deactivate_user(actor, id.into_inner(), expected, users.get_ref(), sessions.get_ref()).await?;
+    Ok(HttpResponse::Ok().insert_header(etag(user.version())).json(ApiResponse::new(200, Vec::<()>::new(), "")))
+}
 
 pub fn register_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_user_indebt);
-    // cfg.service(get_all_users);
-    // cfg.service(get_user_by_id);
-    // cfg.service(get_user_by_email);
-    // cfg.service(get_user_by_display_id);
-    // cfg.service(get_users_by_name);
-    // cfg.service(create_user);
-    // cfg.service(update_user);
-    // cfg.service(delete_user);
+    cfg.service(get_all_users);
+    cfg.service(get_user_by_id);
+    cfg.service(get_user_by_email);
+    cfg.service(get_user_by_display_id);
+    cfg.service(get_users_by_name);
+    cfg.service(create_user);
+    cfg.service(update_user);
+    cfg.service(delete_user);
 }
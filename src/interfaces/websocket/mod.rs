@@ -1,3 +1,9 @@
 // src/interfaces/websocket/mod.rs
+use actix_web::web;
+
 pub mod ws_session;
-pub mod ws_server;
\ No newline at end of file
+pub mod ws_server;
+
+pub fn register_route(cfg: &mut web::ServiceConfig) {
+    ws_session::register_routes(cfg);
+}
@@ -0,0 +1,52 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::domain::order::{order_entity::Order, order_status::OrderStatus};
+
+/// How many events a slow session may fall behind before it starts missing them.
+const CAPACITY: usize = 256;
+
+/// Something that happened in a group, pushed as JSON to the members connected to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GroupEvent {
+    OrderStatusChanged { order_id: i32, status: OrderStatus },
+}
+
+impl GroupEvent {
+    pub fn order_status(order: &Order) -> Self {
+        GroupEvent::OrderStatusChanged {
+            order_id: order.id,
+            status: order.status,
+        }
+    }
+}
+
+/// Fans group events out to every open websocket session; each session keeps its own group's.
+/// Shared with the app as `web::Data<WsServer>`.
+pub struct WsServer {
+    sender: broadcast::Sender<(i32, GroupEvent)>,
+}
+
+impl WsServer {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    /// Sends `event` to the sessions of `group_id`; nothing happens when nobody is connected.
+    pub fn broadcast(&self, group_id: i32, event: GroupEvent) {
+        let _ = self.sender.send((group_id, event));
+    }
+
+    /// Events for every group, from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<(i32, GroupEvent)> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for WsServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
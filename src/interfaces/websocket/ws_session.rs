@@ -0,0 +1,76 @@
+use actix_web::{
+    get,
+    http::header::{self, HeaderValue},
+    web, HttpRequest, HttpResponse,
+};
+use actix_ws::{Message, MessageStream, Session};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::{
+    application::error::{Error, Result},
+    interfaces::{
+        extractors::{
+            auth_user::{websocket_bearer_token, WEBSOCKET_BEARER_PROTOCOL},
+            group_member::GroupMember,
+        },
+        websocket::ws_server::{GroupEvent, WsServer},
+    },
+};
+
+/// Upgrades to a websocket that receives the group's [`GroupEvent`]s until either side closes it.
+/// Messages from the client are ignored apart from pings and close. Browsers authenticate by offering
+/// their token as a subprotocol, which is accepted as [`WEBSOCKET_BEARER_PROTOCOL`].
+#[get("/ws/group/{group_id}")]
+pub async fn group_events(
+    server: web::Data<WsServer>,
+    req: HttpRequest,
+    body: web::Payload,
+    member: GroupMember,
+) -> Result<HttpResponse> {
+    let (mut response, session, stream) =
+        actix_ws::handle(&req, body).map_err(|error| Error::validation(error.to_string()))?;
+    // a browser drops the connection unless one of the subprotocols it offered is picked
+    if websocket_bearer_token(&req).is_some() {
+        response
+            .headers_mut()
+            .insert(header::SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(WEBSOCKET_BEARER_PROTOCOL));
+    }
+    actix_web::rt::spawn(run(session, stream, server.subscribe(), member.group_id));
+    Ok(response)
+}
+
+async fn run(mut session: Session, mut stream: MessageStream, mut events: Receiver<(i32, GroupEvent)>, group_id: i32) {
+    loop {
+        tokio::select! {
+            message = stream.recv() => match message {
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(reason))) => {
+                    let _ = session.close(reason).await;
+                    return;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => break,
+            },
+            event = events.recv() => match event {
+                Ok((event_group, event)) if event_group == group_id => {
+                    let text = serde_json::to_string(&event).expect("group events serialize");
+                    if session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                // a lagging client just misses events; it can always refetch what it shows
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    let _ = session.close(None).await;
+}
+
+pub fn register_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(group_events);
+}
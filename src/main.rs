@@ -1,12 +1,19 @@
-mod domain;
-mod infrastructure;
-mod interfaces;
-
 use actix_cors::Cors;
 use actix_web::{http, App, HttpServer};
-use interfaces::rest;
+use backend::{
+    infrastructure::{
+        jwt::JwtConfig,
+        mailer::mailer_from_env,
+        repositories_impl::{order_repository_impl::PgOrderRepository, register_repositories},
+        round_closer::spawn_round_closer,
+    },
+    interfaces::{rest, websocket, websocket::ws_server::WsServer},
+};
 use sqlx::PgPool;
-use std::env;
+use std::{env, sync::Arc, time::Duration};
+
+/// How often open ordering rounds are checked against their cut-off.
+const ROUND_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 
 #[actix_web::main]
@@ -14,22 +21,34 @@ async fn main() -> std::io::Result<()> {
     dotenvy::dotenv().ok();
     let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let db = PgPool::connect(&db_url).await.unwrap();
+    let jwt = JwtConfig::from_env();
+    let mailer = actix_web::web::Data::from(mailer_from_env());
+    let events = Arc::new(WsServer::new());
+
+    spawn_round_closer(Arc::new(PgOrderRepository::new(db.clone())), events.clone(), ROUND_CHECK_INTERVAL);
+    let events = actix_web::web::Data::from(events);
 
     HttpServer::new(move || {
         let cors = Cors::default()
             .allowed_origin("http://localhost:4200")
-            .allowed_methods(vec!["GET", "POST", "OPTIONS"])
+            .allowed_methods(vec!["GET", "POST", "PATCH", "DELETE", "OPTIONS"])
             .allowed_headers(vec![
                 http::header::AUTHORIZATION,
                 http::header::ACCEPT,
                 http::header::CONTENT_TYPE,
+                http::header::IF_MATCH,
             ])
+            .expose_headers(vec![http::header::ETAG])
             .max_age(3600);
 
         App::new()
-            .app_data(actix_web::web::Data::new(db.clone())) // Share the db connection
+            .app_data(actix_web::web::Data::new(jwt.clone()))
+            .app_data(mailer.clone())
+            .app_data(events.clone())
             .wrap(cors)
+            .configure(|cfg| register_repositories(cfg, &db)) // Share the db connection
             .configure(rest::register_route)
+            .configure(websocket::register_route)
     })
     .bind("127.0.0.1:8080")?
     .run()
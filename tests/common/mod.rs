@@ -0,0 +1,306 @@
+// each test binary uses a different subset of these helpers
+#![allow(dead_code)]
+
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse},
+    http::header,
+    test, web, App,
+};
+use backend::{
+    domain::{
+        group::group_entity::Group, invoice::invoice_entity::Invoice,
+        invoice_details::invoice_details_entity::InvoiceDetails,
+        ledger::ledger_entity::{LedgerEntry, LedgerKind, LedgerTransaction},
+        meal::meal_entity::{Meal, MealProduct},
+        product::product_entity::Product,
+        role::{permission::Permission, role_entity::Role},
+        stock::stock_entity::{Stock, Unit},
+        supplier::supplier_entity::Supplier, user::user_entity::User,
+    },
+    infrastructure::{
+        in_memory::{self, InMemoryDatabase},
+        jwt::JwtConfig,
+        mailer::{LogMailer, Mailer},
+        repositories_impl::register_repositories,
+    },
+    interfaces::{rest, websocket, websocket::ws_server::WsServer},
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+pub const PASSWORD: &str = "password123";
+
+pub const ADMIN_ROLE_ID: i32 = 1;
+
+/// Report filters written to break out of a SQL string literal; every one must be refused.
+pub const MALICIOUS_DATES: [&str; 4] = [
+    "2025-01-01' OR '1'='1",
+    "2025-01-01'; DROP TABLE invoice; --",
+    "2025-01-01' AND is_deleted = true AND '1'='1",
+    "2025-12-31'::date OR true OR created_date::date = '",
+];
+
+/// The app wired to the Postgres repositories over `pool`.
+pub fn app(pool: PgPool) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    app_with(move |cfg| {
+        register_repositories(cfg, &pool);
+        cfg.app_data(web::Data::new(WsServer::new()));
+    })
+}
+
+/// The app wired to in-memory repositories over `db`; no database needed.
+pub fn in_memory_app(db: Arc<InMemoryDatabase>) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    in_memory_app_with_events(db, Arc::new(WsServer::new()))
+}
+
+/// [`in_memory_app`] broadcasting group events through `events`, so tests can subscribe to them.
+pub fn in_memory_app_with_events(db: Arc<InMemoryDatabase>, events: Arc<WsServer>) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    app_with(move |cfg| {
+        in_memory::register_repositories(cfg, &db);
+        cfg.app_data(web::Data::from(events));
+    })
+}
+
+fn app_with(repositories: impl FnOnce(&mut web::ServiceConfig)) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let mailer: Arc<dyn Mailer> = Arc::new(LogMailer);
+
+    App::new()
+        .app_data(web::Data::new(JwtConfig::new(
+            "test-secret",
+            Duration::minutes(15),
+            Duration::days(1),
+        )))
+        .app_data(web::Data::from(mailer))
+        .configure(repositories)
+        .configure(rest::register_route)
+        .configure(websocket::register_route)
+}
+
+/// Logs in through `/auth/login` and returns the bearer access token.
+pub async fn login<S, B>(app: &S, email: &str) -> String
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(serde_json::json!({ "email": email, "password": PASSWORD }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(app, req).await;
+
+    body["data"][0]["access_token"]
+        .as_str()
+        .unwrap_or_else(|| panic!("login failed for {}: {}", email, body))
+        .to_string()
+}
+
+pub fn bearer(token: &str) -> (header::HeaderName, String) {
+    (header::AUTHORIZATION, format!("Bearer {}", token))
+}
+
+/// The `If-Match` header for an `ETag` the API handed out; seeded users and meals are at `"1"`.
+pub fn if_match(etag: &str) -> (header::HeaderName, String) {
+    (header::IF_MATCH, etag.to_string())
+}
+
+pub fn etag<B>(resp: &ServiceResponse<B>) -> String {
+    resp.headers()
+        .get(header::ETAG)
+        .expect("response has an ETag")
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+pub fn user(id: i32, name: &str, balance: i64, is_active: bool, group_id: i32) -> User {
+    User {
+        id,
+        name: name.to_string(),
+        // stored as plaintext like the seed script; the first login upgrades it to a hash
+        password: PASSWORD.to_string(),
+        email: format!("{}@example.com", name.to_lowercase()),
+        email_confirmed: true,
+        user_display_id: name.to_uppercase(),
+        balance,
+        is_active,
+        role_id: 2,
+        group_id,
+        version: 1,
+    }
+}
+
+/// A single piece bought at `price`, all or nothing of it left.
+pub fn stock(id: i32, price: i64, consumed: bool, product_id: i32) -> Stock {
+    Stock {
+        id,
+        price,
+        consumed,
+        product_id,
+        supplier_id: None,
+        unit: Unit::Piece,
+        quantity: Decimal::ONE,
+        remaining: if consumed { Decimal::ZERO } else { Decimal::ONE },
+        purchased_date: at_noon(2025, 1, 1),
+        version: 1,
+    }
+}
+
+fn at_noon(year: i32, month: u32, day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .expect("valid date")
+}
+
+fn opening_entry(id: i32, user_id: Option<i32>, amount: i64) -> LedgerEntry {
+    LedgerEntry { id, transaction_id: 1, user_id, amount, supplier_id: None, customer_id: None }
+}
+
+/// In-memory counterpart of the `tenancy` and `invoices` fixtures.
+///
+/// Group 1 "Alpha" holds Alice (-300), Dave (500, supplier 1) and the deactivated Erin;
+/// group 2 "Beta" holds Bob and the Apple product. Meal 1 "Sandwich" is Bread then Cheese;
+/// invoice 1 (500) serves it and is live, invoice 2 is deleted. Stock 3 (Bread, 120) and
+/// 4 (Cheese, 181) are still available. The ledger holds the balances as one opening adjustment.
+pub fn seeded_database() -> Arc<InMemoryDatabase> {
+    let db = Arc::new(InMemoryDatabase::new());
+
+    {
+        let mut state = db.state();
+
+        state.groups = vec![
+            Group { id: 1, name: "Alpha".to_string(), is_public: false },
+            Group { id: 2, name: "Beta".to_string(), is_public: false },
+        ];
+        state.roles = vec![
+            Role { id: 1, name: "Admin".to_string() },
+            Role { id: 2, name: "User".to_string() },
+            Role { id: 3, name: "Manager".to_string() },
+        ];
+        // same defaults as the role_permission migration
+        state.role_permissions = Permission::ALL.iter().map(|permission| (1, *permission)).collect();
+        state.role_permissions.extend([
+            (2, Permission::InvoiceCreate),
+            (3, Permission::InvoiceCreate),
+            (3, Permission::InvoiceDelete),
+            (3, Permission::GroupAdmin),
+            (3, Permission::StockPurchase),
+        ]);
+        state.users = vec![
+            user(1, "Alice", -300, true, 1),
+            user(2, "Dave", 500, true, 1),
+            user(3, "Erin", -1000, false, 1),
+            user(4, "Bob", 0, true, 2),
+        ];
+        state.products = vec![
+            Product { id: 1, name: "Bread".to_string(), group_id: 1 },
+            Product { id: 2, name: "Cheese".to_string(), group_id: 1 },
+            Product { id: 3, name: "Apple".to_string(), group_id: 2 },
+        ];
+        state.stocks = vec![
+            stock(1, 150, true, 1),
+            stock(2, 350, true, 2),
+            stock(3, 120, false, 1),
+            stock(4, 181, false, 2),
+        ];
+        state.meals = vec![Meal { id: 1, name: "Sandwich".into(), description: String::new(), group_id: 1, version: 1 }];
+        state.meal_products = vec![
+            MealProduct { meal_id: 1, product_id: 1, position: 0 },
+            MealProduct { meal_id: 1, product_id: 2, position: 1 },
+        ];
+        state.suppliers = vec![Supplier { id: 1, balance: 0, user_id: 2 }];
+        state.invoices = vec![
+            Invoice {
+                id: 1,
+                price: 500,
+                is_deleted: false,
+                deleted_by: 0,
+                created_date: at_noon(2025, 3, 10),
+                last_modification_date: at_noon(2025, 3, 10),
+                meal_id: 1,
+                group_id: 1,
+                supplier_id: 1,
+                order_id: None,
+            },
+            Invoice {
+                id: 2,
+                price: 900,
+                is_deleted: true,
+                deleted_by: 1,
+                created_date: at_noon(2025, 3, 11),
+                last_modification_date: at_noon(2025, 3, 11),
+                meal_id: 1,
+                group_id: 1,
+                supplier_id: 1,
+                order_id: None,
+            },
+        ];
+        state.invoice_details = vec![
+            InvoiceDetails { id: 1, invoice_id: 1, stock_id: 1, quantity: Decimal::ONE, cost: 150 },
+            InvoiceDetails { id: 2, invoice_id: 1, stock_id: 2, quantity: Decimal::ONE, cost: 350 },
+            InvoiceDetails { id: 3, invoice_id: 2, stock_id: 1, quantity: Decimal::ONE, cost: 150 },
+        ];
+        // the seeded balances, booked against the group's account
+        state.ledger_transactions = vec![LedgerTransaction {
+            id: 1,
+            kind: LedgerKind::Adjustment,
+            description: "Opening balance".to_string(),
+            group_id: 1,
+            invoice_id: None,
+            payment_id: None,
+            created_date: at_noon(2025, 3, 1),
+        }];
+        state.ledger_entries = vec![
+            opening_entry(1, Some(1), -300),
+            opening_entry(2, Some(2), 500),
+            opening_entry(3, Some(3), -1000),
+            opening_entry(4, None, 800),
+        ];
+    }
+
+    db
+}
+
+/// The cached balance of the user in `db`.
+pub fn balance(db: &InMemoryDatabase, user_id: i32) -> i64 {
+    db.state().users.iter().find(|user| user.id == user_id).unwrap().balance
+}
+
+/// Whether the stock row in `db` is used up.
+pub fn consumed(db: &InMemoryDatabase, stock_id: i32) -> bool {
+    db.state().stocks.iter().find(|stock| stock.id == stock_id).unwrap().consumed
+}
@@ -0,0 +1,104 @@
+//! Optimistic concurrency on updates: the If-Match header against the in-memory repositories.
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+
+#[actix_web::test]
+async fn invoice_edit_needs_the_current_version() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [{ "user_id": 1 }] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created = common::etag(&resp);
+
+    let edit = |etag: Option<&str>| {
+        let req = test::TestRequest::patch()
+            .uri("/invoice/3")
+            .insert_header(common::bearer(&token))
+            .set_json(json!({ "supplier_id": 1 }));
+        match etag {
+            Some(etag) => req.insert_header(common::if_match(etag)).to_request(),
+            None => req.to_request(),
+        }
+    };
+
+    assert_eq!(test::call_service(&app, edit(None)).await.status(), StatusCode::PRECONDITION_REQUIRED);
+    assert_eq!(test::call_service(&app, edit(Some("soon"))).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, edit(Some("\"1\""))).await.status(), StatusCode::PRECONDITION_FAILED);
+
+    let resp = test::call_service(&app, edit(Some(&created))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let edited = common::etag(&resp);
+    assert_ne!(edited, created);
+
+    // a second client still holding the old version loses
+    assert_eq!(test::call_service(&app, edit(Some(&created))).await.status(), StatusCode::PRECONDITION_FAILED);
+
+    let req = test::TestRequest::get().uri("/invoice/3/group/1").insert_header(common::bearer(&token)).to_request();
+    assert_eq!(common::etag(&test::call_service(&app, req).await), edited);
+}
+
+#[actix_web::test]
+async fn user_update_with_a_stale_version_is_rejected() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let rename = |etag: &str, name: &str| {
+        test::TestRequest::patch()
+            .uri("/user/1")
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .set_json(json!({ "name": name }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, rename("\"1\"", "Alicia")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::etag(&resp), "\"2\"");
+
+    let resp = test::call_service(&app, rename("W/\"1\"", "Ally")).await;
+    assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+
+    let req = test::TestRequest::get().uri("/user/1").insert_header(common::bearer(&token)).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(common::etag(&resp), "\"2\"");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["name"], "Alicia");
+}
+
+#[actix_web::test]
+async fn meal_changes_bump_its_version() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let remove = |etag: &str, product_id: i32| {
+        test::TestRequest::delete()
+            .uri(&format!("/meal/1/group/1/product/{}", product_id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, remove("\"2\"", 1)).await;
+    assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+
+    let resp = test::call_service(&app, remove("\"1\"", 1)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::etag(&resp), "\"2\"");
+
+    let req = test::TestRequest::post()
+        .uri("/meal/1/group/1/product")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "product_id": 1 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::PRECONDITION_FAILED);
+}
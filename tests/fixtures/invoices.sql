@@ -0,0 +1,45 @@
+INSERT INTO "group" (id, name, is_public)
+VALUES (1, 'Alpha', false);
+
+INSERT INTO role (id, name)
+VALUES (1, 'Admin'),
+    (2, 'User'),
+    (3, 'Manager');
+
+INSERT INTO "user" (id, name, balance, is_active, role_id, group_id, password, email, email_confirmed, user_display_id)
+VALUES (1, 'Alice', 0, true, 2, 1, 'password123', 'alice@example.com', true, 'ALC01'),
+    (2, 'Dave', 0, true, 2, 1, 'password123', 'dave@example.com', true, 'DAV01');
+
+INSERT INTO role_permission (role_id, permission)
+VALUES (2, 'invoice:create');
+
+INSERT INTO product (id, name, group_id)
+VALUES (1, 'Bread', 1),
+    (2, 'Cheese', 1),
+    (3, 'Ham', 1);
+
+INSERT INTO stock (id, price, consumed, product_id)
+VALUES (1, 150, true, 1),
+    (2, 350, true, 2),
+    (3, 200, false, 3);
+
+INSERT INTO meal (id, name, description, group_id)
+VALUES (1, 'Sandwich', '', 1);
+
+INSERT INTO meal_product (meal_id, product_id, position)
+VALUES (1, 1, 0),
+    (1, 2, 1);
+
+INSERT INTO supplier (id, balance, user_id)
+VALUES (1, 0, 2);
+
+INSERT INTO invoice (id, price, is_deleted, deleted_by, created_date, last_modification_date, meal_id, group_id, supplier_id)
+VALUES (1, 500, false, 0, '2025-03-10 12:00:00', '2025-03-10 12:00:00', 1, 1, 1),
+    (2, 900, true, 1, '2025-03-11 12:00:00', '2025-03-11 12:00:00', 1, 1, 1);
+
+INSERT INTO invoice_details (id, invoice_id, stock_id, cost)
+VALUES (1, 1, 1, 150),
+    (2, 1, 2, 350),
+    (3, 2, 1, 150);
+
+SELECT setval('invoice_id_seq', 2), setval('invoice_details_id_seq', 3), setval('stock_id_seq', 3);
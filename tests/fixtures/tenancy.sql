@@ -0,0 +1,13 @@
+INSERT INTO "group" (id, name, is_public)
+VALUES (1, 'Alpha', false),
+    (2, 'Beta', false);
+
+INSERT INTO role (id, name)
+VALUES (1, 'Admin'),
+    (2, 'User'),
+    (3, 'Manager');
+
+INSERT INTO "user" (id, name, balance, is_active, role_id, group_id, password, email, email_confirmed, user_display_id)
+VALUES (1, 'Alice', 0, true, 2, 1, 'password123', 'alice@example.com', true, 'ALC01'),
+    (2, 'Bob', -250, true, 2, 2, 'password123', 'bob@example.com', true, 'BOB01'),
+    (3, 'Carol', 250, true, 2, 2, 'password123', 'carol@example.com', true, 'CRL01');
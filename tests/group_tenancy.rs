@@ -0,0 +1,198 @@
+//! Cross-group access must be refused by the `GroupMember` extractor.
+//!
+//! The `in_memory_` tests need no database. The others run against a throwaway database created by
+//! `sqlx::test`; point `DATABASE_URL` at a Postgres server and run `cargo test -- --ignored`.
+
+mod common;
+
+use actix_web::{http::StatusCode, post, test, HttpResponse};
+use backend::{infrastructure::in_memory::InMemoryDatabase, interfaces::extractors::group_member::GroupMember};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[post("/group/{group_id}/probe")]
+async fn group_write_probe(member: GroupMember) -> HttpResponse {
+    HttpResponse::Ok().json(member.group_id)
+}
+
+/// The seeded in-memory data with Carol, in credit, joining Bob in group 2 as in the fixture.
+fn tenancy_database() -> Arc<InMemoryDatabase> {
+    let db = common::seeded_database();
+    db.state().users[3].balance = -250;
+    db.state().users.push(common::user(5, "Carol", 250, true, 2));
+    db
+}
+
+#[actix_web::test]
+async fn in_memory_member_can_read_own_group_debts() {
+    let app = test::init_service(common::in_memory_app(tenancy_database())).await;
+    let token = common::login(&app, "bob@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/2")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    let names: Vec<&str> = body["data"].as_array().unwrap().iter().map(|u| u["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Bob", "Carol"]);
+}
+
+#[actix_web::test]
+async fn in_memory_reading_another_groups_debts_is_refused() {
+    let app = test::init_service(common::in_memory_app(tenancy_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/2")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn in_memory_reading_another_groups_invoices_is_refused() {
+    let app = test::init_service(common::in_memory_app(tenancy_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/2")
+        .insert_header(common::bearer(&token))
+        .set_json(serde_json::json!({ "start_date": "2020-01-01", "end_date": "2030-01-01" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn in_memory_writing_to_another_group_is_refused() {
+    let app = test::init_service(common::in_memory_app(tenancy_database()).service(group_write_probe)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let own = test::TestRequest::post()
+        .uri("/group/1/probe")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    assert_eq!(test::call_service(&app, own).await.status(), StatusCode::OK);
+
+    let other = test::TestRequest::post()
+        .uri("/group/2/probe")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    assert_eq!(test::call_service(&app, other).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn in_memory_unknown_group_is_refused() {
+    let app = test::init_service(common::in_memory_app(tenancy_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/999")
+        .insert_header(common::bearer(&token))
+        .to_request();
+
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn in_memory_anonymous_group_reads_are_unauthorized() {
+    let app = test::init_service(common::in_memory_app(tenancy_database())).await;
+
+    let req = test::TestRequest::get().uri("/user/indebt/1").to_request();
+
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("tenancy"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn member_can_read_own_group_debts(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+    let token = common::login(&app, "bob@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/2")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    let names: Vec<&str> = body["data"].as_array().unwrap().iter().map(|u| u["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Bob", "Carol"]);
+}
+
+#[sqlx::test(fixtures("tenancy"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn reading_another_groups_debts_is_refused(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/2")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test(fixtures("tenancy"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn reading_another_groups_invoices_is_refused(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/2")
+        .insert_header(common::bearer(&token))
+        .set_json(serde_json::json!({ "start_date": "2020-01-01", "end_date": "2030-01-01" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test(fixtures("tenancy"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn writing_to_another_group_is_refused(pool: PgPool) {
+    let app = test::init_service(common::app(pool).service(group_write_probe)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let own = test::TestRequest::post()
+        .uri("/group/1/probe")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    assert_eq!(test::call_service(&app, own).await.status(), StatusCode::OK);
+
+    let other = test::TestRequest::post()
+        .uri("/group/2/probe")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    assert_eq!(test::call_service(&app, other).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test(fixtures("tenancy"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn unknown_group_is_refused(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/999")
+        .insert_header(common::bearer(&token))
+        .to_request();
+
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test(fixtures("tenancy"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn anonymous_group_reads_are_unauthorized(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+
+    let req = test::TestRequest::get().uri("/user/indebt/1").to_request();
+
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,160 @@
+//! End-to-end tests of the group report endpoints against the in-memory repositories.
+//!
+//! Unlike the `sqlx::test` suites these need no database and run with a plain `cargo test`.
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+
+#[actix_web::test]
+async fn invoice_report_lists_live_invoices_in_range() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-03-01", "end_date": "2025-03-31" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["status"], 200);
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["date"], "2025-03-10");
+    assert_eq!(body["data"][0]["invoices"][0]["invoice_id"], 1);
+    assert_eq!(body["data"][0]["invoices"][0]["price"], 500);
+    assert_eq!(body["data"][0]["invoices"][0]["meal"], "Bread, Cheese");
+    assert_eq!(body["data"][0]["invoices"][0]["supplier"]["name"], "Dave");
+}
+
+#[actix_web::test]
+async fn invoice_report_outside_range_is_empty() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-04-01", "end_date": "2025-04-30" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["status"], 200);
+    assert_eq!(body["data"], json!([]));
+}
+
+#[actix_web::test]
+async fn invoice_report_rejects_reversed_range() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-03-31", "end_date": "2025-03-01" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn invoice_report_of_another_group_is_forbidden() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "bob@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-03-01", "end_date": "2025-03-31" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn invoice_report_rejects_malicious_dates() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    for payload in common::MALICIOUS_DATES {
+        for body in [
+            json!({ "start_date": payload, "end_date": "2025-12-31" }),
+            json!({ "start_date": "2025-01-01", "end_date": payload }),
+        ] {
+            let req = test::TestRequest::get()
+                .uri("/invoice/group/1")
+                .insert_header(common::bearer(&token))
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "payload {:?} was not rejected", payload);
+        }
+    }
+
+    assert_eq!(db.state().invoices.len(), 2);
+}
+
+#[actix_web::test]
+async fn invoice_report_refuses_a_malicious_group_segment() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1%20OR%201=1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-01-01", "end_date": "2025-12-31" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert!(resp.status().is_client_error());
+}
+
+#[actix_web::test]
+async fn indebt_lists_active_members_most_indebted_first() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/1")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["status"], 200);
+    assert_eq!(
+        body["data"],
+        json!([
+            { "id": 1, "name": "Alice", "balance": -300 },
+            { "id": 2, "name": "Dave", "balance": 500 },
+        ])
+    );
+}
+
+#[actix_web::test]
+async fn indebt_of_another_group_is_forbidden() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "bob@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/indebt/1")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn indebt_requires_a_token() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+
+    let req = test::TestRequest::get().uri("/user/indebt/1").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
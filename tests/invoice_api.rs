@@ -0,0 +1,232 @@
+//! `POST /invoice`: stock consumption, cost splitting and balance postings, all or nothing.
+//!
+//! The last test runs against Postgres; point `DATABASE_URL` at a server and run
+//! `cargo test -- --ignored`.
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[actix_web::test]
+async fn create_invoice_consumes_stock_and_moves_balances() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "meal_id": 1, "supplier_id": 1, "stock_ids": [3, 4],
+            "participants": [{ "user_id": 1 }, { "user_id": 2 }]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    assert_eq!(body["data"][0]["id"], 3);
+    assert_eq!(body["data"][0]["price"], 301);
+    assert!(common::consumed(&db, 3) && common::consumed(&db, 4));
+    let details: Vec<_> =
+        db.state().invoice_details.iter().filter(|details| details.invoice_id == 3).map(|d| d.stock_id).collect();
+    assert_eq!(details, [3, 4]);
+
+    // 301 split as 151 + 150; Dave supplied the meal and ate half of it
+    assert_eq!(common::balance(&db, 1), -300 - 151);
+    assert_eq!(common::balance(&db, 2), 500 + 301 - 150);
+    let expected = json!([{ "user_id": 1, "amount": 151 }, { "user_id": 2, "amount": 150 }]);
+    assert_eq!(body["data"][0]["participants"], expected);
+}
+
+#[actix_web::test]
+async fn participants_are_debited_by_split_strategy() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "meal_id": 1, "supplier_id": 1, "stock_ids": [3, 4], "split": "shares",
+            "participants": [{ "user_id": 1, "value": 1 }, { "user_id": 2, "value": 2 }]
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    // 301 / 3 = 100.33 per share: Alice 100.33, Dave 200.67 rounds up
+    let expected = json!([{ "user_id": 1, "amount": 100 }, { "user_id": 2, "amount": 201 }]);
+    assert_eq!(body["data"][0]["participants"], expected);
+    assert_eq!(common::balance(&db, 1), -400);
+    assert_eq!(common::balance(&db, 2), 500 + 301 - 201);
+
+    let req = test::TestRequest::get().uri("/invoice/3/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["price"], 301);
+    assert_eq!(body["data"][0]["participants"], expected);
+}
+
+#[actix_web::test]
+async fn create_invoice_accepts_a_price_override() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "price": 90, "participants": [{ "user_id": 1 }]
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["data"][0]["price"], 90);
+    assert_eq!(common::balance(&db, 1), -390);
+    assert_eq!(common::balance(&db, 2), 590);
+}
+
+#[actix_web::test]
+async fn create_invoice_rejects_unusable_input_without_side_effects() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let payloads = [
+        // stock 1 is consumed already
+        json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3, 1], "participants": [{ "user_id": 1 }] }),
+        json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3, 3], "participants": [{ "user_id": 1 }] }),
+        // Bob is in another group, Erin is deactivated
+        json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [{ "user_id": 4 }] }),
+        json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [{ "user_id": 3 }] }),
+        json!({ "meal_id": 1, "supplier_id": 9, "stock_ids": [3], "participants": [{ "user_id": 1 }] }),
+        json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [] }),
+        json!({
+            "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "split": "exact",
+            "participants": [{ "user_id": 1, "value": 100 }, { "user_id": 2, "value": 10 }]
+        }),
+    ];
+    for payload in payloads {
+        let req = test::TestRequest::post()
+            .uri("/invoice")
+            .insert_header(common::bearer(&token))
+            .set_json(&payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "payload {}", payload);
+    }
+
+    assert!(!common::consumed(&db, 3));
+    assert_eq!(db.state().invoices.len(), 2);
+    assert_eq!(common::balance(&db, 1), -300);
+}
+
+#[actix_web::test]
+async fn unconfirmed_members_cannot_invoice() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    db.state().users[0].email_confirmed = false;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [{ "user_id": 2 }] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+
+    // reading is still allowed
+    let req = test::TestRequest::get().uri("/invoice/1/group/1").insert_header(common::bearer(&token)).to_request();
+    let etag = common::etag(&test::call_service(&app, req).await);
+    let req = test::TestRequest::patch()
+        .uri("/invoice/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .set_json(json!({ "price": 900, "participants": [{ "user_id": 2 }] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+
+    assert!(!common::consumed(&db, 3));
+    assert_eq!(db.state().invoices[0].price, 500);
+    assert_eq!(common::balance(&db, 2), 500);
+}
+
+#[actix_web::test]
+async fn failed_balance_update_leaves_stock_unconsumed() {
+    let db = common::seeded_database();
+    db.state().users[1].balance = i64::MAX - 500;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    // the supplier's credit overflows the balance column
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "price": 3_000_000_000_i64,
+            "participants": [{ "user_id": 1 }, { "user_id": 2 }]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!common::consumed(&db, 3));
+    assert_eq!(db.state().invoices.len(), 2);
+    assert_eq!(common::balance(&db, 2), i64::MAX - 500);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn failed_invoice_rolls_back_in_postgres(pool: PgPool) {
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let create = |price: i64| {
+        test::TestRequest::post()
+            .uri("/invoice")
+            .insert_header(common::bearer(&token))
+            .set_json(json!({
+                "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "price": price, "participants": [{ "user_id": 1 }]
+            }))
+            .to_request()
+    };
+
+    // the supplier's credit overflows the balance column
+    let set_daves_balance = |balance: i64| {
+        sqlx::query("UPDATE \"user\" SET balance = $1 WHERE id = 2").bind(balance).execute(&pool)
+    };
+    set_daves_balance(i64::MAX - 100).await.unwrap();
+    let resp = test::call_service(&app, create(3_000_000_000)).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let consumed: bool = sqlx::query_scalar("SELECT consumed FROM stock WHERE id = 3").fetch_one(&pool).await.unwrap();
+    let invoices: i64 = sqlx::query_scalar("SELECT count(*) FROM invoice").fetch_one(&pool).await.unwrap();
+    let balance: i64 = sqlx::query_scalar("SELECT balance FROM \"user\" WHERE id = 2").fetch_one(&pool).await.unwrap();
+    assert!(!consumed);
+    assert_eq!(invoices, 2);
+    assert_eq!(balance, i64::MAX - 100);
+    set_daves_balance(0).await.unwrap();
+
+    let resp = test::call_service(&app, create(200)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let balances: Vec<i64> =
+        sqlx::query_scalar("SELECT balance FROM \"user\" ORDER BY id").fetch_all(&pool).await.unwrap();
+    let details: i64 = sqlx::query_scalar("SELECT count(*) FROM invoice_details WHERE stock_id = 3")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let participants: i64 = sqlx::query_scalar("SELECT amount FROM invoice_participant WHERE user_id = 1")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(balances, [-200, 200]);
+    assert_eq!(details, 1);
+    assert_eq!(participants, 200);
+
+    let resp = test::call_service(&app, create(200)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,72 @@
+//! `/invoice/group/{group_id}` must treat its filter as data, never as SQL.
+//!
+//! These run against a throwaway database created by `sqlx::test`; point `DATABASE_URL` at a
+//! Postgres server and run `cargo test -- --ignored`.
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn report_lists_invoices_in_range(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-03-01", "end_date": "2025-03-31" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["status"], 200);
+    assert_eq!(body["data"][0]["date"], "2025-03-10");
+    assert_eq!(body["data"][0]["invoices"][0]["invoice_id"], 1);
+    assert_eq!(body["data"][0]["invoices"][0]["meal"], "Bread, Cheese");
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn malicious_dates_are_rejected(pool: PgPool) {
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    for payload in common::MALICIOUS_DATES {
+        for body in [
+            json!({ "start_date": payload, "end_date": "2025-12-31" }),
+            json!({ "start_date": "2025-01-01", "end_date": payload }),
+        ] {
+            let req = test::TestRequest::get()
+                .uri("/invoice/group/1")
+                .insert_header(common::bearer(&token))
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "payload {:?} was not rejected", payload);
+        }
+    }
+
+    let invoices: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM invoice").fetch_one(&pool).await.unwrap();
+    assert_eq!(invoices, 2);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn malicious_group_segment_is_refused(pool: PgPool) {
+    let app = test::init_service(common::app(pool)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/group/1%20OR%201=1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "start_date": "2025-01-01", "end_date": "2025-12-31" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert!(resp.status().is_client_error());
+}
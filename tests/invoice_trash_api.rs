@@ -0,0 +1,300 @@
+//! Editing, soft-deleting and restoring invoices, each undoing and redoing its balance effects.
+//!
+//! The last test runs against Postgres; point `DATABASE_URL` at a server and run
+//! `cargo test -- --ignored`.
+
+mod common;
+
+use actix_web::{dev::{Service, ServiceResponse}, http::StatusCode, test};
+use backend::domain::ledger::ledger_entity::LedgerKind;
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::PgPool;
+
+async fn current_etag<S, B>(app: &S, token: &str, id: i32) -> String
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+{
+    let req = test::TestRequest::get()
+        .uri(&format!("/invoice/{}/group/1", id))
+        .insert_header(common::bearer(token))
+        .to_request();
+    common::etag(&test::call_service(app, req).await)
+}
+
+fn new_invoice(participants: serde_json::Value) -> serde_json::Value {
+    json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": participants })
+}
+
+#[actix_web::test]
+async fn delete_reverses_balances_and_restore_reapplies_them() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(new_invoice(json!([{ "user_id": 1 }, { "user_id": 2 }])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created = common::etag(&resp);
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-360, 560));
+
+    let delete = |etag: &str| {
+        test::TestRequest::delete()
+            .uri("/invoice/3")
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, delete(&created)).await.status(), StatusCode::FORBIDDEN);
+
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+
+    let resp = test::call_service(&app, delete(&created)).await;
+    let deleted = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["deleted_by"], 1);
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-300, 500));
+    assert_eq!(db.state().ledger_transactions.last().unwrap().kind, LedgerKind::Reversal);
+    assert!(!common::consumed(&db, 3));
+    assert_eq!(test::call_service(&app, delete(&deleted)).await.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::get().uri("/invoice/group/1/trash").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = body["data"].as_array().unwrap().iter().map(|invoice| invoice["id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, [3, 2]);
+
+    let restore = |etag: &str| {
+        test::TestRequest::post()
+            .uri("/invoice/3/restore")
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .to_request()
+    };
+    let resp = test::call_service(&app, restore(&deleted)).await;
+    let restored = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["participants"].as_array().unwrap().len(), 2);
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-360, 560));
+    assert!(common::consumed(&db, 3));
+    assert_eq!(test::call_service(&app, restore(&restored)).await.status(), StatusCode::CONFLICT);
+}
+
+#[actix_web::test]
+async fn restoring_needs_the_stock_the_invoice_drew() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let create = || {
+        test::TestRequest::post()
+            .uri("/invoice")
+            .insert_header(common::bearer(&token))
+            .set_json(new_invoice(json!([{ "user_id": 1 }])))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, create()).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let req = test::TestRequest::delete()
+        .uri("/invoice/3")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&common::etag(&resp)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let deleted = common::etag(&resp);
+
+    // the bread put back goes into another invoice before the first one is restored
+    assert_eq!(test::call_service(&app, create()).await.status(), StatusCode::CREATED);
+    let balances_and_postings =
+        || ((common::balance(&db, 1), common::balance(&db, 2)), db.state().ledger_transactions.len());
+    let before = balances_and_postings();
+
+    let req = test::TestRequest::post()
+        .uri("/invoice/3/restore")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&deleted))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+    assert_eq!(balances_and_postings(), before);
+    assert!(db.state().invoices.iter().find(|invoice| invoice.id == 3).unwrap().is_deleted);
+}
+
+#[actix_web::test]
+async fn invoices_without_postings_move_nothing() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::delete()
+        .uri("/invoice/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&current_etag(&app, &token, 1).await))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::post()
+        .uri("/invoice/2/restore")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&current_etag(&app, &token, 2).await))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-300, 500));
+    assert_eq!(db.state().ledger_transactions.len(), 1);
+    assert!(db.state().invoices.iter().find(|invoice| invoice.id == 1).unwrap().is_deleted);
+    assert_eq!(db.state().invoices.iter().find(|invoice| invoice.id == 2).unwrap().deleted_by, 0);
+}
+
+#[actix_web::test]
+async fn edit_moves_balances_from_the_old_split_to_the_new_one() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let edit = |id: i32, etag: &str, body: serde_json::Value| {
+        test::TestRequest::patch()
+            .uri(&format!("/invoice/{}", id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .set_json(body)
+            .to_request()
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(new_invoice(json!([{ "user_id": 1 }])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let etag = common::etag(&resp);
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-420, 620));
+
+    let resp = test::call_service(&app, edit(3, &etag, json!({ "price": 200 }))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "price": 200, "participants": [{ "user_id": 1 }, { "user_id": 2 }] });
+    let resp = test::call_service(&app, edit(3, &etag, body)).await;
+    let etag = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["price"], 200);
+    assert_eq!(body["data"][0]["participants"], json!([{ "user_id": 1, "amount": 100 }, { "user_id": 2, "amount": 100 }]));
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-400, 600));
+
+    // same price and participants, so the postings cancel out
+    let resp = test::call_service(&app, edit(3, &etag, json!({ "supplier_id": 1 }))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = common::etag(&resp);
+    assert_eq!((common::balance(&db, 1), common::balance(&db, 2)), (-400, 600));
+
+    let resp = test::call_service(&app, edit(3, &etag, json!({ "supplier_id": 7 }))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let deleted = current_etag(&app, &token, 2).await;
+    let resp = test::call_service(&app, edit(2, &deleted, json!({ "participants": [{ "user_id": 1 }] }))).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let legacy = current_etag(&app, &token, 1).await;
+    let resp = test::call_service(&app, edit(1, &legacy, json!({ "supplier_id": 1 }))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_edit_delete_and_restore_keep_balances_in_step(pool: PgPool) {
+    sqlx::query("INSERT INTO role_permission (role_id, permission) VALUES (2, 'invoice:delete')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let balances = || async {
+        sqlx::query_as::<_, (i32, i64)>("SELECT id, balance FROM \"user\" ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+    };
+    let stock = || async {
+        sqlx::query_as::<_, (Decimal, bool)>("SELECT remaining, consumed FROM stock WHERE id = 3")
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(new_invoice(json!([{ "user_id": 1 }])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let etag = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let id = body["data"][0]["id"].as_i64().unwrap();
+    assert_eq!(balances().await, [(1, -200), (2, 200)]);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/invoice/{}", id))
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .set_json(json!({ "price": 300, "split": "exact",
+                          "participants": [{ "user_id": 1, "value": 100 }, { "user_id": 2, "value": 200 }] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = common::etag(&resp);
+    assert_eq!(balances().await, [(1, -100), (2, 100)]);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/invoice/{}", id))
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = common::etag(&resp);
+    assert_eq!(balances().await, [(1, 0), (2, 0)]);
+    assert_eq!(stock().await, (Decimal::ONE, false));
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/invoice/{}/restore", id))
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = common::etag(&resp);
+    assert_eq!(balances().await, [(1, -100), (2, 100)]);
+    assert_eq!(stock().await, (Decimal::ZERO, true));
+
+    // deleted again and its stock used elsewhere, it can't come back
+    let req = test::TestRequest::delete()
+        .uri(&format!("/invoice/{}", id))
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let etag = common::etag(&resp);
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(new_invoice(json!([{ "user_id": 1 }])))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::post()
+        .uri(&format!("/invoice/{}/restore", id))
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+    assert_eq!(balances().await, [(1, -200), (2, 200)]);
+
+    let participants: Vec<(i32, i64)> =
+        sqlx::query_as("SELECT user_id, amount FROM invoice_participant WHERE invoice_id = $1 ORDER BY user_id")
+            .bind(id as i32)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(participants, [(1, 100), (2, 200)]);
+}
@@ -0,0 +1,234 @@
+//! Ledger history, adjustments and reconciliation against the in-memory repositories,
+//! plus the balance check and settlement payments in Postgres (run those with `cargo test -- --ignored`).
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[actix_web::test]
+async fn history_lists_postings_with_running_balance() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [{ "user_id": 1 }] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/ledger/user/1/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    let history = body["data"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["kind"], "adjustment");
+    assert_eq!(history[0]["balance"], -300);
+    assert_eq!(history[1]["kind"], "invoice");
+    assert_eq!(history[1]["invoice_id"], 3);
+    assert_eq!(history[1]["amount"], -120);
+    assert_eq!(history[1]["balance"], -420);
+
+    let req = test::TestRequest::get().uri("/ledger/user/2/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][1]["amount"], 120);
+    assert_eq!(body["data"][1]["balance"], 620);
+}
+
+#[actix_web::test]
+async fn history_of_another_groups_user_is_not_found() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get().uri("/ledger/user/4/group/1").insert_header(common::bearer(&token)).to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn adjustments_must_balance_and_need_group_admin() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let adjustment = |entries: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/ledger/adjustment")
+            .insert_header(common::bearer(&token))
+            .set_json(json!({ "description": "Forgotten coffee", "entries": entries }))
+            .to_request()
+    };
+    let balanced = json!([{ "user_id": 1, "amount": 50 }, { "amount": -50 }]);
+
+    let resp = test::call_service(&app, adjustment(balanced.clone())).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+
+    // an admin still needs a confirmed address to move money
+    db.state().users[0].email_confirmed = false;
+    let resp = test::call_service(&app, adjustment(balanced.clone())).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    db.state().users[0].email_confirmed = true;
+
+    let resp = test::call_service(&app, adjustment(json!([{ "user_id": 1, "amount": 50 }, { "amount": -40 }]))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, adjustment(json!([{ "user_id": 4, "amount": 50 }, { "amount": -50 }]))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = test::call_service(&app, adjustment(balanced)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["kind"], "adjustment");
+    assert_eq!(db.state().users[0].balance, -250);
+}
+
+#[actix_web::test]
+async fn reconcile_reports_and_repairs_drifted_balances() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+    // writes that bypassed the ledger
+    db.state().users[1].balance = 0;
+    db.state().suppliers[0].balance = 75;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let drift = json!([
+        { "account": "user", "id": 2, "balance": 0, "ledger_balance": 500 },
+        { "account": "supplier", "id": 1, "balance": 75, "ledger_balance": 0 },
+    ]);
+
+    let req = test::TestRequest::get().uri("/ledger/reconcile").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"], drift);
+
+    let req = test::TestRequest::post().uri("/ledger/reconcile").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"], drift);
+    assert_eq!(db.state().users[1].balance, 500);
+    assert_eq!(db.state().suppliers[0].balance, 0);
+
+    let req = test::TestRequest::get().uri("/ledger/reconcile").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"], json!([]));
+}
+
+#[actix_web::test]
+async fn settlement_is_planned_and_booked_as_payments() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/ledger/settlement/group/1")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    // Erin is inactive, so Dave keeps the part of his credit she owes
+    let plan = json!([{ "from_user_id": 1, "to_user_id": 2, "amount": 300 }]);
+    assert_eq!(body["data"][0]["transfers"], plan);
+    assert_eq!(body["data"][0]["unsettled"], json!([{ "user_id": 2, "balance": 200 }]));
+
+    let record = |transfers: &serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/ledger/settlement")
+            .insert_header(common::bearer(&token))
+            .set_json(json!({ "transfers": transfers }))
+            .to_request()
+    };
+
+    assert_eq!(test::call_service(&app, record(&plan)).await.status(), StatusCode::FORBIDDEN);
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+    db.state().users[0].email_confirmed = false;
+    assert_eq!(test::call_service(&app, record(&plan)).await.status(), StatusCode::FORBIDDEN);
+    db.state().users[0].email_confirmed = true;
+
+    let stale = json!([{ "from_user_id": 1, "to_user_id": 2, "amount": 250 }]);
+    assert_eq!(test::call_service(&app, record(&stale)).await.status(), StatusCode::CONFLICT);
+
+    let resp = test::call_service(&app, record(&plan)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["kind"], "payment");
+    assert_eq!(body["data"][0]["description"], "Settlement: Alice paid Dave");
+    assert_eq!(db.state().users[0].balance, 0);
+    assert_eq!(db.state().users[1].balance, 200);
+
+    let req = test::TestRequest::get().uri("/payment/group/1").insert_header(common::bearer(&token)).to_request();
+    let payments: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["payment_id"], payments["data"][0]["id"]);
+    assert_eq!(payments["data"][0]["status"], "confirmed");
+    assert_eq!((&payments["data"][0]["payer_id"], &payments["data"][0]["payee_id"]), (&json!(1), &json!(2)));
+    assert_eq!(payments["data"][0]["amount"], 300);
+
+    assert_eq!(test::call_service(&app, record(&json!([]))).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_keeps_every_transaction_balanced(pool: PgPool) {
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "meal_id": 1, "supplier_id": 1, "stock_ids": [3], "participants": [{ "user_id": 1 }] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let entries: Vec<(Option<i32>, i64)> =
+        sqlx::query_as("SELECT user_id, amount FROM ledger_entry ORDER BY amount").fetch_all(&pool).await.unwrap();
+    assert_eq!(entries, [(Some(1), -200), (Some(2), 200)]);
+
+    let req = test::TestRequest::get().uri("/ledger/user/1/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["balance"], -200);
+
+    let unbalanced = sqlx::query(
+        "INSERT INTO ledger_entry (transaction_id, user_id, amount)
+         SELECT id, 1, 10 FROM ledger_transaction LIMIT 1",
+    )
+    .execute(&pool)
+    .await;
+    assert!(unbalanced.is_err());
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn settlement_is_booked_as_confirmed_payments_in_postgres(pool: PgPool) {
+    sqlx::query("INSERT INTO role_permission (role_id, permission) VALUES (2, 'group:admin')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE \"user\" SET balance = balance - 300 WHERE id = 1").execute(&pool).await.unwrap();
+    sqlx::query("UPDATE \"user\" SET balance = balance + 300 WHERE id = 2").execute(&pool).await.unwrap();
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/ledger/settlement/group/1")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/ledger/settlement")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "transfers": body["data"][0]["transfers"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let payments: Vec<(i32, i32, i64, String)> = sqlx::query_as(
+        "SELECT p.payer_id, p.payee_id, p.amount, p.status
+         FROM payment p JOIN ledger_transaction t ON t.payment_id = p.id",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let balances: Vec<i64> =
+        sqlx::query_scalar("SELECT balance FROM \"user\" ORDER BY id").fetch_all(&pool).await.unwrap();
+    assert_eq!(payments, [(1, 2, 300, "confirmed".to_string())]);
+    assert_eq!(balances, [0, 0]);
+}
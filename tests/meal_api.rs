@@ -0,0 +1,145 @@
+//! Meal composition endpoints against the in-memory repositories.
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+
+fn product_names(meal: &serde_json::Value) -> Vec<&str> {
+    meal["products"].as_array().unwrap().iter().map(|p| p["name"].as_str().unwrap()).collect()
+}
+
+#[actix_web::test]
+async fn create_meal_keeps_product_order() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/meal/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({ "name": "Cheese toast", "description": "Hot", "product_ids": [2, 1] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    let meal = &body["data"][0];
+    assert_eq!(meal["name"], "Cheese toast");
+    assert_eq!(meal["group_id"], 1);
+    assert_eq!(product_names(meal), ["Cheese", "Bread"]);
+
+    let req = test::TestRequest::get().uri("/meal/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<_> = body["data"].as_array().unwrap().iter().map(|m| m["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Cheese toast", "Sandwich"]);
+}
+
+#[actix_web::test]
+async fn create_meal_rejects_foreign_and_duplicate_products() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    for product_ids in [json!([1, 3]), json!([1, 1]), json!([])] {
+        let req = test::TestRequest::post()
+            .uri("/meal/group/1")
+            .insert_header(common::bearer(&token))
+            .set_json(json!({ "name": "Broken", "product_ids": product_ids }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "product_ids {}", product_ids);
+    }
+}
+
+#[actix_web::test]
+async fn products_are_added_at_position_and_removed() {
+    let db = common::seeded_database();
+    db.state().products.push(backend::domain::product::product_entity::Product {
+        id: 4,
+        name: "Butter".to_string(),
+        group_id: 1,
+    });
+    let app = test::init_service(common::in_memory_app(db)).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/meal/1/group/1/product")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "product_id": 4, "position": 1 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let etag = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(product_names(&body["data"][0]), ["Bread", "Butter", "Cheese"]);
+
+    let req = test::TestRequest::post()
+        .uri("/meal/1/group/1/product")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .set_json(json!({ "product_id": 4 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::delete()
+        .uri("/meal/1/group/1/product/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(product_names(&body["data"][0]), ["Butter", "Cheese"]);
+
+    let req = test::TestRequest::get().uri("/meal/1/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(product_names(&body["data"][0]), ["Butter", "Cheese"]);
+}
+
+#[actix_web::test]
+async fn last_product_cannot_be_removed() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::delete()
+        .uri("/meal/1/group/1/product/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::delete()
+        .uri("/meal/1/group/1/product/2")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&common::etag(&resp)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn meals_of_another_group_are_hidden() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "bob@example.com").await;
+
+    let req = test::TestRequest::get().uri("/meal/1/group/1").insert_header(common::bearer(&token)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::get().uri("/meal/1/group/2").insert_header(common::bearer(&token)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn invoices_by_meal_skip_deleted_ones() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/meal/1/group/1")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["status"], 200);
+    let ids: Vec<_> = body["data"].as_array().unwrap().iter().map(|i| i["id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, [1]);
+    assert_eq!(body["data"][0]["price"], 500);
+}
@@ -0,0 +1,433 @@
+//! Order lifecycle and billing against the in-memory repositories, plus both against Postgres
+//! (run those with `cargo test -- --ignored`).
+
+mod common;
+
+use actix_web::{
+    dev::{Service, ServiceResponse},
+    http::StatusCode,
+    test,
+};
+use backend::domain::{
+    customer::customer_entity::Customer, ledger::ledger_entity::LedgerKind,
+    order_details::order_details_entity::OrderDetails,
+    product::product_entity::Product,
+    stock::stock_entity::{Stock, Unit},
+    supplier::supplier_entity::Supplier,
+};
+use chrono::Duration;
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::PgPool;
+
+/// Creates an order and walks it to fulfilled; returns its id and ETag.
+async fn fulfilled_order<S, B>(app: &S, token: &str) -> (i64, String)
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: actix_web::body::MessageBody,
+{
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(token)).to_request();
+    let resp = test::call_service(app, req).await;
+    let mut etag = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let id = body["data"][0]["id"].as_i64().unwrap();
+
+    for action in ["open", "lock", "fulfill"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/{}", id, action))
+            .insert_header(common::bearer(token))
+            .insert_header(common::if_match(&etag))
+            .to_request();
+        let resp = test::call_service(app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", action);
+        etag = common::etag(&resp);
+    }
+
+    (id, etag)
+}
+
+fn line(id: i32, stock_id: i32, customer_id: Option<i32>) -> OrderDetails {
+    OrderDetails { id, order_id: 1, stock_id, customer_id, quantity: None }
+}
+
+#[actix_web::test]
+async fn order_moves_through_its_states_and_logs_each_step() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(&token)).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let mut etag = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["status"], "draft");
+    let id = body["data"][0]["id"].as_i64().unwrap();
+
+    for (action, status) in [("open", "open"), ("lock", "locked"), ("fulfill", "fulfilled")] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/{}", id, action))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(&etag))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", action);
+        etag = common::etag(&resp);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["data"][0]["status"], status);
+    }
+
+    let logs: Vec<String> = db.state().system_logs.iter().map(|log| log.transaction_type.clone()).collect();
+    assert_eq!(logs, ["ORDER_OPEN", "ORDER_LOCKED", "ORDER_FULFILLED"]);
+    assert!(db.state().system_logs.iter().all(|log| log.user_id == 1 && log.group_id == 1));
+
+    let req = test::TestRequest::get().uri("/order/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["status"], "fulfilled");
+}
+
+#[actix_web::test]
+async fn transitions_the_state_does_not_allow_are_refused() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(&token)).to_request();
+    let resp = test::call_service(&app, req).await;
+    let draft = common::etag(&resp);
+
+    let act = |action: &str, etag: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/order/1/group/1/{}", action))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .set_json(json!({ "meal_id": 1 }))
+            .to_request()
+    };
+
+    assert_eq!(test::call_service(&app, act("lock", &draft)).await.status(), StatusCode::CONFLICT);
+    assert_eq!(test::call_service(&app, act("invoice", &draft)).await.status(), StatusCode::CONFLICT);
+
+    let resp = test::call_service(&app, act("cancel", &draft)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let cancelled = common::etag(&resp);
+
+    // a client that still saw the draft is told to reload, one that saw the cancellation can't reopen
+    assert_eq!(test::call_service(&app, act("open", &draft)).await.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(test::call_service(&app, act("open", &cancelled)).await.status(), StatusCode::CONFLICT);
+
+    let logs: Vec<String> = db.state().system_logs.iter().map(|log| log.transaction_type.clone()).collect();
+    assert_eq!(logs, ["ORDER_CANCELLED"]);
+}
+
+#[actix_web::test]
+async fn orders_of_another_group_are_hidden() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let bob = common::login(&app, "bob@example.com").await;
+
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(&alice)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/order/1/group/1").insert_header(common::bearer(&bob)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::post()
+        .uri("/order/1/group/2/open")
+        .insert_header(common::bearer(&bob))
+        .insert_header(common::if_match("\"1\""))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn fulfilled_order_is_billed_once_per_supplier() {
+    let db = common::seeded_database();
+    {
+        let mut state = db.state();
+        state.suppliers.push(Supplier { id: 2, balance: 0, user_id: 1 });
+        state.stocks[2].supplier_id = Some(1);
+        state.stocks[3].supplier_id = Some(2);
+        state.stocks.push(Stock { supplier_id: Some(1), ..common::stock(5, 50, false, 1) });
+        state.customers = vec![Customer { id: 1, user_id: 1, balance: 0 }, Customer { id: 2, user_id: 2, balance: 0 }];
+    }
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let (id, etag) = fulfilled_order(&app, &token).await;
+    db.state().order_details = vec![line(1, 3, Some(1)), line(2, 4, Some(2)), line(3, 5, Some(2))];
+
+    let bill = |etag: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/invoice", id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .set_json(json!({ "meal_id": 1 }))
+            .to_request()
+    };
+    let resp = test::call_service(&app, bill(&etag)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let billed = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    // supplier 1 (Dave) sold Alice's bread and Dave's own, supplier 2 (Alice) Dave's cheese
+    let invoices = body["data"].as_array().unwrap();
+    assert_eq!(invoices.len(), 2);
+    assert_eq!((&invoices[0]["supplier_id"], &invoices[0]["price"], &invoices[0]["order_id"]), (&json!(1), &json!(170), &json!(id)));
+    assert_eq!(invoices[0]["participants"], json!([{ "user_id": 1, "amount": 120 }, { "user_id": 2, "amount": 50 }]));
+    assert_eq!((&invoices[1]["supplier_id"], &invoices[1]["price"]), (&json!(2), &json!(181)));
+    assert_eq!(invoices[1]["participants"], json!([{ "user_id": 2, "amount": 181 }]));
+    assert_eq!(db.state().users[0].balance, -300 - 120 + 181);
+    assert_eq!(db.state().users[1].balance, 500 + 120 - 181);
+    // the same postings, seen from the supplier and customer accounts
+    assert_eq!(db.state().suppliers.iter().map(|supplier| supplier.balance).collect::<Vec<_>>(), vec![170, 181]);
+    assert_eq!(db.state().customers.iter().map(|customer| customer.balance).collect::<Vec<_>>(), vec![-120, -231]);
+    assert!(db.state().stocks.iter().filter(|stock| [3, 4, 5].contains(&stock.id)).all(|stock| stock.consumed));
+    assert_eq!(db.state().orders[0].status.as_str(), "invoiced");
+    assert_eq!(db.state().system_logs.last().unwrap().transaction_type, "ORDER_INVOICED");
+
+    // billing again is refused, whichever version the client holds
+    assert_eq!(test::call_service(&app, bill(&billed)).await.status(), StatusCode::CONFLICT);
+    assert_eq!(test::call_service(&app, bill(&etag)).await.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(db.state().invoices.len(), 4);
+}
+
+#[actix_web::test]
+async fn members_sharing_a_lot_are_billed_for_their_part() {
+    let db = common::seeded_database();
+    {
+        let mut state = db.state();
+        state.products.push(Product { id: 4, name: "Butter".to_string(), group_id: 1 });
+        state.stocks.push(Stock { supplier_id: Some(1), unit: Unit::Kilogram, ..common::stock(5, 1000, false, 4) });
+    }
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(&alice)).to_request();
+    let etag = common::etag(&test::call_service(&app, req).await);
+    let req = test::TestRequest::post()
+        .uri("/order/1/group/1/open")
+        .insert_header(common::bearer(&alice))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let add = |token: &str, quantity: f64| {
+        test::TestRequest::post()
+            .uri("/order/1/group/1/item")
+            .insert_header(common::bearer(token))
+            .set_json(json!({ "stock_id": 5, "quantity": quantity }))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, add(&alice, 0.2)).await.status(), StatusCode::CREATED);
+    assert_eq!(test::call_service(&app, add(&dave, 0.3)).await.status(), StatusCode::CREATED);
+    assert_eq!(test::call_service(&app, add(&alice, 0.1)).await.status(), StatusCode::CONFLICT);
+
+    // adding items moved the order on
+    let req = test::TestRequest::get().uri("/order/1/group/1").insert_header(common::bearer(&alice)).to_request();
+    let mut etag = common::etag(&test::call_service(&app, req).await);
+    for action in ["lock", "fulfill"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/order/1/group/1/{}", action))
+            .insert_header(common::bearer(&alice))
+            .insert_header(common::if_match(&etag))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", action);
+        etag = common::etag(&resp);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/order/1/group/1/invoice")
+        .insert_header(common::bearer(&alice))
+        .insert_header(common::if_match(&etag))
+        .set_json(json!({ "meal_id": 1 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    // one draw of half a kilo, shared out by what each of them ordered
+    let invoices = body["data"].as_array().unwrap();
+    assert_eq!(invoices.len(), 1);
+    assert_eq!(invoices[0]["price"], 500);
+    assert_eq!(invoices[0]["participants"], json!([{ "user_id": 1, "amount": 200 }, { "user_id": 2, "amount": 300 }]));
+    let state = db.state();
+    assert_eq!(state.invoice_details.iter().filter(|detail| detail.stock_id == 5).count(), 1);
+    assert_eq!(state.stocks[4].remaining, Decimal::new(5, 1));
+    assert!(!state.stocks[4].consumed);
+    assert_eq!(state.users[0].balance, -300 - 200);
+    assert_eq!(state.users[1].balance, 500 + 200);
+}
+
+#[actix_web::test]
+async fn orders_are_billed_from_the_oldest_lot_first() {
+    let db = common::seeded_database();
+    {
+        let mut state = db.state();
+        state.products.push(Product { id: 4, name: "Butter".to_string(), group_id: 1 });
+        state.stocks.push(Stock { supplier_id: Some(1), ..common::stock(5, 100, false, 4) });
+        let newer = common::stock(6, 300, false, 4);
+        state.stocks.push(Stock {
+            supplier_id: Some(1),
+            purchased_date: newer.purchased_date + Duration::days(7),
+            ..newer
+        });
+        state.customers = vec![Customer { id: 1, user_id: 1, balance: 0 }, Customer { id: 2, user_id: 2, balance: 0 }];
+    }
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let (id, etag) = fulfilled_order(&app, &token).await;
+
+    let bill = || {
+        test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/invoice", id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(&etag))
+            .set_json(json!({ "meal_id": 1 }))
+            .to_request()
+    };
+
+    // the week-old butter goes before this week's
+    db.state().order_details = vec![line(1, 6, Some(1))];
+    assert_eq!(test::call_service(&app, bill()).await.status(), StatusCode::CONFLICT);
+    assert!(db.state().invoices.iter().all(|invoice| invoice.order_id.is_none()));
+
+    db.state().order_details = vec![line(1, 6, Some(1)), line(2, 5, Some(2))];
+    let resp = test::call_service(&app, bill()).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["price"], 400);
+    assert_eq!(
+        body["data"][0]["participants"],
+        json!([{ "user_id": 1, "amount": 300 }, { "user_id": 2, "amount": 100 }])
+    );
+    assert!(db.state().stocks.iter().filter(|stock| stock.product_id == 4).all(|stock| stock.consumed));
+}
+
+#[actix_web::test]
+async fn billing_an_order_is_all_or_nothing() {
+    let db = common::seeded_database();
+    {
+        let mut state = db.state();
+        for stock in state.stocks.iter_mut() {
+            stock.supplier_id = Some(1);
+        }
+        state.customers = vec![Customer { id: 1, user_id: 1, balance: 0 }, Customer { id: 2, user_id: 3, balance: 0 }];
+    }
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let (id, etag) = fulfilled_order(&app, &token).await;
+
+    let bill = || {
+        test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/invoice", id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(&etag))
+            .set_json(json!({ "meal_id": 1 }))
+            .to_request()
+    };
+
+    let cases = [
+        (vec![], StatusCode::BAD_REQUEST),
+        (vec![line(1, 3, Some(1)), line(2, 4, None)], StatusCode::BAD_REQUEST),
+        // Erin is no longer active
+        (vec![line(1, 3, Some(1)), line(2, 4, Some(2))], StatusCode::BAD_REQUEST),
+        (vec![line(1, 3, Some(1)), line(2, 3, Some(1))], StatusCode::BAD_REQUEST),
+        // stock 1 already went into invoice 1
+        (vec![line(1, 3, Some(1)), line(2, 1, Some(1))], StatusCode::CONFLICT),
+    ];
+    for (lines, status) in cases {
+        db.state().order_details = lines;
+        assert_eq!(test::call_service(&app, bill()).await.status(), status);
+    }
+
+    let state = db.state();
+    assert_eq!(state.orders[0].status.as_str(), "fulfilled");
+    assert_eq!(state.invoices.len(), 2);
+    assert!(!state.stocks[2].consumed);
+    assert_eq!(state.ledger_transactions.iter().filter(|tx| tx.kind == LedgerKind::Invoice).count(), 0);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_transitions_are_guarded_and_logged(pool: PgPool) {
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(&token)).to_request();
+    let resp = test::call_service(&app, req).await;
+    let draft = common::etag(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let id = body["data"][0]["id"].as_i64().unwrap();
+
+    let open = |etag: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/open", id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .to_request()
+    };
+    let resp = test::call_service(&app, open(&draft)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let opened = common::etag(&resp);
+    assert_ne!(opened, draft);
+
+    assert_eq!(test::call_service(&app, open(&draft)).await.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(test::call_service(&app, open(&opened)).await.status(), StatusCode::CONFLICT);
+
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM \"order\" WHERE id = $1").bind(id as i32).fetch_one(&pool).await.unwrap();
+    assert_eq!(status, "open");
+    let logs: Vec<String> = sqlx::query_scalar("SELECT transaction_type FROM system_log")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(logs, ["ORDER_OPEN"]);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_bills_a_fulfilled_order_in_one_go(pool: PgPool) {
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let (id, etag) = fulfilled_order(&app, &token).await;
+
+    sqlx::raw_sql(
+        "UPDATE stock SET supplier_id = 1;
+         INSERT INTO product (id, name, group_id) VALUES (4, 'Butter', 1);
+         INSERT INTO stock (id, price, consumed, product_id, supplier_id) VALUES (4, 80, false, 4, 1);
+         INSERT INTO customer (id, user_id, balance) VALUES (1, 1, 0), (2, 2, 0);",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO order_details (order_id, stock_id, customer_id) VALUES ($1, 3, 1), ($1, 4, 2)")
+        .bind(id as i32)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/order/{}/group/1/invoice", id))
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .set_json(json!({ "meal_id": 1 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["price"], 280);
+    assert_eq!(body["data"][0]["order_id"], id);
+
+    let balances: Vec<(i32, i64)> =
+        sqlx::query_as("SELECT id, balance FROM \"user\" ORDER BY id").fetch_all(&pool).await.unwrap();
+    assert_eq!(balances, [(1, -200), (2, 200)]);
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM \"order\" WHERE id = $1").bind(id as i32).fetch_one(&pool).await.unwrap();
+    assert_eq!(status, "invoiced");
+    let consumed: Vec<bool> =
+        sqlx::query_scalar("SELECT consumed FROM stock WHERE id IN (3, 4)").fetch_all(&pool).await.unwrap();
+    assert_eq!(consumed, [true, true]);
+}
@@ -0,0 +1,358 @@
+//! Ordering rounds: opening one, members' items until the cut-off and the automatic lock, against
+//! the in-memory repositories and Postgres (run those with `cargo test -- --ignored`).
+
+mod common;
+
+use actix_web::{http::StatusCode, test, HttpServer};
+use backend::{
+    domain::order::order_status::OrderStatus,
+    infrastructure::{repositories_impl::order_repository_impl::PgOrderRepository, round_closer::close_expired_rounds},
+    interfaces::websocket::ws_server::{GroupEvent, WsServer},
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+const MANAGER_ROLE_ID: i32 = 3;
+
+fn round(cutoff: NaiveDateTime) -> serde_json::Value {
+    json!({ "date": cutoff.date(), "cutoff": cutoff })
+}
+
+#[actix_web::test]
+async fn only_managers_open_rounds_and_only_for_the_future() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let open = |cutoff: NaiveDateTime| {
+        test::TestRequest::post()
+            .uri("/order/round/group/1")
+            .insert_header(common::bearer(&token))
+            .set_json(round(cutoff))
+            .to_request()
+    };
+    let cutoff = Utc::now().naive_utc() + Duration::hours(1);
+
+    assert_eq!(test::call_service(&app, open(cutoff)).await.status(), StatusCode::FORBIDDEN);
+
+    db.state().users[0].role_id = MANAGER_ROLE_ID;
+    let resp = test::call_service(&app, open(cutoff - Duration::hours(2))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = test::call_service(&app, open(cutoff)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["status"], "open");
+    assert_eq!(body["data"][0]["created_by"], 1);
+    assert_eq!(body["data"][0]["round_date"], json!(cutoff.date()));
+
+    let state = db.state();
+    assert_eq!(state.system_logs.len(), 1);
+    assert_eq!(state.system_logs[0].transaction_type, "ORDER_OPEN");
+}
+
+#[actix_web::test]
+async fn members_manage_their_own_items_until_the_cutoff() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = MANAGER_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/order/round/group/1")
+        .insert_header(common::bearer(&alice))
+        .set_json(round(Utc::now().naive_utc() + Duration::hours(1)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let add = |token: &str, stock_id: i32| {
+        test::TestRequest::post()
+            .uri("/order/1/group/1/item")
+            .insert_header(common::bearer(token))
+            .set_json(json!({ "stock_id": stock_id }))
+            .to_request()
+    };
+    let remove = |token: &str, line_id: i32| {
+        test::TestRequest::delete()
+            .uri(&format!("/order/1/group/1/item/{}", line_id))
+            .insert_header(common::bearer(token))
+            .to_request()
+    };
+
+    db.state().users[1].email_confirmed = false;
+    assert_eq!(test::call_service(&app, add(&dave, 3)).await.status(), StatusCode::FORBIDDEN);
+    db.state().users[1].email_confirmed = true;
+
+    let resp = test::call_service(&app, add(&dave, 3)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let daves_line = body["data"][0]["id"].as_i64().unwrap() as i32;
+
+    assert_eq!(test::call_service(&app, add(&dave, 3)).await.status(), StatusCode::CONFLICT);
+    assert_eq!(test::call_service(&app, add(&alice, 1)).await.status(), StatusCode::BAD_REQUEST);
+    let req = test::TestRequest::post()
+        .uri("/order/1/group/1/item")
+        .insert_header(common::bearer(&alice))
+        .set_json(json!({ "stock_id": 4, "quantity": 2 }))
+        .to_request();
+    // only the one piece is left
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+    assert_eq!(test::call_service(&app, add(&alice, 4)).await.status(), StatusCode::CREATED);
+
+    assert_eq!(test::call_service(&app, remove(&alice, daves_line)).await.status(), StatusCode::FORBIDDEN);
+    let resp = test::call_service(&app, remove(&dave, daves_line)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["stock_id"], 4);
+
+    {
+        let state = db.state();
+        let alices_customer = state.customers.iter().find(|customer| customer.user_id == 1).unwrap().id;
+        assert_eq!(state.order_customers, [(1, alices_customer)]);
+    }
+
+    // the cut-off passes before the closer gets to it
+    db.state().orders[0].cutoff = Some(Utc::now().naive_utc() - Duration::minutes(1));
+    assert_eq!(test::call_service(&app, add(&dave, 3)).await.status(), StatusCode::CONFLICT);
+    assert_eq!(test::call_service(&app, remove(&alice, 2)).await.status(), StatusCode::CONFLICT);
+}
+
+#[actix_web::test]
+async fn only_the_creator_or_a_group_admin_moves_an_order_along() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = MANAGER_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/order/round/group/1")
+        .insert_header(common::bearer(&alice))
+        .set_json(round(Utc::now().naive_utc() + Duration::hours(1)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let etag = common::etag(&resp);
+
+    let act = |token: &str, id: i32, action: &str, etag: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/{}", id, action))
+            .insert_header(common::bearer(token))
+            .insert_header(common::if_match(etag))
+            .to_request()
+    };
+
+    // a plain member can neither close nor call off the manager's round
+    assert_eq!(test::call_service(&app, act(&dave, 1, "lock", &etag)).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(test::call_service(&app, act(&dave, 1, "cancel", &etag)).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(db.state().orders[0].status, OrderStatus::Open);
+
+    // they can still run their own order, and a group admin can step in on it
+    let req = test::TestRequest::post().uri("/order/group/1").insert_header(common::bearer(&dave)).to_request();
+    let etag = common::etag(&test::call_service(&app, req).await);
+    let resp = test::call_service(&app, act(&dave, 2, "open", &etag)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = common::etag(&resp);
+    assert_eq!(test::call_service(&app, act(&alice, 2, "cancel", &etag)).await.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn expired_rounds_are_locked_and_announced() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = MANAGER_ROLE_ID;
+    let events = Arc::new(WsServer::new());
+    let mut received = events.subscribe();
+    let app = test::init_service(common::in_memory_app_with_events(db.clone(), events.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let cutoff = Utc::now().naive_utc() + Duration::hours(1);
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/order/round/group/1")
+            .insert_header(common::bearer(&token))
+            .set_json(round(cutoff))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    let opened = |order_id| GroupEvent::OrderStatusChanged { order_id, status: OrderStatus::Open };
+    assert_eq!(received.try_recv().unwrap(), (1, opened(1)));
+    assert_eq!(received.try_recv().unwrap(), (1, opened(2)));
+
+    let locked = close_expired_rounds(cutoff - Duration::minutes(1), db.as_ref(), &events).await.unwrap();
+    assert!(locked.is_empty());
+
+    // round 2 is cancelled before its cut-off, so only round 1 is still open to lock
+    let req = test::TestRequest::get().uri("/order/2/group/1").insert_header(common::bearer(&token)).to_request();
+    let etag = common::etag(&test::call_service(&app, req).await);
+    let req = test::TestRequest::post()
+        .uri("/order/2/group/1/cancel")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match(&etag))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let event = received.try_recv().unwrap().1;
+    assert_eq!(event, GroupEvent::OrderStatusChanged { order_id: 2, status: OrderStatus::Cancelled });
+
+    let locked = close_expired_rounds(cutoff, db.as_ref(), &events).await.unwrap();
+    assert_eq!(locked.len(), 1);
+    assert_eq!(locked[0].status, OrderStatus::Locked);
+    let event = received.try_recv().unwrap();
+    assert_eq!(event, (1, GroupEvent::OrderStatusChanged { order_id: 1, status: OrderStatus::Locked }));
+    assert_eq!(
+        serde_json::to_value(&event.1).unwrap(),
+        json!({ "event": "order_status_changed", "order_id": 1, "status": "locked" })
+    );
+
+    let state = db.state();
+    let log = state.system_logs.last().unwrap();
+    assert_eq!((log.transaction_type.as_str(), log.user_id), ("ORDER_LOCKED", 1));
+}
+
+/// Opens `/ws/group/{group_id}` over `stream` the way a browser does, offering `token` as a
+/// subprotocol; returns the response head.
+async fn upgrade(stream: &mut TcpStream, group_id: i32, token: &str) -> String {
+    let request = format!(
+        "GET /ws/group/{} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\
+         Sec-WebSocket-Protocol: bearer, {}\r\n\r\n",
+        group_id, token
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0; 1];
+        if stream.read(&mut byte).await.unwrap() == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+    String::from_utf8(head).unwrap().to_lowercase()
+}
+
+/// The next frame the server sends, which has to be a short text frame.
+async fn read_text(stream: &mut TcpStream) -> String {
+    let mut header = [0; 2];
+    stream.read_exact(&mut header).await.unwrap();
+    assert_eq!(header[0], 0x81, "a final text frame");
+    let mut payload = vec![0; usize::from(header[1])];
+    stream.read_exact(&mut payload).await.unwrap();
+    String::from_utf8(payload).unwrap()
+}
+
+#[actix_web::test]
+async fn browsers_follow_their_group_over_a_websocket() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = MANAGER_ROLE_ID;
+    let events = Arc::new(WsServer::new());
+    let app = test::init_service(common::in_memory_app_with_events(db.clone(), events.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let server = {
+        let (db, events) = (db.clone(), events.clone());
+        HttpServer::new(move || common::in_memory_app_with_events(db.clone(), events.clone()))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap()
+    };
+    let address = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    let mut stranger = TcpStream::connect(address).await.unwrap();
+    assert!(upgrade(&mut stranger, 1, "not-a-token").await.starts_with("http/1.1 401"));
+    let mut outsider = TcpStream::connect(address).await.unwrap();
+    let bob = common::login(&app, "bob@example.com").await;
+    assert!(upgrade(&mut outsider, 1, &bob).await.starts_with("http/1.1 403"));
+
+    let mut socket = TcpStream::connect(address).await.unwrap();
+    let head = upgrade(&mut socket, 1, &token).await;
+    assert!(head.starts_with("http/1.1 101"), "{}", head);
+    assert!(head.contains("sec-websocket-protocol: bearer\r\n"), "{}", head);
+
+    let req = test::TestRequest::post()
+        .uri("/order/round/group/1")
+        .insert_header(common::bearer(&token))
+        .set_json(round(Utc::now().naive_utc() + Duration::hours(1)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let text = tokio::time::timeout(std::time::Duration::from_secs(5), read_text(&mut socket)).await.unwrap();
+    let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(event, json!({ "event": "order_status_changed", "order_id": 1, "status": "open" }));
+
+    handle.stop(false).await;
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_rounds_take_items_then_lock(pool: PgPool) {
+    sqlx::raw_sql(
+        "UPDATE \"user\" SET role_id = 3 WHERE id = 1;
+         INSERT INTO role_permission (role_id, permission) VALUES (3, 'group:admin');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let app = test::init_service(common::app(pool.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/order/round/group/1")
+        .insert_header(common::bearer(&alice))
+        .set_json(round(Utc::now().naive_utc() + Duration::hours(1)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let id = body["data"][0]["id"].as_i64().unwrap() as i32;
+
+    let add = |token: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/order/{}/group/1/item", id))
+            .insert_header(common::bearer(token))
+            .set_json(json!({ "stock_id": 3 }))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, add(&dave)).await.status(), StatusCode::CREATED);
+    assert_eq!(test::call_service(&app, add(&dave)).await.status(), StatusCode::CONFLICT);
+    // a lot can be shared, one line per member
+    assert_eq!(test::call_service(&app, add(&alice)).await.status(), StatusCode::CREATED);
+
+    let members: Vec<i32> = sqlx::query_scalar(
+        "SELECT c.user_id FROM order_customer oc JOIN customer c ON c.id = oc.customer_id
+         WHERE oc.order_id = $1 ORDER BY c.user_id",
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(members, [1, 2]);
+
+    sqlx::query("UPDATE \"order\" SET cutoff = now() at time zone 'utc' - interval '1 minute' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(test::call_service(&app, add(&alice)).await.status(), StatusCode::CONFLICT);
+
+    let orders = PgOrderRepository::new(pool.clone());
+    let locked = close_expired_rounds(Utc::now().naive_utc(), &orders, &WsServer::new()).await.unwrap();
+    assert_eq!(locked.len(), 1);
+
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM \"order\" WHERE id = $1").bind(id).fetch_one(&pool).await.unwrap();
+    assert_eq!(status, "locked");
+    let logs: Vec<String> =
+        sqlx::query_scalar("SELECT transaction_type FROM system_log ORDER BY id").fetch_all(&pool).await.unwrap();
+    assert_eq!(logs, ["ORDER_OPEN", "ORDER_LOCKED"]);
+}
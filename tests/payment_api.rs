@@ -0,0 +1,137 @@
+//! Payments between members against the in-memory repositories, plus the confirmation
+//! against Postgres (run that one with `cargo test -- --ignored`).
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[actix_web::test]
+async fn confirmed_payment_moves_both_balances() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/payment")
+        .insert_header(common::bearer(&alice))
+        .set_json(json!({ "payee_id": 2, "amount": 200, "method": "bank_transfer", "note": "March" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["status"], "pending");
+    let id = body["data"][0]["id"].as_i64().unwrap();
+
+    // nothing moves until the payee confirms
+    assert_eq!(db.state().users[0].balance, -300);
+    assert_eq!(db.state().users[1].balance, 500);
+
+    let confirm = |token: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/payment/{}/confirm", id))
+            .insert_header(common::bearer(token))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, confirm(&alice)).await.status(), StatusCode::FORBIDDEN);
+
+    let resp = test::call_service(&app, confirm(&dave)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["status"], "confirmed");
+    assert!(body["data"][0]["resolved_date"].is_string());
+
+    assert_eq!(db.state().users[0].balance, -100);
+    assert_eq!(db.state().users[1].balance, 300);
+    assert_eq!(db.state().ledger_transactions.last().unwrap().payment_id, Some(id as i32));
+    assert_eq!(db.state().system_logs.last().unwrap().transaction_type, "PAYMENT_CONFIRMED");
+
+    assert_eq!(test::call_service(&app, confirm(&dave)).await.status(), StatusCode::CONFLICT);
+    assert_eq!(db.state().users[0].balance, -100);
+}
+
+#[actix_web::test]
+async fn rejected_payment_leaves_balances_alone() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/payment")
+        .insert_header(common::bearer(&alice))
+        .set_json(json!({ "payee_id": 2, "amount": 50, "method": "cash" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::post().uri("/payment/1/reject").insert_header(common::bearer(&dave)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["status"], "rejected");
+    assert_eq!(db.state().users[0].balance, -300);
+    assert_eq!(db.state().ledger_transactions.len(), 1);
+    assert_eq!(db.state().system_logs.last().unwrap().transaction_type, "PAYMENT_REJECTED");
+
+    let req = test::TestRequest::post().uri("/payment/1/confirm").insert_header(common::bearer(&dave)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::get().uri("/payment/group/1").insert_header(common::bearer(&alice)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn payee_must_be_another_active_member() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let pay = |payee_id: i32, amount: i64| {
+        test::TestRequest::post()
+            .uri("/payment")
+            .insert_header(common::bearer(&token))
+            .set_json(json!({ "payee_id": payee_id, "amount": amount, "method": "cash" }))
+            .to_request()
+    };
+
+    assert_eq!(test::call_service(&app, pay(1, 100)).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, pay(3, 100)).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, pay(4, 100)).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, pay(2, 0)).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get().uri("/payment/1/group/2").insert_header(common::bearer(&token)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_posts_confirmed_payments_to_the_ledger(pool: PgPool) {
+    let app = test::init_service(common::app(pool.clone())).await;
+    let alice = common::login(&app, "alice@example.com").await;
+    let dave = common::login(&app, "dave@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/payment")
+        .insert_header(common::bearer(&alice))
+        .set_json(json!({ "payee_id": 2, "amount": 75, "method": "other" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let id = body["data"][0]["id"].as_i64().unwrap() as i32;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/payment/{}/confirm", id))
+        .insert_header(common::bearer(&dave))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let balances: Vec<(i32, i64)> =
+        sqlx::query_as("SELECT id, balance FROM \"user\" ORDER BY id").fetch_all(&pool).await.unwrap();
+    assert_eq!(balances, [(1, 75), (2, -75)]);
+    let payment_id: Option<i32> =
+        sqlx::query_scalar("SELECT payment_id FROM ledger_transaction").fetch_one(&pool).await.unwrap();
+    assert_eq!(payment_id, Some(id));
+    let logs: i64 = sqlx::query_scalar("SELECT count(*) FROM system_log WHERE transaction_type = 'PAYMENT_CONFIRMED'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(logs, 1);
+}
@@ -0,0 +1,391 @@
+//! Stock lots: purchases, recounts, partial and first-in first-out consumption by invoices and the
+//! cost of goods that follows, against the in-memory repositories and Postgres (run those with
+//! `cargo test -- --ignored`).
+
+mod common;
+
+use actix_web::{
+    dev::{Service, ServiceResponse},
+    http::StatusCode,
+    test,
+};
+use backend::{domain::role::permission::Permission, infrastructure::in_memory::InMemoryDatabase};
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::PgPool;
+use std::{str::FromStr, sync::Arc};
+
+const MANAGER_ROLE_ID: i32 = 3;
+
+fn quantity(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+/// The seeded database, where Alice's plain User role may also buy stock.
+fn stock_keeping_database() -> Arc<InMemoryDatabase> {
+    let db = common::seeded_database();
+    db.state().role_permissions.push((2, Permission::StockPurchase));
+    db
+}
+
+/// Buys `quantity` kg of cheese for `price`; returns the new lot's id.
+async fn buy_cheese<S, B>(app: &S, token: &str, price: i64, quantity: f64) -> i64
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: actix_web::body::MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/stock/group/1")
+        .insert_header(common::bearer(token))
+        .set_json(json!({ "product_id": 2, "supplier_id": 1, "price": price, "quantity": quantity, "unit": "kg" }))
+        .to_request();
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    body["data"][0]["id"].as_i64().unwrap()
+}
+
+#[actix_web::test]
+async fn purchases_add_lots_to_the_inventory() {
+    let db = stock_keeping_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let id = buy_cheese(&app, &token, 900, 1.5).await;
+    assert_eq!(id, 5);
+
+    let purchase = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/stock/group/1")
+            .insert_header(common::bearer(&token))
+            .set_json(body)
+            .to_request()
+    };
+    // another group's product, nothing bought, a made-up unit, a price out of range
+    for body in [
+        json!({ "product_id": 3, "price": 100, "quantity": 1, "unit": "piece" }),
+        json!({ "product_id": 1, "price": 100, "quantity": 0, "unit": "piece" }),
+        json!({ "product_id": 1, "price": 100, "quantity": 0.0005, "unit": "kg" }),
+        json!({ "product_id": 1, "price": 100, "quantity": 1, "unit": "crate" }),
+        json!({ "product_id": 1, "price": i64::MAX, "quantity": 1, "unit": "piece" }),
+    ] {
+        let status = test::call_service(&app, purchase(body.clone())).await.status();
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+
+    // buying needs stock:purchase, and a confirmed account
+    db.state().role_permissions.retain(|grant| *grant != (2, Permission::StockPurchase));
+    let body = json!({ "product_id": 1, "price": 100, "quantity": 1, "unit": "piece" });
+    assert_eq!(test::call_service(&app, purchase(body)).await.status(), StatusCode::FORBIDDEN);
+    db.state().role_permissions.push((2, Permission::StockPurchase));
+
+    db.state().users[0].email_confirmed = false;
+    let body = json!({ "product_id": 1, "price": 100, "quantity": 1, "unit": "piece" });
+    assert_eq!(test::call_service(&app, purchase(body)).await.status(), StatusCode::FORBIDDEN);
+    db.state().users[0].email_confirmed = true;
+
+    let req = test::TestRequest::get().uri("/stock/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = body["data"].as_array().unwrap().iter().map(|stock| stock["id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, [3, 4, 5]);
+    assert_eq!(body["data"][2]["unit"], "kg");
+    assert_eq!(body["data"][2]["quantity"], 1.5);
+    assert_eq!(body["data"][2]["remaining"], 1.5);
+
+    // the other group sees none of it
+    let bob = common::login(&app, "bob@example.com").await;
+    let req = test::TestRequest::get().uri("/stock/5/group/2").insert_header(common::bearer(&bob)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn managers_correct_counts_with_a_reason() {
+    let db = stock_keeping_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let id = buy_cheese(&app, &token, 900, 1.5).await;
+
+    let adjust = |stock_id: i64, etag: &str, remaining: f64, reason: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/stock/{}/group/1/adjust", stock_id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .set_json(json!({ "remaining": remaining, "reason": reason }))
+            .to_request()
+    };
+
+    let status = test::call_service(&app, adjust(id, "\"1\"", 1.2, "dried out")).await.status();
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    db.state().users[0].role_id = MANAGER_ROLE_ID;
+    assert_eq!(test::call_service(&app, adjust(id, "\"1\"", 1.2, "")).await.status(), StatusCode::BAD_REQUEST);
+    let status = test::call_service(&app, adjust(id, "\"1\"", -1.0, "dried out")).await.status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // more than was bought can't turn up in a recount
+    let status = test::call_service(&app, adjust(id, "\"1\"", 1.6, "found some")).await.status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let resp = test::call_service(&app, adjust(id, "\"1\"", 1.2, "dried out")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::etag(&resp), "\"2\"");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["remaining"], 1.2);
+    assert_eq!(body["data"][0]["quantity"], 1.5);
+
+    // a recount based on what the lot looked like before is refused
+    let status = test::call_service(&app, adjust(id, "\"1\"", 1.0, "dried out more")).await.status();
+    assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+    let resp = test::call_service(&app, adjust(4, "\"1\"", 0.0, "eaten without an invoice")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["consumed"], true);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/stock/{}/group/1/adjustment", id))
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["change"], -0.3);
+    assert_eq!(body["data"][0]["reason"], "dried out");
+    assert_eq!(body["data"][0]["user_id"], 1);
+
+    let req = test::TestRequest::get().uri("/stock/group/1").insert_header(common::bearer(&token)).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = body["data"].as_array().unwrap().iter().map(|stock| stock["id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, [3, id]);
+}
+
+#[actix_web::test]
+async fn invoices_can_use_part_of_a_lot() {
+    let db = stock_keeping_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let id = buy_cheese(&app, &token, 1000, 2.0).await;
+
+    // the stock part of the body varies, the rest doesn't
+    let invoice = |stock: serde_json::Value| {
+        let mut body = json!({ "meal_id": 1, "supplier_id": 1, "participants": [{ "user_id": 1 }] });
+        body.as_object_mut().unwrap().extend(stock.as_object().unwrap().clone());
+        test::TestRequest::post().uri("/invoice").insert_header(common::bearer(&token)).set_json(body).to_request()
+    };
+
+    let resp = test::call_service(&app, invoice(json!({ "stock_uses": [{ "stock_id": id, "quantity": 0.5 }] }))).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["price"], 250);
+    {
+        let state = db.state();
+        let lot = state.stocks.iter().find(|stock| stock.id == id as i32).unwrap();
+        assert_eq!((lot.remaining, lot.consumed), (quantity("1.5"), false));
+        assert_eq!(state.invoice_details.last().unwrap().quantity, quantity("0.5"));
+    }
+
+    let resp = test::call_service(&app, invoice(json!({ "stock_uses": [{ "stock_id": id, "quantity": 2 }] }))).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let resp = test::call_service(&app, invoice(json!({ "stock_uses": [{ "stock_id": id, "quantity": 0 }] }))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, invoice(json!({}))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // a whole row takes what is left of it, at its share of the price
+    let resp = test::call_service(&app, invoice(json!({ "stock_ids": [id, 3] }))).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["price"], 750 + 120);
+    let state = db.state();
+    let lot = state.stocks.iter().find(|stock| stock.id == id as i32).unwrap();
+    assert_eq!((lot.remaining, lot.consumed), (Decimal::ZERO, true));
+}
+
+#[actix_web::test]
+async fn products_are_drawn_from_the_oldest_lots_first() {
+    let db = stock_keeping_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let older = buy_cheese(&app, &token, 1000, 2.0).await;
+    let newer = buy_cheese(&app, &token, 1500, 2.0).await;
+
+    let invoice = |body: serde_json::Value| {
+        let mut base = json!({ "meal_id": 1, "supplier_id": 1, "participants": [{ "user_id": 1 }] });
+        base.as_object_mut().unwrap().extend(body.as_object().unwrap().clone());
+        test::TestRequest::post().uri("/invoice").insert_header(common::bearer(&token)).set_json(base).to_request()
+    };
+    let cheese = |quantity: f64| json!({ "product_uses": [{ "product_id": 2, "quantity": quantity }] });
+
+    // the seeded single piece of cheese can't be counted in kg
+    assert_eq!(test::call_service(&app, invoice(cheese(3.0))).await.status(), StatusCode::BAD_REQUEST);
+    db.state().stocks.retain(|stock| stock.id != 4);
+    // nor can the newer lot be picked while the older one has cheese left
+    let resp = test::call_service(&app, invoice(json!({ "stock_uses": [{ "stock_id": newer, "quantity": 0.5 }] })));
+    assert_eq!(resp.await.status(), StatusCode::CONFLICT);
+
+    let resp = test::call_service(&app, invoice(cheese(3.0))).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let first = body["data"][0]["id"].as_i64().unwrap();
+    assert_eq!(body["data"][0]["price"], 1000 + 750);
+    assert_eq!(test::call_service(&app, invoice(cheese(1.5))).await.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/invoice/{}/group/1/cost", first))
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["cost_of_goods"], 1750);
+    let lots: Vec<(i64, f64, i64)> = body["data"][0]["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|lot| {
+            (lot["stock_id"].as_i64().unwrap(), lot["quantity"].as_f64().unwrap(), lot["cost"].as_i64().unwrap())
+        })
+        .collect();
+    assert_eq!(lots, [(older, 2.0, 1000), (newer, 1.0, 750)]);
+
+    // a lot picked by hand and drawn for its product again ends up on one row, charged at a set price
+    let body = json!({
+        "stock_uses": [{ "stock_id": newer, "quantity": 0.5 }],
+        "product_uses": [{ "product_id": 2, "quantity": 0.5 }],
+        "price": 2000
+    });
+    let resp = test::call_service(&app, invoice(body)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    {
+        let state = db.state();
+        let row = state.invoice_details.last().unwrap();
+        assert_eq!((row.stock_id, row.quantity, row.cost), (newer as i32, Decimal::ONE, 750));
+        assert!(state.stocks.iter().find(|stock| stock.id == newer as i32).unwrap().consumed);
+    }
+
+    // the seeded invoice counts, the deleted one doesn't
+    let req = test::TestRequest::get()
+        .uri("/invoice/meal/1/group/1/cost")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["invoices"].as_array().unwrap().len(), 3);
+    assert_eq!(body["data"][0]["price"], 500 + 1750 + 2000);
+    assert_eq!(body["data"][0]["cost_of_goods"], 500 + 1750 + 750);
+
+    let bob = common::login(&app, "bob@example.com").await;
+    let req = test::TestRequest::get()
+        .uri(&format!("/invoice/{}/group/2/cost", first))
+        .insert_header(common::bearer(&bob))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_lots_are_drawn_down_and_recounted(pool: PgPool) {
+    sqlx::raw_sql(
+        "UPDATE \"user\" SET role_id = 3 WHERE id = 1;
+         INSERT INTO role_permission (role_id, permission) VALUES (3, 'invoice:create'), (3, 'group:admin'), (3, 'stock:purchase');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    // a second lot of a product that is already stocked
+    let id = buy_cheese(&app, &token, 1000, 2.0).await as i32;
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "meal_id": 1, "supplier_id": 1, "stock_uses": [{ "stock_id": id, "quantity": 0.75 }],
+            "participants": [{ "user_id": 1 }]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["price"], 375);
+
+    // the invoice drew the lot down, so the version seen at purchase is stale
+    let adjust = |etag: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/stock/{}/group/1/adjust", id))
+            .insert_header(common::bearer(&token))
+            .insert_header(common::if_match(etag))
+            .set_json(json!({ "remaining": 1, "reason": "recounted" }))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, adjust("\"1\"")).await.status(), StatusCode::PRECONDITION_FAILED);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/stock/{}/group/1", id))
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let etag = common::etag(&test::call_service(&app, req).await);
+    assert_eq!(test::call_service(&app, adjust(&etag)).await.status(), StatusCode::OK);
+
+    let (remaining, consumed): (Decimal, bool) =
+        sqlx::query_as("SELECT remaining, consumed FROM stock WHERE id = $1").bind(id).fetch_one(&pool).await.unwrap();
+    assert_eq!((remaining, consumed), (Decimal::ONE, false));
+    let used: Decimal = sqlx::query_scalar("SELECT quantity FROM invoice_details WHERE stock_id = $1")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(used, quantity("0.75"));
+    let change: Decimal = sqlx::query_scalar("SELECT change FROM stock_adjustment WHERE stock_id = $1")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(change, quantity("-0.25"));
+}
+
+#[sqlx::test(fixtures("invoices"))]
+#[ignore = "requires a Postgres server at DATABASE_URL"]
+async fn postgres_records_the_lots_a_product_was_drawn_from(pool: PgPool) {
+    sqlx::query("INSERT INTO role_permission (role_id, permission) VALUES (2, 'stock:purchase')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = test::init_service(common::app(pool.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let older = buy_cheese(&app, &token, 1000, 2.0).await as i32;
+    let newer = buy_cheese(&app, &token, 1500, 2.0).await as i32;
+
+    let req = test::TestRequest::post()
+        .uri("/invoice")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "meal_id": 1, "supplier_id": 1, "product_uses": [{ "product_id": 2, "quantity": 2.5 }],
+            "participants": [{ "user_id": 1 }]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let id = body["data"][0]["id"].as_i64().unwrap() as i32;
+    assert_eq!(body["data"][0]["price"], 1375);
+
+    let rows: Vec<(i32, Decimal, i64)> =
+        sqlx::query_as("SELECT stock_id, quantity, cost FROM invoice_details WHERE invoice_id = $1 ORDER BY id")
+            .bind(id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(rows, [(older, quantity("2"), 1000), (newer, quantity("0.5"), 375)]);
+    let remaining: Vec<Decimal> = sqlx::query_scalar("SELECT remaining FROM stock WHERE id = ANY($1) ORDER BY id")
+        .bind([older, newer])
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, [Decimal::ZERO, quantity("1.5")]);
+
+    let req = test::TestRequest::get()
+        .uri("/invoice/meal/1/group/1/cost")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"][0]["price"], 500 + 1375);
+    assert_eq!(body["data"][0]["cost_of_goods"], 500 + 1375);
+}
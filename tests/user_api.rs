@@ -0,0 +1,303 @@
+//! User management endpoints, run against the in-memory repositories.
+
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+#[actix_web::test]
+async fn users_are_listed_without_passwords() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    let users = body["data"].as_array().unwrap();
+    let names: Vec<&str> = users.iter().map(|user| user["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Alice", "Dave", "Erin"]);
+    assert!(users.iter().all(|user| user.get("password").is_none()));
+}
+
+#[actix_web::test]
+async fn users_of_other_groups_are_not_found() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    for uri in ["/user/4", "/user/email/bob@example.com", "/user/display/BOB"] {
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header(common::bearer(&token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn search_matches_part_of_the_name() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::get()
+        .uri("/user/search/AV")
+        .insert_header(common::bearer(&token))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["data"][0]["name"], "Dave");
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn creating_users_needs_user_manage() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::post()
+        .uri("/user")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "name": "Frank", "password": "password123", "email": "frank@example.com",
+            "user_display_id": "FRK01", "role_id": 2
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn admin_creates_validated_user_in_own_group() {
+    let db = common::seeded_database();
+    db.state().users[1].role_id = common::ADMIN_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "dave@example.com").await;
+
+    let invalid = test::TestRequest::post()
+        .uri("/user")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "name": "Frank", "password": "short", "email": "not-an-email",
+            "user_display_id": "FRK01", "role_id": 2
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, invalid).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/user")
+        .insert_header(common::bearer(&token))
+        .set_json(json!({
+            "name": "Frank", "password": "password123", "email": "frank@example.com",
+            "user_display_id": "FRK01", "role_id": 2
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"][0]["group_id"], 1);
+    assert_eq!(body["data"][0]["balance"], 0);
+    assert!(body["data"][0].get("password").is_none());
+
+    let stored = db.state().users.iter().find(|user| user.name == "Frank").unwrap().password.clone();
+    assert!(stored.starts_with("$argon2id$"));
+}
+
+#[actix_web::test]
+async fn members_patch_their_own_profile_only() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let own = test::TestRequest::patch()
+        .uri("/user/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "name": "Alicia" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, own).await;
+    assert_eq!(body["data"][0]["name"], "Alicia");
+    assert_eq!(body["data"][0]["email"], "alice@example.com");
+
+    let promote = test::TestRequest::patch()
+        .uri("/user/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"2\""))
+        .set_json(json!({ "role_id": common::ADMIN_ROLE_ID }))
+        .to_request();
+    assert_eq!(test::call_service(&app, promote).await.status(), StatusCode::FORBIDDEN);
+
+    let other = test::TestRequest::patch()
+        .uri("/user/2")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "name": "Mallory" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, other).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn changing_own_password_needs_the_current_one() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let missing = test::TestRequest::patch()
+        .uri("/user/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "password": "new-password" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, missing).await.status(), StatusCode::BAD_REQUEST);
+
+    let ok = test::TestRequest::patch()
+        .uri("/user/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "password": "new-password", "current_password": common::PASSWORD }))
+        .to_request();
+    assert_eq!(test::call_service(&app, ok).await.status(), StatusCode::OK);
+
+    let login = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(json!({ "email": "alice@example.com", "password": "new-password" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, login).await.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn a_new_password_signs_out_the_other_sessions() {
+    let db = common::seeded_database();
+    db.state().users[0].role_id = common::ADMIN_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db)).await;
+    let sign_in = |email: &'static str| {
+        test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({ "email": email, "password": common::PASSWORD }))
+            .to_request()
+    };
+    let refresh = |refresh_token: &str| {
+        test::TestRequest::post()
+            .uri("/auth/refresh")
+            .set_json(json!({ "refresh_token": refresh_token }))
+            .to_request()
+    };
+    let tokens = |body: serde_json::Value| {
+        let token = |name: &str| body["data"][0][name].as_str().unwrap().to_string();
+        (token("access_token"), token("refresh_token"))
+    };
+
+    // Alice changes her own password on her laptop; her phone is signed out, the laptop is not
+    let (_, phone) = tokens(test::call_and_read_body_json(&app, sign_in("alice@example.com")).await);
+    let (laptop, laptop_refresh) = tokens(test::call_and_read_body_json(&app, sign_in("alice@example.com")).await);
+    let req = test::TestRequest::patch()
+        .uri("/user/1")
+        .insert_header(common::bearer(&laptop))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "password": "new-password", "current_password": common::PASSWORD }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(test::call_service(&app, refresh(&phone)).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(test::call_service(&app, refresh(&laptop_refresh)).await.status(), StatusCode::OK);
+
+    // a manager setting Dave's password signs Dave out everywhere
+    let (_, dave) = tokens(test::call_and_read_body_json(&app, sign_in("dave@example.com")).await);
+    let req = test::TestRequest::patch()
+        .uri("/user/2")
+        .insert_header(common::bearer(&laptop))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "password": "reset-by-alice" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(test::call_service(&app, refresh(&dave)).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn sessions_are_marked_seen_at_most_once_a_minute() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+    let list = || test::TestRequest::get().uri("/user").insert_header(common::bearer(&token)).to_request();
+
+    let recently = Utc::now().naive_utc() - Duration::seconds(30);
+    db.state().active_sessions[0].last_seen_date = recently;
+    assert_eq!(test::call_service(&app, list()).await.status(), StatusCode::OK);
+    assert_eq!(db.state().active_sessions[0].last_seen_date, recently);
+
+    let a_while_ago = Utc::now().naive_utc() - Duration::minutes(5);
+    db.state().active_sessions[0].last_seen_date = a_while_ago;
+    assert_eq!(test::call_service(&app, list()).await.status(), StatusCode::OK);
+    assert!(db.state().active_sessions[0].last_seen_date > recently);
+}
+
+#[actix_web::test]
+async fn changing_email_requires_confirming_it_again() {
+    let db = common::seeded_database();
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let token = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::patch()
+        .uri("/user/1")
+        .insert_header(common::bearer(&token))
+        .insert_header(common::if_match("\"1\""))
+        .set_json(json!({ "email": "alice@example.org" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["data"][0]["email_confirmed"], false);
+    assert_eq!(db.state().email_confirmation_tokens.iter().filter(|token| token.user_id == 1).count(), 1);
+}
+
+#[actix_web::test]
+async fn delete_deactivates_and_signs_the_user_out() {
+    let db = common::seeded_database();
+    db.state().users[1].role_id = common::ADMIN_ROLE_ID;
+    let app = test::init_service(common::in_memory_app(db.clone())).await;
+    let admin = common::login(&app, "dave@example.com").await;
+    let alice = common::login(&app, "alice@example.com").await;
+
+    let req = test::TestRequest::delete()
+        .uri("/user/1")
+        .insert_header(common::bearer(&admin))
+        .insert_header(common::if_match("\"1\""))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let alice_row = db.state().users.iter().find(|user| user.id == 1).cloned().unwrap();
+    assert!(!alice_row.is_active);
+
+    let req = test::TestRequest::get()
+        .uri("/user")
+        .insert_header(common::bearer(&alice))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+
+    let own = test::TestRequest::delete()
+        .uri("/user/2")
+        .insert_header(common::bearer(&admin))
+        .insert_header(common::if_match("\"1\""))
+        .to_request();
+    assert_eq!(test::call_service(&app, own).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn password_resets_are_rate_limited_whether_or_not_the_account_exists() {
+    let app = test::init_service(common::in_memory_app(common::seeded_database())).await;
+
+    for email in ["alice@example.com", "nobody@example.com"] {
+        let forgot = || {
+            test::TestRequest::post()
+                .uri("/auth/forgot-password")
+                .set_json(json!({ "email": email }))
+                .to_request()
+        };
+        for _ in 0..3 {
+            assert_eq!(test::call_service(&app, forgot()).await.status(), StatusCode::OK);
+        }
+        assert_eq!(test::call_service(&app, forgot()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}